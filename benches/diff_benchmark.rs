@@ -0,0 +1,118 @@
+use std::fmt::Write;
+
+#[cfg(feature = "bench-fixtures")]
+use criterion::BenchmarkId;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+#[cfg(feature = "bench-fixtures")]
+use termdiff::{fixtures, testgen::Generator, Algorithm};
+use termdiff::{ArrowsTheme, DrawDiff};
+
+fn lines(count: usize) -> String {
+    (0..count).fold(String::new(), |mut text, index| {
+        let _ = writeln!(text, "let value_{index} = {index};");
+        text
+    })
+}
+
+fn bench_identical(c: &mut Criterion) {
+    let text = lines(10_000);
+    let theme = ArrowsTheme::default();
+
+    c.bench_function("identical 10k lines", |b| {
+        b.iter(|| {
+            format!(
+                "{}",
+                DrawDiff::new(black_box(&text), black_box(&text), &theme)
+            )
+        });
+    });
+}
+
+fn bench_mostly_identical(c: &mut Criterion) {
+    let old = lines(10_000);
+    let new = old.replacen("let value_5000 = 5000;", "let value_5000 = 0;", 1);
+    let theme = ArrowsTheme::default();
+
+    c.bench_function("mostly identical 10k lines", |b| {
+        b.iter(|| {
+            format!(
+                "{}",
+                DrawDiff::new(black_box(&old), black_box(&new), &theme)
+            )
+        });
+    });
+}
+
+/// Above this many lines the LCS algorithm takes too long to be worth
+/// benchmarking
+#[cfg(feature = "bench-fixtures")]
+const LCS_MAX_LINES: usize = 2_000;
+
+#[cfg(feature = "bench-fixtures")]
+fn bench_algorithms(c: &mut Criterion) {
+    let mut group = c.benchmark_group("algorithms");
+
+    for fixture in fixtures::all() {
+        let lines = fixture.old.lines().count();
+
+        for (name, algorithm) in [
+            ("myers", Algorithm::Myers),
+            ("patience", Algorithm::Patience),
+            ("lcs", Algorithm::Lcs),
+        ] {
+            if algorithm == Algorithm::Lcs && lines > LCS_MAX_LINES {
+                continue;
+            }
+
+            group.bench_with_input(
+                BenchmarkId::new(name, fixture.name),
+                &fixture,
+                |b, fixture| {
+                    let old: Vec<&str> = fixture.old.split_inclusive('\n').collect();
+                    let new: Vec<&str> = fixture.new.split_inclusive('\n').collect();
+
+                    b.iter(|| algorithm.diff(black_box(&old), black_box(&new)));
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+#[cfg(feature = "bench-fixtures")]
+fn bench_change_density(c: &mut Criterion) {
+    let mut group = c.benchmark_group("change density");
+    let theme = ArrowsTheme::default();
+
+    for density in [0.01, 0.1, 0.5] {
+        let (old, new) = Generator::new(0)
+            .with_lines(5_000)
+            .with_change_density(density)
+            .pair();
+
+        group.bench_with_input(BenchmarkId::from_parameter(density), &density, |b, _| {
+            b.iter(|| {
+                format!(
+                    "{}",
+                    DrawDiff::new(black_box(&old), black_box(&new), &theme)
+                )
+            });
+        });
+    }
+
+    group.finish();
+}
+
+// The algorithm comparison needs the generated fixtures, see `just bench`
+#[cfg(feature = "bench-fixtures")]
+criterion_group!(
+    benches,
+    bench_identical,
+    bench_mostly_identical,
+    bench_algorithms,
+    bench_change_density
+);
+#[cfg(not(feature = "bench-fixtures"))]
+criterion_group!(benches, bench_identical, bench_mostly_identical);
+criterion_main!(benches);
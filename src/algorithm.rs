@@ -0,0 +1,330 @@
+use std::{
+    fmt::Debug,
+    sync::{PoisonError, RwLock},
+};
+
+use super::{
+    cancellation::{CancellationToken, Cancelled},
+    intern::{diff_lines_observed, diff_lines_with},
+    ops::{deletes_first, from_similar, DiffOp},
+    progress::Progress,
+};
+
+/// Something that works out which lines changed between two texts
+///
+/// Implement this to use your own algorithm with [`DrawDiff`] and the
+/// themes. Register it with [`register_algorithm`], and select it with
+/// [`Algorithm::Custom`].
+///
+/// The lines are given with their line endings. The ops returned need to
+/// cover every line of both sides in order, which [`verify`] can check.
+/// Deletes followed directly by inserts are drawn as a changed block, with
+/// the changed parts of the lines emphasized. Runs of deletes and inserts
+/// are put in that order, so a changed block is drawn the same way whichever
+/// algorithm found it.
+///
+/// The trait is object safe, and the built in algorithms implement it too,
+/// so an algorithm can hand some of its work to them.
+///
+/// [`DrawDiff`]: crate::DrawDiff
+/// [`verify`]: crate::verify
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{
+///     register_algorithm, verify, Algorithm, ArrowsTheme, DiffAlgorithm, DiffOp, DrawDiff,
+/// };
+///
+/// /// Line up the texts line by line, like a side by side listing
+/// #[derive(Debug)]
+/// struct LineByLine;
+/// impl DiffAlgorithm for LineByLine {
+///     fn diff(&self, old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+///         let mut ops = vec![];
+///         for (index, (old_line, new_line)) in old.iter().zip(new).enumerate() {
+///             if old_line == new_line {
+///                 ops.push(DiffOp::equal(index..index + 1, index..index + 1));
+///             } else {
+///                 ops.push(DiffOp::delete(index..index + 1, index));
+///                 ops.push(DiffOp::insert(index + 1, index..index + 1));
+///             }
+///         }
+///         let shared = old.len().min(new.len());
+///         ops.push(DiffOp::delete(shared..old.len(), shared));
+///         ops.push(DiffOp::insert(old.len(), shared..new.len()));
+///         ops
+///     }
+/// }
+///
+/// static LINE_BY_LINE: LineByLine = LineByLine;
+/// register_algorithm("line-by-line", &LINE_BY_LINE);
+///
+/// let (old, new) = ("a\nb\n", "b\nb\nc\n");
+/// let ops = LINE_BY_LINE.diff(&["a\n", "b\n"], &["b\n", "b\n", "c\n"]);
+/// assert_eq!(verify(old, new, &ops), Ok(()));
+///
+/// let theme = ArrowsTheme::default();
+/// let actual = format!(
+///     "{}",
+///     DrawDiff::new(old, new, &theme).with_algorithm(Algorithm::Custom("line-by-line"))
+/// );
+/// assert_eq!(actual, "< left / > right\n<a\n>b\n b\n>c\n");
+/// ```
+pub trait DiffAlgorithm: Debug {
+    /// Work out the ops that turn the old lines into the new lines
+    fn diff(&self, old: &[&str], new: &[&str]) -> Vec<DiffOp>;
+}
+
+/// Which algorithm to work out the diff with
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{Algorithm, ArrowsTheme, DrawDiff};
+/// let theme = ArrowsTheme::default();
+/// let actual = format!(
+///     "{}",
+///     DrawDiff::new("a\nb\n", "b\na\n", &theme).with_algorithm(Algorithm::Patience)
+/// );
+///
+/// assert_eq!(actual, "< left / > right\n>b\n a\n<b\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Algorithm<'a> {
+    /// Myers' algorithm, which finds the smallest diff
+    #[default]
+    Myers,
+    /// Patience diff, which lines up unique lines first, and often reads
+    /// better for code
+    Patience,
+    /// The longest common subsequence, which is slow on large inputs
+    Lcs,
+    /// An algorithm registered under this name with
+    /// [`register_algorithm`], falling back to [`Algorithm::Myers`] if there
+    /// isn't one
+    Custom(&'a str),
+}
+
+impl Algorithm<'_> {
+    /// There is an algorithm to use for this, rather than it falling back to
+    /// Myers
+    #[must_use]
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::Custom(name) => registered(name).is_some(),
+            _ => true,
+        }
+    }
+
+    /// Work out the ops that turn the old lines into the new lines
+    #[must_use]
+    pub fn diff(&self, old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+        let algorithm = match self {
+            Self::Myers => similar::Algorithm::Myers,
+            Self::Patience => similar::Algorithm::Patience,
+            Self::Lcs => similar::Algorithm::Lcs,
+            Self::Custom(name) => match registered(name) {
+                Some(algorithm) => return deletes_first(algorithm.diff(old, new)),
+                None => similar::Algorithm::Myers,
+            },
+        };
+
+        from_similar(&diff_lines_with(algorithm, old, new))
+    }
+
+    /// Work out the ops that turn the old lines into the new lines,
+    /// reporting progress if the diff is large enough, see [`Progress`], and
+    /// giving up if the token is cancelled, see [`CancellationToken`]
+    pub(crate) fn diff_observed(
+        &self,
+        old: &[&str],
+        new: &[&str],
+        progress: Option<Progress<'_>>,
+        cancellation: Option<&CancellationToken>,
+    ) -> Result<Vec<DiffOp>, Cancelled> {
+        if cancellation.is_some_and(CancellationToken::is_cancelled) {
+            return Err(Cancelled);
+        }
+        let progress = progress.filter(|progress| progress.applies(old.len(), new.len()));
+        let algorithm = match self {
+            _ if progress.is_none() && cancellation.is_none() => return Ok(self.diff(old, new)),
+            Self::Myers => similar::Algorithm::Myers,
+            Self::Patience => similar::Algorithm::Patience,
+            Self::Lcs => similar::Algorithm::Lcs,
+            Self::Custom(_) => return Ok(self.diff(old, new)),
+        };
+
+        let mut observe = |done, total| {
+            if let Some(progress) = progress {
+                progress.report(done, total);
+            }
+            !cancellation.is_some_and(CancellationToken::is_cancelled)
+        };
+        Ok(from_similar(&diff_lines_observed(
+            algorithm,
+            old,
+            new,
+            &mut observe,
+        )?))
+    }
+}
+
+impl DiffAlgorithm for Algorithm<'_> {
+    fn diff(&self, old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+        Algorithm::diff(self, old, new)
+    }
+}
+
+type Registered = (&'static str, &'static (dyn DiffAlgorithm + Sync));
+
+static REGISTRY: RwLock<Vec<Registered>> = RwLock::new(Vec::new());
+
+/// Make an algorithm available as [`Algorithm::Custom`] under a name
+///
+/// Registering another algorithm under the same name replaces it.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{register_algorithm, Algorithm, ChangeTag, DiffAlgorithm, DiffOp};
+///
+/// /// Treat the texts as entirely different
+/// #[derive(Debug)]
+/// struct Rewrite;
+/// impl DiffAlgorithm for Rewrite {
+///     fn diff(&self, old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+///         vec![
+///             DiffOp::new(ChangeTag::Delete, 0..old.len(), 0..0),
+///             DiffOp::new(ChangeTag::Insert, old.len()..old.len(), 0..new.len()),
+///         ]
+///     }
+/// }
+///
+/// static REWRITE: Rewrite = Rewrite;
+/// register_algorithm("rewrite", &REWRITE);
+///
+/// assert!(Algorithm::Custom("rewrite").is_available());
+/// assert_eq!(Algorithm::Custom("rewrite").diff(&["a\n"], &["a\n"]).len(), 2);
+/// ```
+pub fn register_algorithm(name: &'static str, algorithm: &'static (dyn DiffAlgorithm + Sync)) {
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+
+    registry.retain(|(registered, _)| *registered != name);
+    registry.push((name, algorithm));
+}
+
+/// Look up an algorithm by name, built in or registered, as used for the
+/// `TERMDIFF_ALGORITHM` environment variable
+#[cfg(feature = "env")]
+pub(crate) fn named(name: &str) -> Option<Algorithm<'static>> {
+    match name {
+        "myers" => Some(Algorithm::Myers),
+        "patience" => Some(Algorithm::Patience),
+        "lcs" => Some(Algorithm::Lcs),
+        _ => REGISTRY
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(registered, _)| *registered == name)
+            .map(|(registered, _)| Algorithm::Custom(registered)),
+    }
+}
+
+fn registered(name: &str) -> Option<&'static (dyn DiffAlgorithm + Sync)> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(registered, _)| *registered == name)
+        .map(|(_, algorithm)| *algorithm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{register_algorithm, Algorithm, DiffAlgorithm};
+    use crate::{ChangeTag, DiffOp};
+
+    #[derive(Debug)]
+    struct Everything(ChangeTag);
+    impl DiffAlgorithm for Everything {
+        fn diff(&self, old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+            vec![DiffOp::new(self.0, 0..old.len(), 0..new.len())]
+        }
+    }
+
+    static DELETE: Everything = Everything(ChangeTag::Delete);
+    static EQUAL: Everything = Everything(ChangeTag::Equal);
+
+    #[test]
+    fn algorithms_can_be_used_as_trait_objects() {
+        let algorithms: [&dyn DiffAlgorithm; 2] = [&Algorithm::Patience, &EQUAL];
+
+        assert_eq!(
+            algorithms.map(|algorithm| algorithm.diff(&["a\n"], &["a\n"])),
+            [
+                vec![DiffOp::new(ChangeTag::Equal, 0..1, 0..1)],
+                vec![DiffOp::new(ChangeTag::Equal, 0..1, 0..1)]
+            ]
+        );
+    }
+
+    #[test]
+    fn unregistered_names_fall_back_to_myers() {
+        let algorithm = Algorithm::Custom("not registered");
+
+        assert!(!algorithm.is_available());
+        assert_eq!(
+            algorithm.diff(&["a\n"], &["a\n"]),
+            Algorithm::Myers.diff(&["a\n"], &["a\n"])
+        );
+    }
+
+    #[test]
+    fn registering_again_replaces_the_algorithm() {
+        register_algorithm("replaced", &DELETE);
+        register_algorithm("replaced", &EQUAL);
+
+        assert_eq!(
+            Algorithm::Custom("replaced").diff(&["a\n"], &["a\n"]),
+            vec![DiffOp::new(ChangeTag::Equal, 0..1, 0..1)]
+        );
+    }
+
+    #[derive(Debug)]
+    struct InsertsFirst;
+    impl DiffAlgorithm for InsertsFirst {
+        fn diff(&self, old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+            vec![
+                DiffOp::insert(0, 0..new.len()),
+                DiffOp::delete(0..old.len(), new.len()),
+            ]
+        }
+    }
+
+    static INSERTS_FIRST: InsertsFirst = InsertsFirst;
+
+    #[test]
+    fn every_algorithm_puts_deletes_before_inserts() {
+        register_algorithm("inserts-first", &INSERTS_FIRST);
+        let old = ["a\n", "b\n", "c\n", "d\n", "e\n"];
+        let new = ["x\n", "b\n", "y\n", "z\n", "d\n", "a\n"];
+
+        for algorithm in [
+            Algorithm::Myers,
+            Algorithm::Patience,
+            Algorithm::Lcs,
+            Algorithm::Custom("inserts-first"),
+        ] {
+            let ops = algorithm.diff(&old, &new);
+            assert!(
+                ops.windows(2).all(|pair| {
+                    (pair[0].tag(), pair[1].tag()) != (ChangeTag::Insert, ChangeTag::Delete)
+                }),
+                "{:?} gave {:?}",
+                algorithm,
+                ops
+            );
+        }
+    }
+}
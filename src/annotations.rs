@@ -0,0 +1,161 @@
+use std::fmt::{Display, Formatter};
+
+use super::{
+    change::ChangeTag, line_endings::split_ending, lines::split_lines, result::DiffResult,
+};
+
+/// How serious an annotation is, which decides how it is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnnotationLevel {
+    /// Shown as information
+    #[default]
+    Notice,
+    /// Shown as a warning
+    Warning,
+    /// Shown as an error
+    Error,
+}
+
+impl AnnotationLevel {
+    fn command(self) -> &'static str {
+        match self {
+            Self::Notice => "notice",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Draws each group of changed lines as a GitHub Actions workflow command,
+/// so the changes show up as annotations on the lines of the new file
+///
+/// Each annotation gives the removed lines prefixed with `-` and the added
+/// lines prefixed with `+`. Line numbers are in the new text.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{AnnotationLevel, DiffResult, GithubAnnotations};
+/// let result = DiffResult::new("a = 1\nb = 2\n", "a = 1\nb = 3\n");
+///
+/// assert_eq!(
+///     GithubAnnotations::new("src/config.toml", &result)
+///         .with_level(AnnotationLevel::Warning)
+///         .with_title("Config changed")
+///         .to_string(),
+///     "::warning file=src/config.toml,line=2,endLine=2,title=Config changed::-b = 2%0A+b = 3\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct GithubAnnotations<'a> {
+    file: &'a str,
+    result: &'a DiffResult<'a>,
+    level: AnnotationLevel,
+    title: Option<&'a str>,
+}
+
+impl<'a> GithubAnnotations<'a> {
+    /// Annotations for the changes made to a file, from a diff that has
+    /// already been worked out
+    #[must_use]
+    pub fn new(file: &'a str, result: &'a DiffResult<'a>) -> Self {
+        Self {
+            file,
+            result,
+            level: AnnotationLevel::default(),
+            title: None,
+        }
+    }
+
+    /// How serious the annotations are
+    #[must_use]
+    pub fn with_level(mut self, level: AnnotationLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// A title to give every annotation
+    #[must_use]
+    pub fn with_title(mut self, title: &'a str) -> Self {
+        self.title = Some(title);
+        self
+    }
+}
+
+impl Display for GithubAnnotations<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (old, new) = self.result.texts();
+        let old: Vec<&str> = split_lines(old).collect();
+        let new: Vec<&str> = split_lines(new).collect();
+
+        for hunk in self.result.ops().split(|op| op.tag() == ChangeTag::Equal) {
+            let (Some(first), Some(last)) = (hunk.first(), hunk.last()) else {
+                continue;
+            };
+            let (start, end) = (first.new_range().start, last.new_range().end);
+            let line = (start + 1).min(new.len().max(1));
+            let mut message = String::new();
+
+            for op in hunk {
+                let (prefix, lines) = match op.tag() {
+                    ChangeTag::Delete | ChangeTag::MovedFrom => ('-', &old[op.old_range()]),
+                    _ => ('+', &new[op.new_range()]),
+                };
+                for content in lines {
+                    if !message.is_empty() {
+                        message.push('\n');
+                    }
+                    message.push(prefix);
+                    message.push_str(split_ending(content).0);
+                }
+            }
+
+            write!(
+                f,
+                "::{} file={},line={line},endLine={}",
+                self.level.command(),
+                escape_property(self.file),
+                end.max(line)
+            )?;
+            if let Some(title) = self.title {
+                write!(f, ",title={}", escape_property(title))?;
+            }
+            writeln!(f, "::{}", escape_data(&message))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Escape the message of a workflow command
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property of a workflow command, which can't contain the
+/// characters that separate properties either
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GithubAnnotations;
+    use crate::DiffResult;
+
+    #[test]
+    fn each_hunk_is_annotated() {
+        let changed = DiffResult::new("1\n2\n3\n4\n", "0\n1\n3\n4\n");
+        let unchanged = DiffResult::new("100%\n", "100%\n");
+
+        assert_eq!(
+            GithubAnnotations::new("a,b.txt", &changed).to_string(),
+            "::notice file=a%2Cb.txt,line=1,endLine=1::+0\n\
+             ::notice file=a%2Cb.txt,line=3,endLine=3::-2\n"
+        );
+        assert_eq!(GithubAnnotations::new("a", &unchanged).to_string(), "");
+    }
+}
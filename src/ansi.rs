@@ -0,0 +1,239 @@
+use std::convert::TryFrom;
+
+use crossterm::style::{Attribute, Color, ContentStyle};
+
+/// Rendered output split into runs of text and the style they are drawn in,
+/// for testing themes without comparing escape codes
+///
+/// The escape codes are read the way a terminal would, so two themes that
+/// write different codes for the same look give the same segments.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::style::{ContentStyle, Stylize};
+/// use termdiff::{AnsiAssert, ArrowsColorTheme, DrawDiff};
+/// let theme = ArrowsColorTheme::default();
+/// let output = DrawDiff::new("a fox\n", "a cat\n", &theme).to_string();
+/// let ansi = AnsiAssert::new(&output);
+///
+/// assert_eq!(ansi.plain(), "< left / > right\n<a fox\n>a cat\n");
+/// ansi.assert_style("fox", ContentStyle::new().red().underlined());
+/// ansi.assert_style("cat", ContentStyle::new().green().underlined());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiAssert {
+    segments: Vec<(ContentStyle, String)>,
+}
+
+impl AnsiAssert {
+    /// Split output into runs of text in the same style
+    #[must_use]
+    pub fn new(output: &str) -> Self {
+        let mut segments: Vec<(ContentStyle, String)> = vec![];
+        let mut style = ContentStyle::new();
+        let mut rest = output;
+
+        while !rest.is_empty() {
+            if let Some((parameters, after)) = sequence(rest) {
+                if let Some(parameters) = parameters {
+                    apply_sgr(&mut style, parameters);
+                }
+                rest = after;
+                continue;
+            }
+
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first..]
+                .find('\u{1b}')
+                .map_or(rest.len(), |index| index + first);
+            let (text, after) = rest.split_at(end);
+            match segments.last_mut() {
+                Some((last, previous)) if *last == style => previous.push_str(text),
+                _ => segments.push((style, text.to_string())),
+            }
+            rest = after;
+        }
+
+        Self { segments }
+    }
+
+    /// The runs of text, with the style each is drawn in
+    #[must_use]
+    pub fn segments(&self) -> &[(ContentStyle, String)] {
+        &self.segments
+    }
+
+    /// The text with no styles
+    #[must_use]
+    pub fn plain(&self) -> String {
+        self.segments
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+
+    /// The style of the first run of text that contains some text, if any do
+    #[must_use]
+    pub fn style_of(&self, text: &str) -> Option<ContentStyle> {
+        self.segments
+            .iter()
+            .find(|(_, segment)| segment.contains(text))
+            .map(|(style, _)| *style)
+    }
+
+    /// Check that the first run of text containing some text is drawn in a
+    /// style
+    ///
+    /// # Panics
+    ///
+    /// Panics if no run contains the text, or the run is in a different
+    /// style, listing all the runs.
+    #[track_caller]
+    pub fn assert_style(&self, text: &str, style: ContentStyle) {
+        match self.style_of(text) {
+            Some(actual) if actual == style => {}
+            Some(actual) => panic!(
+                "{text:?} is drawn in {actual:?}, not {style:?}, in {:#?}",
+                self.segments
+            ),
+            None => panic!("{text:?} is not in {:#?}", self.segments),
+        }
+    }
+}
+
+/// The parameters of a select graphic rendition sequence at the start of
+/// some text, or `None` for another escape sequence, with the text after it
+fn sequence(text: &str) -> Option<(Option<&str>, &str)> {
+    let rest = text.strip_prefix('\u{1b}')?;
+    let Some(rest) = rest.strip_prefix('[') else {
+        let next = rest.chars().next().map_or(0, char::len_utf8);
+        return Some((None, &rest[next..]));
+    };
+    let end = rest.find(|character: char| ('@'..='~').contains(&character))?;
+    let parameters = rest[end..].starts_with('m').then(|| &rest[..end]);
+
+    Some((parameters, &rest[end + 1..]))
+}
+
+fn apply_sgr(style: &mut ContentStyle, parameters: &str) {
+    let mut codes = parameters
+        .split(';')
+        .map(|code| code.parse::<u16>().unwrap_or(0));
+
+    while let Some(code) = codes.next() {
+        match code {
+            0 => *style = ContentStyle::new(),
+            1 => style.attributes.set(Attribute::Bold),
+            2 => style.attributes.set(Attribute::Dim),
+            3 => style.attributes.set(Attribute::Italic),
+            4 => style.attributes.set(Attribute::Underlined),
+            5 => style.attributes.set(Attribute::SlowBlink),
+            6 => style.attributes.set(Attribute::RapidBlink),
+            7 => style.attributes.set(Attribute::Reverse),
+            8 => style.attributes.set(Attribute::Hidden),
+            9 => style.attributes.set(Attribute::CrossedOut),
+            21 => style.attributes.set(Attribute::DoubleUnderlined),
+            22 => {
+                style.attributes.unset(Attribute::Bold);
+                style.attributes.unset(Attribute::Dim);
+            }
+            23 => style.attributes.unset(Attribute::Italic),
+            24 => {
+                style.attributes.unset(Attribute::Underlined);
+                style.attributes.unset(Attribute::DoubleUnderlined);
+            }
+            25 => {
+                style.attributes.unset(Attribute::SlowBlink);
+                style.attributes.unset(Attribute::RapidBlink);
+            }
+            27 => style.attributes.unset(Attribute::Reverse),
+            28 => style.attributes.unset(Attribute::Hidden),
+            29 => style.attributes.unset(Attribute::CrossedOut),
+            30..=37 => style.foreground_color = Some(ansi_color(code - 30)),
+            38 => style.foreground_color = extended_color(&mut codes),
+            39 => style.foreground_color = None,
+            40..=47 => style.background_color = Some(ansi_color(code - 40)),
+            48 => style.background_color = extended_color(&mut codes),
+            49 => style.background_color = None,
+            58 => style.underline_color = extended_color(&mut codes),
+            59 => style.underline_color = None,
+            90..=97 => style.foreground_color = Some(ansi_color(code - 90 + 8)),
+            100..=107 => style.background_color = Some(ansi_color(code - 100 + 8)),
+            _ => {}
+        }
+    }
+}
+
+/// A 256 color or RGB color, from the codes after a `38`, `48` or `58`
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let mut component = || u8::try_from(codes.next()?).ok();
+
+    match component()? {
+        5 => component().map(ansi_color),
+        2 => Some(Color::Rgb {
+            r: component()?,
+            g: component()?,
+            b: component()?,
+        }),
+        _ => None,
+    }
+}
+
+/// One of the 256 colors, named as crossterm names them where it can be
+fn ansi_color(value: impl Into<u16>) -> Color {
+    let value = value.into();
+
+    match value {
+        0 => Color::Black,
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        7 => Color::Grey,
+        8 => Color::DarkGrey,
+        9 => Color::Red,
+        10 => Color::Green,
+        11 => Color::Yellow,
+        12 => Color::Blue,
+        13 => Color::Magenta,
+        14 => Color::Cyan,
+        15 => Color::White,
+        _ => Color::AnsiValue(u8::try_from(value).unwrap_or(u8::MAX)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{Color, ContentStyle, Stylize};
+
+    use super::AnsiAssert;
+
+    #[test]
+    fn different_codes_for_the_same_look_agree() {
+        let named = format!("{}", "a".red().on_dark_blue().bold());
+        let raw = "\u{1b}[1m\u{1b}[91;44ma\u{1b}[0m";
+
+        assert_eq!(AnsiAssert::new(&named), AnsiAssert::new(raw));
+        assert_eq!(
+            AnsiAssert::new("\u{1b}[38;2;1;2;3mx\u{1b}[39my\u{1b}[2Kz").segments(),
+            [
+                (
+                    ContentStyle::new().with(Color::Rgb { r: 1, g: 2, b: 3 }),
+                    "x".to_string()
+                ),
+                (ContentStyle::new(), "yz".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_can_start_with_wide_characters() {
+        let ansi = AnsiAssert::new("é\n\u{1b}[31m😀é\u{1b}[0m");
+
+        assert_eq!(ansi.plain(), "é\n😀é");
+        assert_eq!(ansi.style_of("😀"), Some(ContentStyle::new().dark_red()));
+    }
+}
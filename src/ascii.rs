@@ -0,0 +1,65 @@
+use std::fmt::{Display, Formatter, Write};
+
+/// Displays something with only ASCII characters, swapping the symbols the
+/// themes use for ASCII ones and escaping any other characters
+pub(crate) struct Ascii<'a>(pub(crate) &'a dyn Display);
+
+impl Display for Ascii<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(AsciiWriter { inner: f }, "{}", self.0)
+    }
+}
+
+/// Writes text on to another writer, replacing characters that aren't
+/// ASCII
+struct AsciiWriter<'a, 'b> {
+    inner: &'a mut Formatter<'b>,
+}
+
+impl Write for AsciiWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut start = 0;
+
+        for (index, character) in s.char_indices() {
+            if character.is_ascii() {
+                continue;
+            }
+
+            self.inner.write_str(&s[start..index])?;
+            match fallback(character) {
+                Some(fallback) => self.inner.write_str(fallback)?,
+                None => write!(self.inner, "\\u{{{:x}}}", u32::from(character))?,
+            }
+            start = index + character.len_utf8();
+        }
+
+        self.inner.write_str(&s[start..])
+    }
+}
+
+/// An ASCII stand in for a symbol the themes use
+fn fallback(character: char) -> Option<&'static str> {
+    match character {
+        '␊' => Some("\\n"),
+        '␍' => Some("\\r"),
+        '∅' => Some("(none)"),
+        '→' => Some("->"),
+        '↪' => Some(">"),
+        '⋯' | '…' => Some("..."),
+        '·' => Some("."),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ascii;
+
+    #[test]
+    fn symbols_have_fallbacks_and_anything_else_is_escaped() {
+        assert_eq!(
+            Ascii(&"<a\u{1b}[4m␊\u{1b}[0m\n⋯\n ␍␊→␊ café\n").to_string(),
+            "<a\u{1b}[4m\\n\u{1b}[0m\n...\n \\r\\n->\\n caf\\u{e9}\n"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{
+    algorithm::Algorithm, cache::DiffCache, config::DiffConfig, lines::split_lines, ops::DiffOp,
+};
+
+/// Draw the diffs of many pairs of texts, calling `progress` with how many
+/// are done after each one
+///
+/// The diffs are given back in the order they are given, each with its
+/// label. With the `rayon` feature the diffs are worked out and drawn in
+/// parallel, so `progress` is called from many threads, and the diffs may be
+/// done in any order.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use termdiff::{diff_batch, ArrowsTheme, DiffConfig};
+/// let theme = ArrowsTheme::default();
+/// let config = DiffConfig::new(&theme);
+/// let done = AtomicUsize::new(0);
+/// let results = diff_batch(
+///     vec![("a", "x\n", "y\n"), ("b", "z\n", "z\n")],
+///     &config,
+///     |count| {
+///         done.fetch_max(count, Ordering::Relaxed);
+///     },
+/// );
+///
+/// assert_eq!(
+///     results,
+///     vec![
+///         ("a", "< left / > right\n<x\n>y\n".to_string()),
+///         ("b", "< left / > right\n z\n".to_string()),
+///     ]
+/// );
+/// assert_eq!(done.load(Ordering::Relaxed), 2);
+/// ```
+pub fn diff_batch<'a, L: Send>(
+    pairs: impl IntoIterator<Item = (L, &'a str, &'a str)>,
+    config: &DiffConfig<'_>,
+    progress: impl Fn(usize) + Sync,
+) -> Vec<(L, String)> {
+    let pairs: Vec<(L, &str, &str)> = pairs.into_iter().collect();
+    let done = AtomicUsize::new(0);
+
+    map_all(pairs, |(label, old, new)| {
+        let drawn = config.draw(old, new).to_string();
+        progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+        (label, drawn)
+    })
+}
+
+/// Draw the diffs of many pairs of texts like [`diff_batch`], taking the
+/// diffs that have already been worked out from a [`DiffCache`] and adding
+/// the rest to it
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_batch_cached, ArrowsTheme, DiffCache, DiffConfig};
+/// let theme = ArrowsTheme::default();
+/// let config = DiffConfig::new(&theme);
+/// let mut cache = DiffCache::new(16);
+/// let pairs = vec![("a", "x\n", "y\n"), ("b", "z\n", "z\n")];
+///
+/// let first = diff_batch_cached(pairs.clone(), &config, &mut cache, |_| {});
+/// let second = diff_batch_cached(pairs, &config, &mut cache, |_| {});
+///
+/// assert_eq!(first, second);
+/// assert_eq!(cache.len(), 2);
+/// ```
+pub fn diff_batch_cached<'a, L: Send>(
+    pairs: impl IntoIterator<Item = (L, &'a str, &'a str)>,
+    config: &DiffConfig<'_>,
+    cache: &mut DiffCache,
+    progress: impl Fn(usize) + Sync,
+) -> Vec<(L, String)> {
+    let algorithm = config.algorithm();
+    let pairs: Vec<(L, &str, &str, Option<Vec<DiffOp>>)> = pairs
+        .into_iter()
+        .map(|(label, old, new)| {
+            let cached = cache
+                .get(old, new, algorithm)
+                .map(|result| result.ops().to_vec());
+            (label, old, new, cached)
+        })
+        .collect();
+    let done = AtomicUsize::new(0);
+
+    let drawn = map_all(pairs, |(label, old, new, cached)| {
+        let ops = cached.unwrap_or_else(|| ops(old, new, algorithm));
+        let drawn = config.draw(old, new).with_ops(&ops).to_string();
+        progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+        (label, drawn, (old, new, ops))
+    });
+
+    drawn
+        .into_iter()
+        .map(|(label, drawn, (old, new, ops))| {
+            cache.insert(old, new, algorithm, ops);
+            (label, drawn)
+        })
+        .collect()
+}
+
+#[cfg(feature = "rayon")]
+fn map_all<T: Send, U: Send>(items: Vec<T>, map: impl Fn(T) -> U + Sync + Send) -> Vec<U> {
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    items.into_par_iter().map(map).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn map_all<T, U>(items: Vec<T>, map: impl Fn(T) -> U) -> Vec<U> {
+    items.into_iter().map(map).collect()
+}
+
+fn ops(old: &str, new: &str, algorithm: Algorithm<'_>) -> Vec<DiffOp> {
+    let old: Vec<&str> = split_lines(old).collect();
+    let new: Vec<&str> = split_lines(new).collect();
+    algorithm.diff(&old, &new)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::diff_batch;
+    use crate::{ArrowsTheme, DiffConfig};
+
+    #[test]
+    fn progress_counts_every_diff_once() {
+        let theme = ArrowsTheme::default();
+        let counts = Mutex::new(vec![]);
+        let pairs: Vec<(usize, &str, &str)> = (0..50).map(|index| (index, "a\n", "b\n")).collect();
+
+        let results = diff_batch(pairs, &DiffConfig::new(&theme), |count| {
+            counts.lock().unwrap().push(count);
+        });
+        let mut counts = counts.into_inner().unwrap();
+        counts.sort_unstable();
+
+        assert_eq!(counts, (1..=50).collect::<Vec<usize>>());
+        assert!(results
+            .iter()
+            .enumerate()
+            .all(|(index, (label, _))| index == *label));
+    }
+}
@@ -0,0 +1,161 @@
+use std::{borrow::Cow, fmt::Write as _, io::Write};
+
+use super::{draw_diff::DrawDiff, intern::diff_lines, ops::from_similar, themes::Theme};
+
+/// Print a diff of bytes that are mostly, but not always, valid UTF-8 to a
+/// writer
+///
+/// Lines are compared as bytes, so lines that only differ in their invalid
+/// sequences are still shown as changed. Invalid sequences are rendered with
+/// the replacement character `�`, as [`String::from_utf8_lossy`] does.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_bytes, ArrowsTheme};
+/// let old = b"a\nb\xFF\n";
+/// let new = b"a\nb\xFE\n";
+/// let mut buffer: Vec<u8> = Vec::new();
+/// let theme = ArrowsTheme::default();
+/// diff_bytes(&mut buffer, old, new, &theme).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(actual, "< left / > right\n a\n<b�\n>b�\n");
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+pub fn diff_bytes(
+    w: &mut dyn Write,
+    old: &[u8],
+    new: &[u8],
+    theme: &dyn Theme,
+) -> std::io::Result<()> {
+    write_bytes_diff(w, old, new, theme, false)
+}
+
+/// Print a diff of bytes that are mostly, but not always, valid UTF-8 to a
+/// writer, escaping invalid sequences
+///
+/// Like [`diff_bytes`], but each byte of an invalid sequence is rendered as
+/// a hex escape like `\xFF`, so the differences in them can be seen.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_bytes_escaped, ArrowsTheme};
+/// let old = b"a\nb\xFF\n";
+/// let new = b"a\nb\xFE\n";
+/// let mut buffer: Vec<u8> = Vec::new();
+/// let theme = ArrowsTheme::default();
+/// diff_bytes_escaped(&mut buffer, old, new, &theme).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(actual, "< left / > right\n a\n<b\\xFF\n>b\\xFE\n");
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+pub fn diff_bytes_escaped(
+    w: &mut dyn Write,
+    old: &[u8],
+    new: &[u8],
+    theme: &dyn Theme,
+) -> std::io::Result<()> {
+    write_bytes_diff(w, old, new, theme, true)
+}
+
+fn write_bytes_diff(
+    w: &mut dyn Write,
+    old: &[u8],
+    new: &[u8],
+    theme: &dyn Theme,
+    escape: bool,
+) -> std::io::Result<()> {
+    let old_lines = split_byte_lines(old);
+    let new_lines = split_byte_lines(new);
+    let ops = from_similar(&diff_lines(&old_lines, &new_lines));
+
+    let old_text: Vec<Cow<'_, str>> = old_lines.iter().map(|line| decode(line, escape)).collect();
+    let new_text: Vec<Cow<'_, str>> = new_lines.iter().map(|line| decode(line, escape)).collect();
+    let old_text: Vec<&str> = old_text.iter().map(AsRef::as_ref).collect();
+    let new_text: Vec<&str> = new_text.iter().map(AsRef::as_ref).collect();
+
+    let output: DrawDiff<'_> = DrawDiff::from_lines(&old_text, &new_text, theme).with_ops(&ops);
+    write!(w, "{output}")
+}
+
+/// Split bytes into lines, keeping each line's ending, the same way text is
+/// split
+pub(crate) fn split_byte_lines(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut lines = vec![];
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        let end = rest
+            .iter()
+            .position(|byte| *byte == b'\n' || *byte == b'\r')
+            .map_or(rest.len(), |index| {
+                if rest[index] == b'\r' && rest.get(index + 1) == Some(&b'\n') {
+                    index + 2
+                } else {
+                    index + 1
+                }
+            });
+
+        let (line, remaining) = rest.split_at(end);
+        lines.push(line);
+        rest = remaining;
+    }
+
+    lines
+}
+
+/// Turn a line of bytes into text, replacing or escaping invalid sequences
+fn decode(line: &[u8], escape: bool) -> Cow<'_, str> {
+    if !escape {
+        return String::from_utf8_lossy(line);
+    }
+
+    if let Ok(line) = std::str::from_utf8(line) {
+        return line.into();
+    }
+
+    let mut decoded = String::with_capacity(line.len());
+    for chunk in line.utf8_chunks() {
+        decoded.push_str(chunk.valid());
+        for byte in chunk.invalid() {
+            let _ = write!(decoded, "\\x{byte:02X}");
+        }
+    }
+
+    decoded.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use similar::DiffableStr;
+
+    use super::{decode, split_byte_lines};
+
+    #[test]
+    fn bytes_split_like_text() {
+        for text in ["", "a", "a\n", "a\r\nb\rc\n\n", "\r\r\n\n"] {
+            assert_eq!(
+                split_byte_lines(text.as_bytes()),
+                text.tokenize_lines()
+                    .iter()
+                    .map(|line| line.as_bytes())
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_sequences_are_escaped() {
+        assert_eq!(decode(b"caf\xC3\xA9 \xC3\n", true), "café \\xC3\n");
+        assert_eq!(decode(b"caf\xC3\xA9 \xC3\n", false), "café \u{FFFD}\n");
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use super::{algorithm::Algorithm, lines::split_lines, ops::DiffOp, result::DiffResult};
+
+/// Diffs that have already been worked out, kept so the same pair of texts
+/// isn't diffed twice
+///
+/// Diffs are looked up by a hash of both texts and the algorithm, and the
+/// texts are compared as well, so a clash of hashes can't give the wrong
+/// diff. Once the cache is full, the diff that was used longest ago is
+/// dropped to make room.
+///
+/// [`diff_batch_cached`] and [`IncrementalDiff::new_cached`] can share a
+/// cache with each other.
+///
+/// [`diff_batch_cached`]: crate::diff_batch_cached
+/// [`IncrementalDiff::new_cached`]: crate::IncrementalDiff::new_cached
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{Algorithm, ArrowsTheme, DiffCache};
+/// let theme = ArrowsTheme::default();
+/// let mut cache = DiffCache::new(16);
+///
+/// let result = cache.diff("a\nb\n", "a\nc\n", Algorithm::Myers);
+/// assert_eq!(
+///     format!("{}", result.draw(&theme)),
+///     "< left / > right\n a\n<b\n>c\n"
+/// );
+///
+/// // Diffed already, so this comes from the cache
+/// cache.diff("a\nb\n", "a\nc\n", Algorithm::Myers);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiffCache {
+    capacity: usize,
+    entries: VecDeque<(u64, DiffResult<'static>)>,
+}
+
+impl DiffCache {
+    /// An empty cache that holds up to `capacity` diffs, and always at least
+    /// one
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// The diff of two texts, from the cache if it is there, or worked out
+    /// and added to the cache if not
+    pub fn diff(&mut self, old: &str, new: &str, algorithm: Algorithm<'_>) -> &DiffResult<'static> {
+        if self.get(old, new, algorithm).is_none() {
+            let old_lines: Vec<&str> = split_lines(old).collect();
+            let new_lines: Vec<&str> = split_lines(new).collect();
+            let ops = algorithm.diff(&old_lines, &new_lines);
+            self.insert(old, new, algorithm, ops);
+        }
+
+        &self.entries[self.entries.len() - 1].1
+    }
+
+    /// How many diffs are in the cache
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// There are no diffs in the cache
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every diff from the cache
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The diff of two texts if it is in the cache, marking it as the most
+    /// recently used
+    pub(crate) fn get(
+        &mut self,
+        old: &str,
+        new: &str,
+        algorithm: Algorithm<'_>,
+    ) -> Option<&DiffResult<'static>> {
+        let index = self.position(key(old, new, algorithm), old, new)?;
+        let entry = self.entries.remove(index)?;
+        self.entries.push_back(entry);
+
+        self.entries.back().map(|(_, result)| result)
+    }
+
+    /// Add a diff that has been worked out elsewhere to the cache
+    pub(crate) fn insert(
+        &mut self,
+        old: &str,
+        new: &str,
+        algorithm: Algorithm<'_>,
+        ops: Vec<DiffOp>,
+    ) {
+        let key = key(old, new, algorithm);
+        if let Some(index) = self.position(key, old, new) {
+            self.entries.remove(index);
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        let result = DiffResult::from_parts(old.to_string(), new.to_string(), ops);
+        self.entries.push_back((key, result));
+    }
+
+    fn position(&self, key: u64, old: &str, new: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(entry_key, result)| *entry_key == key && result.texts() == (old, new))
+    }
+}
+
+fn key(old: &str, new: &str, algorithm: Algorithm<'_>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (old, new, algorithm).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiffCache;
+    use crate::Algorithm;
+
+    #[test]
+    fn the_least_recently_used_diff_is_dropped() {
+        let mut cache = DiffCache::new(2);
+        cache.diff("a", "b", Algorithm::Myers);
+        cache.diff("c", "d", Algorithm::Myers);
+        cache.diff("a", "b", Algorithm::Myers);
+        cache.diff("e", "f", Algorithm::Myers);
+
+        assert!(cache.get("a", "b", Algorithm::Myers).is_some());
+        assert!(cache.get("c", "d", Algorithm::Myers).is_none());
+        assert!(cache.get("a", "b", Algorithm::Patience).is_none());
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// A flag for stopping a long diff from another thread
+///
+/// Clones share the same flag, so one can be handed to the thread working
+/// out the diff and another kept to cancel it. The algorithm checks the flag
+/// each time it finds another run of equal, deleted or inserted lines, and
+/// gives up once it is set. It can't check it while searching for the next
+/// run, so a diff of long texts with little in common may carry on for a
+/// while after the token is cancelled. Diffs with an
+/// [`Algorithm::Custom`](crate::Algorithm::Custom) algorithm can only be
+/// cancelled before they start.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{CancellationToken, Cancelled, DiffResult};
+/// let token = CancellationToken::new();
+/// assert!(DiffResult::new_cancellable("a\n", "b\n", &token).is_ok());
+///
+/// token.clone().cancel();
+/// assert_eq!(
+///     DiffResult::new_cancellable("a\n", "b\n", &token),
+///     Err(Cancelled)
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// A token that hasn't been cancelled
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop any diff checking this token, or any clone of it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// The token has been cancelled
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The diff was given up on because its [`CancellationToken`] was cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the diff was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
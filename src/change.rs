@@ -0,0 +1,246 @@
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter},
+};
+
+use similar::InlineChange;
+
+use super::line_endings::LineEnding;
+
+/// What happened to a line
+///
+/// With the `serde` feature this can be serialized, as `"equal"`, `"delete"`,
+/// `"insert"`, `"moved_from"` or `"moved_to"`.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::ChangeTag;
+///
+/// assert_eq!(ChangeTag::Delete.as_char(), '-');
+/// assert_eq!(format!("{}foo", ChangeTag::Insert), "+foo");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ChangeTag {
+    /// The line is in both the old and the new text
+    Equal,
+    /// The line was removed from the old text
+    Delete,
+    /// The line was added to the new text
+    Insert,
+    /// The line was removed from here, and inserted elsewhere
+    MovedFrom,
+    /// The line was inserted here, and removed from elsewhere
+    MovedTo,
+}
+
+impl ChangeTag {
+    /// Every tag, in a stable order
+    pub const ALL: [Self; 5] = [
+        Self::Equal,
+        Self::Delete,
+        Self::Insert,
+        Self::MovedFrom,
+        Self::MovedTo,
+    ];
+
+    /// The conventional marker for the tag, as used by unified diffs
+    ///
+    /// Moved lines use the marker of the delete or insert they replace.
+    #[must_use]
+    pub fn as_char(self) -> char {
+        match self {
+            Self::Equal => ' ',
+            Self::Delete | Self::MovedFrom => '-',
+            Self::Insert | Self::MovedTo => '+',
+        }
+    }
+}
+
+impl Display for ChangeTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
+impl From<similar::ChangeTag> for ChangeTag {
+    fn from(tag: similar::ChangeTag) -> Self {
+        match tag {
+            similar::ChangeTag::Equal => Self::Equal,
+            similar::ChangeTag::Delete => Self::Delete,
+            similar::ChangeTag::Insert => Self::Insert,
+        }
+    }
+}
+
+/// A single line of the diff, split into segments that may be emphasized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Change<'a> {
+    tag: ChangeTag,
+    values: Vec<(bool, Cow<'a, str>)>,
+    whitespace_only: bool,
+    line_ending: Option<(LineEnding, LineEnding)>,
+    normalization_changed: bool,
+    missing_newline: bool,
+    line_numbers: (Option<usize>, Option<usize>),
+}
+
+impl<'a> Change<'a> {
+    pub(crate) fn new(tag: ChangeTag, values: Vec<(bool, Cow<'a, str>)>) -> Self {
+        Self {
+            tag,
+            values,
+            whitespace_only: false,
+            line_ending: None,
+            normalization_changed: false,
+            missing_newline: false,
+            line_numbers: (None, None),
+        }
+    }
+
+    pub(crate) fn tag(&self) -> ChangeTag {
+        self.tag
+    }
+
+    pub(crate) fn values(&self) -> &[(bool, Cow<'a, str>)] {
+        &self.values
+    }
+
+    /// The content of the line, without its newline
+    pub(crate) fn content(&self) -> Cow<'_, str> {
+        let content: Cow<'_, str> = match self.values.as_slice() {
+            [(_, value)] => Cow::Borrowed(value.as_ref()),
+            values => values
+                .iter()
+                .map(|(_, value)| value.as_ref())
+                .collect::<String>()
+                .into(),
+        };
+
+        match content {
+            Cow::Borrowed(content) => Cow::Borrowed(content.trim_end_matches(['\r', '\n'])),
+            Cow::Owned(content) => Cow::Owned(content.trim_end_matches(['\r', '\n']).to_string()),
+        }
+    }
+
+    /// The line is blank, or only changed its indentation
+    pub(crate) fn whitespace_only(&self) -> bool {
+        self.whitespace_only
+    }
+
+    pub(crate) fn mark_whitespace_only(&mut self) {
+        self.whitespace_only = true;
+    }
+
+    /// The line is the last line of a text that doesn't end with a newline
+    pub(crate) fn missing_newline(&self) -> bool {
+        self.missing_newline
+    }
+
+    pub(crate) fn mark_missing_newline(&mut self) {
+        self.missing_newline = true;
+    }
+
+    /// The numbers of the line in the old and new text, starting from 1
+    pub(crate) fn line_numbers(&self) -> (Option<usize>, Option<usize>) {
+        self.line_numbers
+    }
+
+    pub(crate) fn set_line_numbers(&mut self, old: Option<usize>, new: Option<usize>) {
+        self.line_numbers = (old, new);
+    }
+
+    /// How the line ending changed, if the values are the line without its
+    /// ending because only the ending changed
+    pub(crate) fn line_ending_change(&self) -> Option<(LineEnding, LineEnding)> {
+        self.line_ending
+    }
+
+    pub(crate) fn with_line_ending_change(mut self, old: LineEnding, new: LineEnding) -> Self {
+        self.line_ending = Some((old, new));
+        self
+    }
+
+    /// The line is only unchanged under a Unicode normalization form, and
+    /// the values are the line without its ending
+    pub(crate) fn normalization_changed(&self) -> bool {
+        self.normalization_changed
+    }
+
+    pub(crate) fn with_normalization_changed(mut self) -> Self {
+        self.normalization_changed = true;
+        self
+    }
+
+    /// The same line, no longer borrowing from anything
+    pub(crate) fn into_owned(self) -> Change<'static> {
+        Change {
+            tag: self.tag,
+            values: self
+                .values
+                .into_iter()
+                .map(|(emphasized, value)| (emphasized, Cow::Owned(value.into_owned())))
+                .collect(),
+            whitespace_only: self.whitespace_only,
+            line_ending: self.line_ending,
+            normalization_changed: self.normalization_changed,
+            missing_newline: self.missing_newline,
+            line_numbers: self.line_numbers,
+        }
+    }
+
+    /// Change what happened to this line, removing any emphasis and
+    /// classification, as they no longer relate to the old tag
+    pub(crate) fn retag(&mut self, tag: ChangeTag) {
+        self.tag = tag;
+        self.whitespace_only = false;
+        for (emphasized, _) in &mut self.values {
+            *emphasized = false;
+        }
+    }
+}
+
+impl<'a> From<InlineChange<'a, str>> for Change<'a> {
+    fn from(change: InlineChange<'a, str>) -> Self {
+        Self::new(
+            change.tag().into(),
+            change
+                .values()
+                .iter()
+                .map(|(emphasized, value)| (*emphasized, Cow::Borrowed(*value)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChangeTag;
+
+    #[test]
+    fn markers_match_unified_diffs() {
+        let markers: String = ChangeTag::ALL.iter().map(ToString::to_string).collect();
+
+        assert_eq!(markers, " -+-+");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn tags_serialize_as_snake_case() {
+        let json = serde_json::to_string(&ChangeTag::ALL).unwrap();
+
+        assert_eq!(
+            json,
+            r#"["equal","delete","insert","moved_from","moved_to"]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<ChangeTag>>(&json).unwrap(),
+            ChangeTag::ALL
+        );
+    }
+}
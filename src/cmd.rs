@@ -1,6 +1,17 @@
-use std::io::Write;
+use std::{
+    cmp::Ordering,
+    io::{BufRead, Write},
+};
 
-use super::{draw_diff::DrawDiff, themes::Theme};
+use super::{
+    color::{enable_ansi_support, ColorChoice},
+    config::DiffConfig,
+    draw_diff::DrawDiff,
+    draw_error::DrawError,
+    lines::split_lines,
+    scratch::DiffScratch,
+    themes::Theme,
+};
 
 /// Print a diff to a writer
 ///
@@ -53,10 +64,457 @@ pub fn diff(w: &mut dyn Write, old: &str, new: &str, theme: &dyn Theme) -> std::
     write!(w, "{output}")
 }
 
+/// Draw a diff into a string
+///
+/// See [`DrawDiff::try_to_string`] to draw a diff with other options this
+/// way.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_to_string, ArrowsTheme};
+/// let theme = ArrowsTheme::default();
+///
+/// assert_eq!(
+///     diff_to_string("a\nb\n", "a\nc\n", &theme).unwrap(),
+///     "< left / > right\n a\n<b\n>c\n"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors if drawing the diff fails, because a theme or highlighter failed
+/// to format something.
+pub fn diff_to_string(old: &str, new: &str, theme: &dyn Theme) -> Result<String, DrawError> {
+    DrawDiff::new(old, new, theme).try_to_string()
+}
+
+/// Draw a diff of two values, pretty printed with their [`Debug`]
+/// implementations
+///
+/// This is handy for test assertions on structs, where the values would
+/// otherwise each need formatting by hand.
+///
+/// [`Debug`]: std::fmt::Debug
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_debug, ArrowsTheme};
+///
+/// #[derive(Debug)]
+/// struct Server {
+///     host: &'static str,
+///     port: u16,
+/// }
+///
+/// let theme = ArrowsTheme::default();
+/// let old = Server {
+///     host: "example.com",
+///     port: 80,
+/// };
+/// let new = Server {
+///     host: "example.com",
+///     port: 8080,
+/// };
+///
+/// assert_eq!(
+///     diff_debug(&old, &new, &theme).unwrap(),
+///     "< left / > right
+///  Server {
+///      host: \"example.com\",
+/// <    port: 80,
+/// >    port: 8080,
+///  }
+/// "
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors if drawing the diff fails, because a theme or highlighter failed
+/// to format something.
+pub fn diff_debug<T: std::fmt::Debug + ?Sized>(
+    old: &T,
+    new: &T,
+    theme: &dyn Theme,
+) -> Result<String, DrawError> {
+    diff_to_string(&format!("{old:#?}\n"), &format!("{new:#?}\n"), theme)
+}
+
+/// Print a diff of text that has already been split into lines to a writer
+///
+/// Lines should keep their line endings, see [`DrawDiff::from_lines`].
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_lines, ArrowsTheme};
+/// let old = ["a\n", "b\n", "c"];
+/// let new = ["a\n", "c\n"];
+/// let mut buffer: Vec<u8> = Vec::new();
+/// let theme = ArrowsTheme::default();
+/// diff_lines(&mut buffer, &old, &new, &theme).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(
+///     actual,
+///     "< left / > right
+///  a
+/// <b
+/// <c
+/// >c␊
+/// "
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+pub fn diff_lines(
+    w: &mut dyn Write,
+    old: &[&str],
+    new: &[&str],
+    theme: &dyn Theme,
+) -> std::io::Result<()> {
+    let output: DrawDiff<'_> = DrawDiff::from_lines(old, new, theme);
+    write!(w, "{output}")
+}
+
+/// Print a diff of lines from two iterators to a writer
+///
+/// Lines should keep their line endings, see [`DrawDiff::from_lines`]. They
+/// don't need to be in one contiguous string, but the diff can't be worked
+/// out until both sides are known, so every line is held until the diff is
+/// drawn.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_iter, ArrowsTheme};
+/// let old = "a\nb\nc\n".split_inclusive('\n');
+/// let new = ["a\n", "c\n"];
+/// let mut buffer: Vec<u8> = Vec::new();
+/// let theme = ArrowsTheme::default();
+/// diff_iter(&mut buffer, old, new, &theme).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(actual, "< left / > right\n a\n<b\n c\n");
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+pub fn diff_iter<'a>(
+    w: &mut dyn Write,
+    old: impl IntoIterator<Item = &'a str>,
+    new: impl IntoIterator<Item = &'a str>,
+    theme: &dyn Theme,
+) -> std::io::Result<()> {
+    let old: Vec<&str> = old.into_iter().collect();
+    let new: Vec<&str> = new.into_iter().collect();
+
+    diff_lines(w, &old, &new, theme)
+}
+
+/// Print a diff of the text from two readers to a writer
+///
+/// The diff can't be worked out until both sides are known, so each reader
+/// is read to its end into a single string first, and the diff is drawn from
+/// lines borrowed from those.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_reader, ArrowsTheme};
+/// let old = std::io::Cursor::new("a\nb\nc\n");
+/// let new = std::io::Cursor::new("a\nc\n");
+/// let mut buffer: Vec<u8> = Vec::new();
+/// let theme = ArrowsTheme::default();
+/// diff_reader(&mut buffer, old, new, &theme).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(actual, "< left / > right\n a\n<b\n c\n");
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to read from either reader, if either isn't valid
+/// UTF-8, or on failing to write to the writer.
+pub fn diff_reader(
+    w: &mut dyn Write,
+    old: impl BufRead,
+    new: impl BufRead,
+    theme: &dyn Theme,
+) -> std::io::Result<()> {
+    let old = read_text(old)?;
+    let new = read_text(new)?;
+
+    diff_iter(w, split_lines(&old), split_lines(&new), theme)
+}
+
+/// Read all the text from a reader into one string
+fn read_text(mut reader: impl BufRead) -> std::io::Result<String> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Print a diff to standard output
+///
+/// On Windows the console is set up to understand the theme's escape
+/// sequences first, and they are left out on legacy consoles that can't, so
+/// they aren't printed as garbage.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{print_diff, ArrowsColorTheme};
+/// let theme = ArrowsColorTheme::default();
+///
+/// print_diff("a\nb\n", "a\nc\n", &theme).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to standard output.
+pub fn print_diff(old: &str, new: &str, theme: &dyn Theme) -> std::io::Result<()> {
+    let color = if enable_ansi_support() {
+        ColorChoice::Always
+    } else {
+        ColorChoice::Never
+    };
+    let output = DrawDiff::new(old, new, theme).with_color(color);
+
+    write!(std::io::stdout().lock(), "{output}")
+}
+
+/// Print a diff to standard output, then ask whether to go ahead with the
+/// changes
+///
+/// An answer of `y` or `yes`, in any case, is `true`. Anything else,
+/// including reaching the end of standard input, is `false`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use termdiff::{confirm_with_diff, ArrowsColorTheme};
+/// let theme = ArrowsColorTheme::default();
+///
+/// if confirm_with_diff("a\nb\n", "a\nc\n", &theme).unwrap() {
+///     // write the changes
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to standard output or read from standard
+/// input.
+pub fn confirm_with_diff(old: &str, new: &str, theme: &dyn Theme) -> std::io::Result<bool> {
+    print_diff(old, new, theme)?;
+    confirm(std::io::stdin().lock(), std::io::stdout().lock())
+}
+
+fn confirm(mut input: impl BufRead, mut output: impl Write) -> std::io::Result<bool> {
+    write!(output, "Apply these changes? [y/N] ")?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Print a diff to a writer, drawn with the options in a [`DiffConfig`]
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_with, ArrowsTheme, DiffConfig};
+/// let theme = ArrowsTheme::default();
+/// let config = DiffConfig::new(&theme).with_context(1);
+/// let mut buffer: Vec<u8> = Vec::new();
+/// diff_with(&mut buffer, "a\nb\nc\nd\n", "a\nb\nc\nD\n", &config).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(actual, "< left / > right\n c\n<d\n>D\n");
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+pub fn diff_with(
+    w: &mut dyn Write,
+    old: &str,
+    new: &str,
+    config: &DiffConfig<'_>,
+) -> std::io::Result<()> {
+    write!(w, "{}", config.draw(old, new))
+}
+
+/// Print a diff to a writer, drawing it in a reusable scratch buffer first
+///
+/// The scratch buffers are cleared before use and keep their capacity
+/// afterwards, so passing the same ones in when printing many diffs avoids
+/// allocating new buffers for each of them.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_with_scratch, ArrowsTheme, DiffScratch};
+/// let theme = ArrowsTheme::default();
+/// let mut scratch = DiffScratch::new();
+/// let mut buffer: Vec<u8> = Vec::new();
+///
+/// for (old, new) in [("a\n", "b\n"), ("c\n", "c\n")] {
+///     diff_with_scratch(&mut buffer, old, new, &theme, &mut scratch).unwrap();
+/// }
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(
+///     actual,
+///     "< left / > right
+/// <a
+/// >b
+/// < left / > right
+///  c
+/// "
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+pub fn diff_with_scratch(
+    w: &mut dyn Write,
+    old: &str,
+    new: &str,
+    theme: &dyn Theme,
+    scratch: &mut DiffScratch,
+) -> std::io::Result<()> {
+    w.write_all(
+        DrawDiff::new(old, new, theme)
+            .render_with(scratch)
+            .as_bytes(),
+    )
+}
+
+/// Print a diff to an async writer
+///
+/// Needs the `async` feature.
+///
+/// # Examples
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread()
+/// #     .build()
+/// #     .unwrap()
+/// #     .block_on(async {
+/// use termdiff::{diff_async, ArrowsTheme};
+/// let old = "a\nb\nc";
+/// let new = "a\nc\n";
+/// let mut buffer: Vec<u8> = Vec::new();
+/// let theme = ArrowsTheme::default();
+/// diff_async(&mut buffer, old, new, &theme).await.unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(
+///     actual,
+///     "< left / > right
+///  a
+/// <b
+/// <c
+/// >c␊
+/// "
+/// );
+/// # });
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+#[cfg(feature = "async")]
+pub async fn diff_async<W>(
+    w: &mut W,
+    old: &str,
+    new: &str,
+    theme: &dyn Theme,
+) -> std::io::Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin + ?Sized,
+{
+    use tokio::io::AsyncWriteExt;
+
+    for chunk in DrawDiff::new(old, new, theme).render_chunks() {
+        let chunk = chunk.map_err(std::io::Error::other)?;
+        w.write_all(chunk.as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+/// The two pieces of text are identical, so a diff of them would be empty
+///
+/// This is much cheaper than working out the diff, so it can be used to skip
+/// printing diffs of text that hasn't changed.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::identical;
+///
+/// assert!(identical("a\nb\n", "a\nb\n"));
+/// assert!(!identical("a\nb\n", "a\nb"));
+/// ```
+#[must_use]
+pub fn identical(old: &str, new: &str) -> bool {
+    old == new
+}
+
+/// Compare two pieces of text a line at a time, stopping at the first line
+/// that differs, without working out the diff
+///
+/// This is `Equal` when a diff of them would be empty, like [`identical`],
+/// and otherwise orders them by the first line that differs, so it can also
+/// be used to sort texts.
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// use termdiff::compare;
+///
+/// assert_eq!(compare("a\nb\n", "a\nb\n"), Ordering::Equal);
+/// assert_eq!(compare("a\nb\n", "a\nc\n"), Ordering::Less);
+/// assert_eq!(compare("b\n", "a\nz\n"), Ordering::Greater);
+/// ```
+#[must_use]
+pub fn compare(old: &str, new: &str) -> Ordering {
+    split_lines(old).cmp(split_lines(new))
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::ArrowsTheme;
-    use crate::ArrowsColorTheme;
+    use super::confirm;
+    use crate::{ArrowsColorTheme, DiffScratch};
+
+    #[test]
+    fn only_yes_confirms() {
+        let answer = |input: &str| {
+            let mut output: Vec<u8> = Vec::new();
+            let confirmed = confirm(input.as_bytes(), &mut output).unwrap();
+            assert_eq!(output, b"Apply these changes? [y/N] ");
+            confirmed
+        };
+
+        assert!(answer("y\n"));
+        assert!(answer(" YES\r\n"));
+        assert!(!answer("n\n"));
+        assert!(!answer("yess\n"));
+        assert!(!answer(""));
+    }
 
     #[test]
     fn single_characters() {
@@ -77,6 +535,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn scratch_buffer_is_reused() {
+        let mut scratch = DiffScratch {
+            output: String::with_capacity(1024),
+            ..DiffScratch::default()
+        };
+        let mut buffer: Vec<u8> = Vec::new();
+        super::diff_with_scratch(&mut buffer, "a\n", "b\n", &ArrowsTheme {}, &mut scratch).unwrap();
+
+        assert_eq!(scratch.output.capacity(), 1024);
+        assert_eq!(scratch.output.as_bytes(), buffer.as_slice());
+    }
+
+    #[test]
+    fn readers_match_text() {
+        let old = "a\r\nb\rc";
+        let new = "a\nc\r";
+        let mut expected: Vec<u8> = Vec::new();
+        let mut actual: Vec<u8> = Vec::new();
+        super::diff(&mut expected, old, new, &ArrowsTheme {}).unwrap();
+        super::diff_reader(&mut actual, old.as_bytes(), new.as_bytes(), &ArrowsTheme {}).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn color_single_characters() {
         let old = "a\nb\nc";
@@ -0,0 +1,352 @@
+use std::{
+    fmt::{Display, Formatter, Write},
+    io::IsTerminal,
+};
+
+/// Whether diffs should be drawn in color
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::ColorChoice;
+///
+/// assert!(ColorChoice::Always.enabled());
+/// assert!(!ColorChoice::Never.enabled());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Use color if standard output is a terminal, unless `NO_COLOR` is set,
+    /// or if `CLICOLOR_FORCE` is set to something other than `0`
+    #[default]
+    Auto,
+    /// Always use the theme's colors and styles, even when piped
+    Always,
+    /// Never use colors or styles, leaving any the theme has out
+    Never,
+}
+
+impl ColorChoice {
+    /// The choice the environment asks for, `Always` when `CLICOLOR_FORCE` is
+    /// set to something other than `0`, `Never` when `NO_COLOR` is set to
+    /// anything, or `Auto` otherwise
+    ///
+    /// Needs the `env` feature, which is on by default.
+    #[cfg(feature = "env")]
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    pub(crate) fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        if var("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+            Self::Always
+        } else if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            Self::Never
+        } else {
+            Self::Auto
+        }
+    }
+
+    /// Colors should be used
+    #[must_use]
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => match Self::from_vars(|name| std::env::var(name).ok()) {
+                Self::Auto => std::io::stdout().is_terminal() && enable_ansi_support(),
+                choice => choice.enabled(),
+            },
+        }
+    }
+}
+
+/// Which color codes to write
+///
+/// The themes write 256 color codes, like `ESC [ 38;5;9 m`. Some minimal
+/// terminals and consoles don't understand these and show them as text, so
+/// they can be swapped for the original 16 color codes, like `ESC [ 91 m`.
+/// The named colors look the same either way.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsColorTheme, DrawDiff, Palette};
+/// let theme = ArrowsColorTheme::default();
+/// let output = DrawDiff::new("a\n", "b\n", &theme)
+///     .with_palette(Palette::Ansi16)
+///     .to_string();
+///
+/// assert!(output.starts_with("\u{1b}[91m< left\u{1b}[39m"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    /// Write colors as the themes do, with 256 color codes
+    #[default]
+    Ansi256,
+    /// Write colors with the 16 color codes, dropping any colors that aren't
+    /// one of the 16
+    Ansi16,
+}
+
+impl Palette {
+    /// The palette the terminal supports, going by `TERM` and `COLORTERM`
+    ///
+    /// This is `Ansi256` when `TERM` names a 256 color or direct color
+    /// terminal, or `COLORTERM` is set, and `Ansi16` otherwise. Needs the
+    /// `env` feature, which is on by default.
+    #[cfg(feature = "env")]
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    #[cfg(any(feature = "env", test))]
+    pub(crate) fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM").unwrap_or_default();
+
+        if term.contains("256color")
+            || term.contains("direct")
+            || var("COLORTERM").is_some_and(|value| !value.is_empty())
+        {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+/// Make sure the terminal understands the escape sequences the color themes
+/// use, returning whether it does
+///
+/// On Windows this turns on virtual terminal processing for the console,
+/// which legacy consoles don't support. Everywhere else it does nothing, as
+/// terminals understand escape sequences already.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{enable_ansi_support, ArrowsColorTheme, ArrowsTheme, Theme};
+/// let theme: &dyn Theme = if enable_ansi_support() {
+///     &ArrowsColorTheme {}
+/// } else {
+///     &ArrowsTheme {}
+/// };
+/// ```
+#[must_use]
+pub fn enable_ansi_support() -> bool {
+    #[cfg(windows)]
+    {
+        crossterm::ansi_support::supports_ansi()
+    }
+    #[cfg(not(windows))]
+    {
+        true
+    }
+}
+
+/// Displays something with any ANSI escape sequences left out
+pub(crate) struct Uncolored<'a>(pub(crate) &'a dyn Display);
+
+impl Display for Uncolored<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            StripEscapes {
+                inner: f,
+                state: State::Text
+            },
+            "{}",
+            self.0
+        )
+    }
+}
+
+/// Displays something with its 256 color codes swapped for 16 color codes
+pub(crate) struct Ansi16<'a>(pub(crate) &'a dyn Display);
+
+impl Display for Ansi16<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            Downsample {
+                inner: f,
+                sequence: None
+            },
+            "{}",
+            self.0
+        )
+    }
+}
+
+/// Writes text on to another writer, rewriting select graphic rendition
+/// sequences to use the 16 color codes
+struct Downsample<'a, 'b> {
+    inner: &'a mut Formatter<'b>,
+    sequence: Option<String>,
+}
+
+impl Write for Downsample<'_, '_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut start = 0;
+
+        for (index, character) in s.char_indices() {
+            match &mut self.sequence {
+                None if character == '\u{1b}' => {
+                    self.inner.write_str(&s[start..index])?;
+                    self.sequence = Some(String::new());
+                }
+                None => {}
+                Some(sequence) => {
+                    sequence.push(character);
+
+                    let finished = !sequence.starts_with('[')
+                        || (sequence.len() > 1 && ('\u{40}'..='\u{7e}').contains(&character));
+                    if finished {
+                        match sequence
+                            .strip_prefix('[')
+                            .and_then(|rest| rest.strip_suffix('m'))
+                        {
+                            Some(parameters) => {
+                                write!(self.inner, "\u{1b}[{}m", downsample(parameters))?;
+                            }
+                            None => write!(self.inner, "\u{1b}{sequence}")?,
+                        }
+                        self.sequence = None;
+                        start = index + character.len_utf8();
+                    }
+                }
+            }
+        }
+
+        if self.sequence.is_none() {
+            self.inner.write_str(&s[start..])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Rewrite the parameters of a select graphic rendition sequence, swapping
+/// 256 color codes for 16 color ones, and resetting colors that can't be
+/// swapped
+fn downsample(parameters: &str) -> String {
+    let codes: Vec<&str> = parameters.split(';').collect();
+    let mut rewritten: Vec<String> = Vec::with_capacity(codes.len());
+    let mut index = 0;
+
+    while index < codes.len() {
+        let layer = match codes[index] {
+            "38" => Some(30),
+            "48" => Some(40),
+            "58" => None,
+            code => {
+                rewritten.push(code.to_string());
+                index += 1;
+                continue;
+            }
+        };
+        let (color, length) = match codes.get(index + 1) {
+            Some(&"5") => (codes.get(index + 2).and_then(|code| code.parse().ok()), 3),
+            Some(&"2") => (None, 5),
+            _ => (None, 1),
+        };
+
+        match (layer, color) {
+            (Some(base), Some(color @ 0..=7)) => rewritten.push((base + color).to_string()),
+            (Some(base), Some(color @ 8..=15)) => {
+                rewritten.push((base + 60 + color - 8).to_string());
+            }
+            (Some(base), _) => rewritten.push((base + 9).to_string()),
+            (None, _) => {}
+        }
+        index += length;
+    }
+
+    rewritten.join(";")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Text,
+    Escape,
+    Sequence,
+}
+
+/// Writes text on to another writer, dropping control sequences like
+/// `ESC [ 38;5;9 m`
+struct StripEscapes<'a, 'b> {
+    inner: &'a mut Formatter<'b>,
+    state: State,
+}
+
+impl Write for StripEscapes<'_, '_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut start = 0;
+
+        for (index, character) in s.char_indices() {
+            self.state = match (self.state, character) {
+                (State::Text, '\u{1b}') => {
+                    self.inner.write_str(&s[start..index])?;
+                    State::Escape
+                }
+                (State::Text, _) => continue,
+                (State::Escape, '[') => State::Sequence,
+                (State::Sequence, '\u{40}'..='\u{7e}') | (State::Escape, _) => {
+                    start = index + character.len_utf8();
+                    State::Text
+                }
+                (State::Sequence, _) => State::Sequence,
+            };
+        }
+
+        if self.state == State::Text {
+            self.inner.write_str(&s[start..])?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ansi16, ColorChoice, Palette, Uncolored};
+
+    #[test]
+    fn escapes_are_removed() {
+        let styled = "\u{1b}[38;5;9m<\u{1b}[39m a \u{1b}[4mb\u{1b}[0m\n";
+
+        assert_eq!(Uncolored(&styled).to_string(), "< a b\n");
+    }
+
+    #[test]
+    fn forcing_beats_no_color() {
+        let choice = |force: &str, no_color: &str| {
+            ColorChoice::from_vars(|name| match name {
+                "CLICOLOR_FORCE" => Some(force.to_string()),
+                "NO_COLOR" => Some(no_color.to_string()),
+                _ => None,
+            })
+        };
+
+        assert_eq!(choice("1", "1"), ColorChoice::Always);
+        assert_eq!(choice("0", "1"), ColorChoice::Never);
+        assert_eq!(choice("0", ""), ColorChoice::Auto);
+    }
+
+    #[test]
+    fn colors_are_swapped_for_the_sixteen_color_codes() {
+        let styled = "\u{1b}[38;5;9m<\u{1b}[39m \u{1b}[1;38;5;2;48;5;200ma\u{1b}[0m \u{1b}[38;2;1;2;3mb\u{1b}[K\n";
+
+        assert_eq!(
+            Ansi16(&styled).to_string(),
+            "\u{1b}[91m<\u{1b}[39m \u{1b}[1;32;49ma\u{1b}[0m \u{1b}[39mb\u{1b}[K\n"
+        );
+        assert_eq!(
+            Palette::from_vars(|name| (name == "TERM").then(|| "xterm-256color".to_string())),
+            Palette::Ansi256
+        );
+        assert_eq!(
+            Palette::from_vars(|name| (name == "TERM").then(|| "linux".to_string())),
+            Palette::Ansi16
+        );
+    }
+}
@@ -0,0 +1,422 @@
+#[cfg(feature = "env")]
+use super::{
+    algorithm::named,
+    themes::{ArrowsColorTheme, ArrowsTheme, MonochromeTheme, SignsColorTheme, SignsTheme},
+};
+use super::{
+    algorithm::Algorithm,
+    color::{ColorChoice, Palette},
+    draw_diff::DrawDiff,
+    granularity::Granularity,
+    themes::{AccessibleTheme, Theme},
+    whitespace::WhitespaceChanges,
+};
+#[cfg(feature = "env")]
+use std::io::IsTerminal;
+
+#[cfg(feature = "env")]
+static ACCESSIBLE: AccessibleTheme = AccessibleTheme {};
+#[cfg(feature = "env")]
+static ARROWS: ArrowsTheme = ArrowsTheme {};
+#[cfg(feature = "env")]
+static ARROWS_COLOR: ArrowsColorTheme = ArrowsColorTheme {};
+#[cfg(feature = "env")]
+static MONOCHROME: MonochromeTheme = MonochromeTheme {};
+#[cfg(feature = "env")]
+static SIGNS: SignsTheme = SignsTheme {};
+#[cfg(feature = "env")]
+static SIGNS_COLOR: SignsColorTheme = SignsColorTheme {};
+
+/// The options for drawing diffs, bundled together so they can be passed
+/// around and reused
+///
+/// The theme has to be [`Sync`], so the same options can draw diffs on many
+/// threads at once, as [`diff_batch`](crate::diff_batch) does.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_with, Algorithm, ArrowsTheme, DiffConfig, Granularity};
+/// let theme = ArrowsTheme::default();
+/// let config = DiffConfig::new(&theme)
+///     .with_algorithm(Algorithm::Patience)
+///     .with_granularity(Granularity::Line)
+///     .with_context(0);
+/// let mut buffer: Vec<u8> = Vec::new();
+/// diff_with(&mut buffer, "a\nb\nc\n", "a\nB\nc\n", &config).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(actual, "< left / > right\n@@ line 2 @@\n<b\n>B\n");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DiffConfig<'a> {
+    theme: &'a (dyn Theme + Sync),
+    algorithm: Algorithm<'a>,
+    granularity: Granularity,
+    context: Option<usize>,
+    color: ColorChoice,
+    palette: Palette,
+    whitespace_changes: WhitespaceChanges,
+}
+
+impl<'a> DiffConfig<'a> {
+    /// Options that draw diffs with a theme, and the defaults for everything
+    /// else
+    #[must_use]
+    pub fn new(theme: &'a (dyn Theme + Sync)) -> Self {
+        Self {
+            theme,
+            algorithm: Algorithm::default(),
+            granularity: Granularity::default(),
+            context: None,
+            color: ColorChoice::Always,
+            palette: Palette::Ansi256,
+            whitespace_changes: WhitespaceChanges::default(),
+        }
+    }
+
+    /// Options suited to source code
+    ///
+    /// Uses the patience algorithm, which keeps functions and blocks
+    /// together, highlights indentation-only changes, and shows three lines
+    /// of context around each change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{diff_with, ArrowsTheme, DiffConfig};
+    /// let theme = ArrowsTheme::default();
+    /// let old = "fn a() {\n1\n2\n3\n4\n5\n}\n";
+    /// let new = "fn a() {\n1\n2\n3\n4\nfive\n}\n";
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// diff_with(&mut buffer, old, new, &DiffConfig::code(&theme)).unwrap();
+    /// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+    ///
+    /// assert_eq!(actual, "< left / > right\n 2\n 3\n 4\n<5\n>five\n }\n");
+    /// ```
+    #[must_use]
+    pub fn code(theme: &'a (dyn Theme + Sync)) -> Self {
+        Self::new(theme)
+            .with_algorithm(Algorithm::Patience)
+            .with_whitespace_changes(WhitespaceChanges::Highlight)
+            .with_context(3)
+    }
+
+    /// Options suited to paragraphs of text, like documentation
+    ///
+    /// Highlights changed words, ignores lines that only differ in blank
+    /// lines or indentation, and shows the whole text. For text that may
+    /// have been rewrapped, see [`SentenceDiff`](crate::SentenceDiff).
+    #[must_use]
+    pub fn prose(theme: &'a (dyn Theme + Sync)) -> Self {
+        Self::new(theme)
+            .with_granularity(Granularity::Word)
+            .with_whitespace_changes(WhitespaceChanges::Ignore)
+    }
+
+    /// Options suited to screen readers
+    ///
+    /// Draws with the [`AccessibleTheme`], which marks
+    /// changed words with `[DEL]` and `[INS]`, and leaves out colors, so
+    /// nothing relies on color to be understood.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{diff_with, DiffConfig};
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// diff_with(&mut buffer, "a fox\n", "a cat\n", &DiffConfig::accessible()).unwrap();
+    /// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+    ///
+    /// assert_eq!(
+    ///     actual,
+    ///     "- deleted, + inserted\n- a [DEL]fox[/DEL]\n+ a [INS]cat[/INS]\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn accessible() -> DiffConfig<'static> {
+        DiffConfig::new(&AccessibleTheme {})
+            .with_granularity(Granularity::Word)
+            .with_color(ColorChoice::Never)
+    }
+
+    /// Options suited to log files
+    ///
+    /// Compares whole lines, because timestamps and ids make highlighting
+    /// the changes within lines noisy, and shows one line of context around
+    /// each change.
+    #[must_use]
+    pub fn logs(theme: &'a (dyn Theme + Sync)) -> Self {
+        Self::new(theme)
+            .with_granularity(Granularity::Line)
+            .with_context(1)
+    }
+
+    /// Options read from the environment, falling back to the
+    /// [`ArrowsColorTheme`], [`ColorChoice::Auto`], the [`Palette`] the
+    /// terminal supports, and the defaults for anything not set
+    ///
+    /// When `NO_COLOR` is set, no theme is picked with `TERMDIFF_THEME`, and
+    /// standard output is a terminal, the [`MonochromeTheme`] is used, as
+    /// `NO_COLOR` only asks for no colors and bold and underlined text still
+    /// shows changes well.
+    ///
+    /// See [`DiffConfig::with_env`] for the variables that are read. Needs the
+    /// `env` feature, which is on by default.
+    #[cfg(feature = "env")]
+    #[must_use]
+    pub fn from_env() -> DiffConfig<'static> {
+        DiffConfig::new(&ARROWS_COLOR)
+            .with_color(ColorChoice::Auto)
+            .with_palette(Palette::from_env())
+            .with_env()
+            .with_monochrome_fallback(
+                |name| std::env::var(name).ok(),
+                std::io::stdout().is_terminal(),
+            )
+    }
+
+    #[cfg(feature = "env")]
+    fn with_monochrome_fallback(
+        self,
+        var: impl Fn(&str) -> Option<String>,
+        terminal: bool,
+    ) -> Self {
+        let no_color = self.color == ColorChoice::Never
+            && var("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+        if no_color && terminal && var("TERMDIFF_THEME").is_none() {
+            self.with_theme(&MONOCHROME).with_color(ColorChoice::Always)
+        } else {
+            self
+        }
+    }
+
+    /// Override options with any set in the environment, so people running
+    /// a program can pick how its diffs look
+    ///
+    /// * `TERMDIFF_THEME` is one of `accessible`, `arrows`, `arrows-color`,
+    ///   `monochrome`, `signs` or `signs-color`
+    /// * `TERMDIFF_ALGORITHM` is one of `myers`, `patience`, `lcs` or the name
+    ///   of an algorithm registered with
+    ///   [`register_algorithm`](crate::register_algorithm)
+    /// * `TERMDIFF_CONTEXT` is the number of unchanged lines to show around
+    ///   changes
+    /// * `CLICOLOR_FORCE` and `NO_COLOR` force color on or off, see
+    ///   [`ColorChoice::from_env`]
+    ///
+    /// Variables with values that aren't recognised are ignored. Needs the
+    /// `env` feature, which is on by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffConfig};
+    /// let theme = ArrowsTheme::default();
+    /// std::env::set_var("TERMDIFF_CONTEXT", "2");
+    ///
+    /// assert_eq!(DiffConfig::new(&theme).with_env().context(), Some(2));
+    /// ```
+    #[cfg(feature = "env")]
+    #[must_use]
+    pub fn with_env(self) -> Self {
+        self.with_vars(|name| std::env::var(name).ok())
+    }
+
+    #[cfg(feature = "env")]
+    fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        let theme: Option<&'static (dyn Theme + Sync)> = match var("TERMDIFF_THEME").as_deref() {
+            Some("accessible") => Some(&ACCESSIBLE),
+            Some("arrows") => Some(&ARROWS),
+            Some("arrows-color") => Some(&ARROWS_COLOR),
+            Some("monochrome") => Some(&MONOCHROME),
+            Some("signs") => Some(&SIGNS),
+            Some("signs-color") => Some(&SIGNS_COLOR),
+            _ => None,
+        };
+        if let Some(theme) = theme {
+            self.theme = theme;
+        }
+        if let Some(algorithm) = var("TERMDIFF_ALGORITHM").as_deref().and_then(named) {
+            self.algorithm = algorithm;
+        }
+        match ColorChoice::from_vars(&var) {
+            ColorChoice::Auto => {}
+            color => self.color = color,
+        }
+        if let Some(context) = var("TERMDIFF_CONTEXT").and_then(|value| value.parse().ok()) {
+            self.context = Some(context);
+        }
+
+        self
+    }
+
+    /// The theme to draw with
+    #[must_use]
+    pub fn theme(&self) -> &'a (dyn Theme + Sync) {
+        self.theme
+    }
+
+    /// The algorithm to work out the diff with
+    #[must_use]
+    pub fn algorithm(&self) -> Algorithm<'a> {
+        self.algorithm
+    }
+
+    /// How finely to compare changed lines
+    #[must_use]
+    pub fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    /// How many unchanged lines to show around changes, if they aren't all
+    /// shown
+    #[must_use]
+    pub fn context(&self) -> Option<usize> {
+        self.context
+    }
+
+    /// Whether to keep the colors of the theme
+    #[must_use]
+    pub fn color(&self) -> ColorChoice {
+        self.color
+    }
+
+    /// What to do with changes that are only whitespace
+    #[must_use]
+    pub fn whitespace_changes(&self) -> WhitespaceChanges {
+        self.whitespace_changes
+    }
+
+    /// See [`DrawDiff::with_color`]
+    #[must_use]
+    pub fn with_color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Draw with a different theme
+    #[must_use]
+    pub fn with_theme(mut self, theme: &'a (dyn Theme + Sync)) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// See [`DrawDiff::with_algorithm`]
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: Algorithm<'a>) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// See [`DrawDiff::with_granularity`]
+    #[must_use]
+    pub fn with_granularity(mut self, granularity: Granularity) -> Self {
+        self.granularity = granularity;
+        self
+    }
+
+    /// See [`DrawDiff::with_context`]
+    #[must_use]
+    pub fn with_context(mut self, context: usize) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Which color codes to write
+    #[must_use]
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    /// See [`DrawDiff::with_palette`]
+    #[must_use]
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// See [`DrawDiff::with_whitespace_changes`]
+    #[must_use]
+    pub fn with_whitespace_changes(mut self, whitespace_changes: WhitespaceChanges) -> Self {
+        self.whitespace_changes = whitespace_changes;
+        self
+    }
+
+    /// A diff of two pieces of text, drawn with these options
+    #[must_use]
+    pub fn draw<'b>(&self, old: &'b str, new: &'b str) -> DrawDiff<'b>
+    where
+        'a: 'b,
+    {
+        let diff = DrawDiff::new(old, new, self.theme)
+            .with_algorithm(self.algorithm)
+            .with_color(self.color)
+            .with_palette(self.palette)
+            .with_granularity(self.granularity)
+            .with_whitespace_changes(self.whitespace_changes);
+
+        match self.context {
+            Some(context) => diff.with_context(context),
+            None => diff,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "env"))]
+mod tests {
+    use super::DiffConfig;
+    use crate::{Algorithm, ArrowsTheme, ColorChoice};
+
+    #[test]
+    fn variables_override_the_options() {
+        let theme = ArrowsTheme::default();
+        let config = DiffConfig::new(&theme).with_vars(|name| match name {
+            "TERMDIFF_THEME" => Some("signs".to_string()),
+            "TERMDIFF_ALGORITHM" => Some("patience".to_string()),
+            "TERMDIFF_CONTEXT" => Some("3".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(format!("{:?}", config.theme()), "SignsTheme");
+        assert_eq!(config.algorithm(), Algorithm::Patience);
+        assert_eq!(config.context(), Some(3));
+        assert_eq!(config.color(), ColorChoice::Always);
+    }
+
+    #[test]
+    fn color_can_be_turned_off() {
+        let theme = ArrowsTheme::default();
+        let config =
+            DiffConfig::new(&theme).with_vars(|name| (name == "NO_COLOR").then(|| "1".to_string()));
+
+        assert_eq!(config.color(), ColorChoice::Never);
+    }
+
+    #[test]
+    fn no_color_on_a_terminal_falls_back_to_monochrome() {
+        let var = |name: &str| (name == "NO_COLOR").then(|| "1".to_string());
+        let config = |terminal| {
+            DiffConfig::new(&super::ARROWS_COLOR)
+                .with_vars(var)
+                .with_monochrome_fallback(var, terminal)
+        };
+
+        assert_eq!(format!("{:?}", config(true).theme()), "MonochromeTheme");
+        assert_eq!(config(true).color(), ColorChoice::Always);
+        assert_eq!(format!("{:?}", config(false).theme()), "ArrowsColorTheme");
+        assert_eq!(config(false).color(), ColorChoice::Never);
+    }
+
+    #[test]
+    fn unrecognised_values_are_ignored() {
+        let theme = ArrowsTheme::default();
+        let config = DiffConfig::new(&theme)
+            .with_context(1)
+            .with_vars(|_| Some("nonsense".to_string()));
+
+        assert_eq!(format!("{:?}", config.theme()), "ArrowsTheme");
+        assert_eq!(config.algorithm(), Algorithm::Myers);
+        assert_eq!(config.context(), Some(1));
+    }
+}
@@ -0,0 +1,114 @@
+use super::algorithm::Algorithm;
+
+/// What is being diffed, for themes that describe the diff in its header
+///
+/// The header is only worked out once the diff has been, so the counts of
+/// changes are known. Themes get `&self`, so one that wants to remember
+/// something between diffs can keep it in a `Cell` or similar.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use termdiff::{DiffContext, DrawDiff, Theme};
+///
+/// /// Say what is being compared, and how much changed
+/// #[derive(Debug)]
+/// struct SummaryTheme {}
+/// impl Theme for SummaryTheme {
+///     fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+///         " ".into()
+///     }
+///
+///     fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+///         "-".into()
+///     }
+///
+///     fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+///         "+".into()
+///     }
+///
+///     fn header<'this>(&self) -> Cow<'this, str> {
+///         "".into()
+///     }
+///
+///     fn header_with_context<'this>(&self, context: &DiffContext<'_>) -> Cow<'this, str> {
+///         format!(
+///             "comparing {} vs {} ({} changes)\n",
+///             context.old_label().unwrap_or("old"),
+///             context.new_label().unwrap_or("new"),
+///             context.insertions() + context.deletions()
+///         )
+///         .into()
+///     }
+/// }
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DrawDiff::new("a\nb\n", "a\nc\n", &SummaryTheme {}).with_labels("expected", "actual")
+///     ),
+///     "comparing expected vs actual (2 changes)\n a\n-b\n+c\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiffContext<'a> {
+    old_label: Option<&'a str>,
+    new_label: Option<&'a str>,
+    insertions: usize,
+    deletions: usize,
+    algorithm: Algorithm<'a>,
+}
+
+impl<'a> DiffContext<'a> {
+    /// Describe a diff, with no labels
+    #[must_use]
+    pub fn new(insertions: usize, deletions: usize, algorithm: Algorithm<'a>) -> Self {
+        Self {
+            old_label: None,
+            new_label: None,
+            insertions,
+            deletions,
+            algorithm,
+        }
+    }
+
+    /// Name the old and new text
+    #[must_use]
+    pub fn with_labels(mut self, old: &'a str, new: &'a str) -> Self {
+        self.old_label = Some(old);
+        self.new_label = Some(new);
+        self
+    }
+
+    /// The name of the old text, if it was given one
+    #[must_use]
+    pub fn old_label(&self) -> Option<&'a str> {
+        self.old_label
+    }
+
+    /// The name of the new text, if it was given one
+    #[must_use]
+    pub fn new_label(&self) -> Option<&'a str> {
+        self.new_label
+    }
+
+    /// How many lines were added
+    #[must_use]
+    pub fn insertions(&self) -> usize {
+        self.insertions
+    }
+
+    /// How many lines were removed
+    #[must_use]
+    pub fn deletions(&self) -> usize {
+        self.deletions
+    }
+
+    /// The algorithm the diff was worked out with
+    #[must_use]
+    pub fn algorithm(&self) -> Algorithm<'a> {
+        self.algorithm
+    }
+}
@@ -0,0 +1,83 @@
+use super::{
+    change::ChangeTag, granularity::Granularity, inline::tokens, intern::diff_lines,
+    lines::split_lines, ops::from_similar,
+};
+
+/// How many lines, words or characters need changing to turn one piece of
+/// text into another
+///
+/// This is worked out from the same diff that would be drawn. A removed
+/// token followed by an added one counts as a single substitution, like the
+/// Levenshtein distance. As the diff isn't always the shortest possible
+/// edit, the result can be higher than the true Levenshtein distance.
+/// Counting words, the whitespace between them isn't counted.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{distance, Granularity};
+///
+/// assert_eq!(distance("kitten\n", "sitting\n", Granularity::Char), 3);
+/// assert_eq!(distance("a cat sat\n", "a dog sat\n", Granularity::Word), 1);
+/// assert_eq!(distance("a\nb\nc\n", "a\nc\nd\n", Granularity::Line), 2);
+/// ```
+#[must_use]
+pub fn distance(old: &str, new: &str, granularity: Granularity) -> usize {
+    let old_lines: Vec<&str> = split_lines(old).collect();
+    let new_lines: Vec<&str> = split_lines(new).collect();
+    let (old_tokens, new_tokens) = match granularity {
+        Granularity::Line => (old_lines, new_lines),
+        Granularity::Word | Granularity::Char => (
+            tokens(&old_lines, granularity),
+            tokens(&new_lines, granularity),
+        ),
+    };
+    let ops = from_similar(&diff_lines(&old_tokens, &new_tokens));
+    // The whitespace between words is split out as tokens of its own, but
+    // isn't a word
+    let counted = |tokens: &[&str]| match granularity {
+        Granularity::Word => tokens
+            .iter()
+            .filter(|token| !token.trim().is_empty())
+            .count(),
+        Granularity::Line | Granularity::Char => tokens.len(),
+    };
+
+    let (mut total, mut deleted, mut inserted) = (0, 0, 0);
+    for op in &ops {
+        match op.tag() {
+            ChangeTag::Equal => {
+                total += deleted.max(inserted);
+                deleted = 0;
+                inserted = 0;
+            }
+            ChangeTag::Delete | ChangeTag::MovedFrom => {
+                deleted += counted(&old_tokens[op.old_range()]);
+            }
+            ChangeTag::Insert | ChangeTag::MovedTo => {
+                inserted += counted(&new_tokens[op.new_range()]);
+            }
+        }
+    }
+
+    total + deleted.max(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::distance;
+    use crate::Granularity;
+
+    #[test]
+    fn identical_and_empty_texts() {
+        assert_eq!(distance("same\n", "same\n", Granularity::Char), 0);
+        assert_eq!(distance("", "abc", Granularity::Char), 3);
+        assert_eq!(distance("one two\n", "", Granularity::Word), 2);
+    }
+
+    #[test]
+    fn whitespace_is_not_counted_as_words() {
+        assert_eq!(distance("a b\n", "a  b\n", Granularity::Word), 0);
+        assert_eq!(distance("a b\n", "a b c d\n", Granularity::Word), 2);
+    }
+}
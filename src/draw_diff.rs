@@ -1,20 +1,102 @@
 use std::{
     borrow::{Borrow, Cow},
-    fmt::{Display, Formatter},
+    cell::Cell,
+    fmt::{Display, Formatter, Write},
+    mem::take,
+    ops::Range,
 };
 
-use similar::{ChangeTag, DiffableStr, TextDiff};
+use crossterm::style::ContentStyle;
+use similar::TextDiff;
 
-use super::themes::Theme;
+#[cfg(feature = "normalization")]
+use super::normalization::Normalization;
+use super::{
+    algorithm::Algorithm,
+    ascii::Ascii,
+    cancellation::{CancellationToken, Cancelled},
+    change::{Change, ChangeTag},
+    color::{Ansi16, ColorChoice, Palette, Uncolored},
+    diff_context::DiffContext,
+    draw_error::DrawError,
+    framed::{without_final_newline, Framed, Indented},
+    granularity::Granularity,
+    header::Header,
+    highlight::{apply_spans, Highlighter},
+    ignore_regions::{collapse_regions, mask_regions},
+    inline::{inline_changes, unemphasized},
+    line_endings::{split_ending, LineEnding},
+    line_info::LineInfo,
+    lines::Input,
+    moves::MoveDetection,
+    newline::NewlinePolicy,
+    ops::{merge_into, DiffOp, OpTransform},
+    pairing::{paired_inline_changes, LinePairing},
+    post_process::PostProcessor,
+    progress::Progress,
+    redact::{redact_line, Redactor},
+    replace_order::{order_replaced, ReplaceOrder},
+    result::similarity,
+    scratch::{recycle, DiffScratch},
+    themes::{prefix_column, Theme},
+    whitespace::{classify_whitespace, ignore_whitespace, WhitespaceChanges},
+    width::{display_width, fit_width},
+};
+
+/// Options to set on each [`DrawDiff`] drawn for a set of diffs, by calling
+/// `with_` methods on it
+pub(crate) type DrawOptions<'a> = Box<dyn Fn(DrawDiff<'_>) -> DrawDiff<'_> + 'a>;
+
+const FIRST_STRONG_ISOLATE: char = '\u{2068}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+const LEFT_TO_RIGHT_MARK: char = '\u{200e}';
 
 /// The struct that draws the diff
 ///
 /// Uses similar under the hood
 #[derive(Debug)]
+// Each option is an independent switch set by its own `with_` method
+#[allow(clippy::struct_excessive_bools)]
 pub struct DrawDiff<'a> {
-    old: &'a str,
-    new: &'a str,
+    old: Input<'a>,
+    new: Input<'a>,
     theme: &'a dyn Theme,
+    /// The theme's prefix width, worked out once rather than for each line
+    prefix_width: usize,
+    highlighter: Option<&'a dyn Highlighter>,
+    redactor: Option<&'a dyn Redactor>,
+    post_processor: Option<&'a dyn PostProcessor>,
+    labels: Option<(&'a str, &'a str)>,
+    minimal_inline: bool,
+    move_detection: Option<usize>,
+    whitespace_changes: WhitespaceChanges,
+    ignore_markers: Option<(&'a str, &'a str)>,
+    line_pairing: LinePairing,
+    replace_order: ReplaceOrder,
+    transforms: Vec<&'a dyn OpTransform>,
+    ops: Option<&'a [DiffOp]>,
+    line_ending_markers: bool,
+    trailing_whitespace: bool,
+    bidi_isolates: bool,
+    ascii_only: bool,
+    indent: Option<&'a str>,
+    newline_policy: NewlinePolicy,
+    #[cfg(feature = "normalization")]
+    normalization: Option<Normalization>,
+    wrap_width: Option<usize>,
+    header: Header,
+    only_changes: bool,
+    algorithm: Algorithm<'a>,
+    progress: Option<Progress<'a>>,
+    cancellation: Option<&'a CancellationToken>,
+    granularity: Granularity,
+    context: Option<usize>,
+    color: ColorChoice,
+    palette: Palette,
+    similarity_threshold: Option<f64>,
+    merge_hunks_within: usize,
+    hunk_headers: bool,
+    final_newline: bool,
 }
 
 impl<'input> DrawDiff<'input> {
@@ -44,12 +126,1621 @@ impl<'input> DrawDiff<'input> {
     /// ```
     #[must_use]
     pub fn new<'a>(old: &'a str, new: &'a str, theme: &'a dyn Theme) -> DrawDiff<'a> {
-        DrawDiff { old, new, theme }
+        DrawDiff::from_input(Input::Text(old), Input::Text(new), theme)
+    }
+
+    /// Make a new instance of the diff drawer from text that has already been
+    /// split into lines
+    ///
+    /// Lines should keep their line endings, as [`str::split_inclusive`]
+    /// does. A line without one is treated as the end of text with no
+    /// trailing newline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let old = ["The quick brown fox and\n", "jumps over the sleepy dog\n"];
+    /// let new = ["The quick brown fox and\n", "jumps over the lazy dog\n"];
+    /// assert_eq!(
+    ///     format!("{}", DrawDiff::from_lines(&old, &new, &theme)),
+    ///     "< left / > right
+    ///  The quick brown fox and
+    /// <jumps over the sleepy dog
+    /// >jumps over the lazy dog
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_lines<'a>(
+        old: &'a [&'a str],
+        new: &'a [&'a str],
+        theme: &'a dyn Theme,
+    ) -> DrawDiff<'a> {
+        DrawDiff::from_input(Input::Lines(old), Input::Lines(new), theme)
+    }
+
+    fn from_input<'a>(old: Input<'a>, new: Input<'a>, theme: &'a dyn Theme) -> DrawDiff<'a> {
+        DrawDiff {
+            old,
+            new,
+            theme,
+            prefix_width: theme.prefix_width(),
+            highlighter: None,
+            redactor: None,
+            post_processor: None,
+            labels: None,
+            minimal_inline: false,
+            move_detection: None,
+            whitespace_changes: WhitespaceChanges::Show,
+            ignore_markers: None,
+            line_pairing: LinePairing::Block,
+            replace_order: ReplaceOrder::Blocks,
+            transforms: vec![],
+            ops: None,
+            line_ending_markers: false,
+            trailing_whitespace: false,
+            bidi_isolates: false,
+            ascii_only: false,
+            indent: None,
+            newline_policy: NewlinePolicy::Mark,
+            #[cfg(feature = "normalization")]
+            normalization: None,
+            wrap_width: None,
+            header: Header::Always,
+            only_changes: false,
+            algorithm: Algorithm::Myers,
+            progress: None,
+            cancellation: None,
+            granularity: Granularity::Word,
+            context: None,
+            color: ColorChoice::Always,
+            palette: Palette::Ansi256,
+            similarity_threshold: None,
+            merge_hunks_within: 0,
+            hunk_headers: false,
+            final_newline: true,
+        }
+    }
+
+    /// Style the content of lines with a [`Highlighter`] before the theme is
+    /// applied
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Range;
+    ///
+    /// use crossterm::style::{ContentStyle, Stylize};
+    /// use termdiff::{DrawDiff, Highlighter, SignsTheme};
+    ///
+    /// #[derive(Debug)]
+    /// struct FirstCharacterBold {}
+    /// impl Highlighter for FirstCharacterBold {
+    ///     fn highlight_line(&self, _line: &str) -> Vec<(Range<usize>, ContentStyle)> {
+    ///         vec![(0..1, ContentStyle::new().bold())]
+    ///     }
+    /// }
+    ///
+    /// let theme = SignsTheme::default();
+    /// let highlighter = FirstCharacterBold {};
+    /// let actual = format!(
+    ///     "{}",
+    ///     DrawDiff::new("a\nb\n", "a\nc\n", &theme).with_highlighter(&highlighter)
+    /// );
+    ///
+    /// assert_eq!(
+    ///     actual,
+    ///     "--- remove | insert +++\n \u{1b}[1ma\u{1b}[0m\n-\u{1b}[1mb\u{1b}[0m\n+\u{1b}[1mc\u{1b}[0m\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_highlighter(mut self, highlighter: &'input dyn Highlighter) -> Self {
+        self.highlighter = Some(highlighter);
+        self
+    }
+
+    /// Name the old and new text, for themes that use them in the header,
+    /// see [`DiffContext`]
+    #[must_use]
+    pub fn with_labels(mut self, old: &'input str, new: &'input str) -> Self {
+        self.labels = Some((old, new));
+        self
+    }
+
+    /// Mask the content of lines with a [`Redactor`] before they are drawn,
+    /// while still diffing the real text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use termdiff::{ArrowsTheme, DrawDiff, Redactor};
+    ///
+    /// /// Hide the values of secrets in an env file
+    /// #[derive(Debug)]
+    /// struct Secrets {}
+    /// impl Redactor for Secrets {
+    ///     fn redact<'line>(&self, line: &'line str) -> Cow<'line, str> {
+    ///         match line.split_once('=') {
+    ///             Some((key, _)) if key.ends_with("_TOKEN") => format!("{key}=****").into(),
+    ///             _ => line.into(),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let theme = ArrowsTheme::default();
+    /// let redactor = Secrets {};
+    /// let actual = format!(
+    ///     "{}",
+    ///     DrawDiff::new(
+    ///         "HOST=a\nAPI_TOKEN=hunter2\n",
+    ///         "HOST=b\nAPI_TOKEN=hunter3\n",
+    ///         &theme
+    ///     )
+    ///     .with_redactor(&redactor)
+    /// );
+    ///
+    /// assert_eq!(
+    ///     actual,
+    ///     "< left / > right\n<HOST=a\n<API_TOKEN=****\n>HOST=b\n>API_TOKEN=****\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: &'input dyn Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Pass each drawn line through a [`PostProcessor`] before it is
+    /// written, to leave it out or rewrite it
+    ///
+    /// The processor sees each line once it is colored, made ASCII and
+    /// indented as asked for, and what it gives back is written as it is.
+    #[must_use]
+    pub fn with_post_processor(mut self, post_processor: &'input dyn PostProcessor) -> Self {
+        self.post_processor = Some(post_processor);
+        self
+    }
+
+    /// Only emphasize the parts of changed lines that actually differ
+    ///
+    /// Adjacent changed words are emphasized as one run, and the characters
+    /// that replaced words have in common at their start and end are left
+    /// unemphasized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{DrawDiff, Theme};
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Debug)]
+    /// struct BracketsTheme {}
+    /// impl Theme for BracketsTheme {
+    ///     fn highlight_insert<'this>(&self, input: &'this str) -> Cow<'this, str> {
+    ///         format!("[{}]", input).into()
+    ///     }
+    ///
+    ///     fn highlight_delete<'this>(&self, input: &'this str) -> Cow<'this, str> {
+    ///         format!("[{}]", input).into()
+    ///     }
+    ///
+    ///     fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         " ".into()
+    ///     }
+    ///
+    ///     fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "-".into()
+    ///     }
+    ///
+    ///     fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "+".into()
+    ///     }
+    ///
+    ///     fn header<'this>(&self) -> Cow<'this, str> {
+    ///         "".into()
+    ///     }
+    /// }
+    ///
+    /// let theme = BracketsTheme {};
+    /// let old = "print(foo(bar), x)\n";
+    /// let new = "print(foo(baz), x)\n";
+    ///
+    /// assert_eq!(
+    ///     format!("{}", DrawDiff::new(old, new, &theme)),
+    ///     "-[print(foo(bar),] x)\n+[print(foo(baz),] x)\n"
+    /// );
+    /// assert_eq!(
+    ///     format!("{}", DrawDiff::new(old, new, &theme).with_minimal_inline(true)),
+    ///     "-print(foo(ba[r]), x)\n+print(foo(ba[z]), x)\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_minimal_inline(mut self, minimal_inline: bool) -> Self {
+        self.minimal_inline = minimal_inline;
+        self
+    }
+
+    /// Tag blocks of lines that were removed in one place and inserted in
+    /// another as moved, so the theme can style them apart from other changes
+    ///
+    /// This is like `git diff --color-moved`. Blocks shorter than
+    /// `min_lines` lines are left as removals and insertions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use termdiff::{DrawDiff, Theme};
+    ///
+    /// #[derive(Debug)]
+    /// struct MovesTheme {}
+    /// impl Theme for MovesTheme {
+    ///     fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         " ".into()
+    ///     }
+    ///
+    ///     fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "-".into()
+    ///     }
+    ///
+    ///     fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "+".into()
+    ///     }
+    ///
+    ///     fn header<'this>(&self) -> Cow<'this, str> {
+    ///         "".into()
+    ///     }
+    ///
+    ///     fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "<".into()
+    ///     }
+    ///
+    ///     fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         ">".into()
+    ///     }
+    /// }
+    ///
+    /// let theme = MovesTheme {};
+    /// let old = "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn e() {}\n";
+    /// let new = "fn c() {}\nfn d() {}\nfn e() {}\nfn a() {}\nfn b() {}\nfn f() {}\n";
+    ///
+    /// assert_eq!(
+    ///     format!("{}", DrawDiff::new(old, new, &theme).with_move_detection(2)),
+    ///     "<fn a() {}
+    /// <fn b() {}
+    ///  fn c() {}
+    ///  fn d() {}
+    ///  fn e() {}
+    /// >fn a() {}
+    /// >fn b() {}
+    /// +fn f() {}
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_move_detection(mut self, min_lines: usize) -> Self {
+        self.move_detection = Some(min_lines);
+        self
+    }
+
+    /// Choose what to do with changes that only add or remove blank lines,
+    /// or only change indentation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{DrawDiff, SignsTheme, WhitespaceChanges};
+    /// let old = "a\nb\n";
+    /// let new = "a\n  b\n\n";
+    /// let theme = SignsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new(old, new, &theme).with_whitespace_changes(WhitespaceChanges::Show)
+    ///     ),
+    ///     "--- remove | insert +++\n a\n-b\n+  b\n+\n"
+    /// );
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new(old, new, &theme).with_whitespace_changes(WhitespaceChanges::Ignore)
+    ///     ),
+    ///     "--- remove | insert +++\n a\n   b\n \n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_whitespace_changes(mut self, whitespace_changes: WhitespaceChanges) -> Self {
+        self.whitespace_changes = whitespace_changes;
+        self
+    }
+
+    /// Treat the lines between a line containing `start` and the next line
+    /// containing `end` as unchanged, drawing one line in their place with
+    /// the theme's [`ignored_region`]
+    ///
+    /// This is for blocks that are generated inside files that are otherwise
+    /// written by hand. The lines with the markers are still drawn, and a
+    /// region is only ignored where both its markers are unchanged.
+    ///
+    /// [`ignored_region`]: crate::Theme::ignored_region
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let old = "a\n// termdiff:ignore-start\nx = 1\n// termdiff:ignore-end\nb\n";
+    /// let new = "A\n// termdiff:ignore-start\nx = 2\ny = 3\n// termdiff:ignore-end\nb\n";
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new(old, new, &theme)
+    ///         .with_ignore_markers("termdiff:ignore-start", "termdiff:ignore-end")
+    ///         .to_string(),
+    ///     "< left / > right
+    /// <a
+    /// >A
+    ///  // termdiff:ignore-start
+    ///  ⋯ 1 → 2 ignored lines
+    ///  // termdiff:ignore-end
+    ///  b
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_ignore_markers(mut self, start: &'input str, end: &'input str) -> Self {
+        self.ignore_markers = Some((start, end));
+        self
+    }
+
+    /// Choose how deleted and inserted lines are matched up when working out
+    /// which parts of them to emphasize
+    #[must_use]
+    pub fn with_line_pairing(mut self, line_pairing: LinePairing) -> Self {
+        self.line_pairing = line_pairing;
+        self
+    }
+
+    /// Choose the order the lines of a replaced block are drawn in, see
+    /// [`ReplaceOrder`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff, ReplaceOrder};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("a\nb\nc\n", "A\nB\nc\n", &theme)
+    ///         .with_replace_order(ReplaceOrder::Interleaved)
+    ///         .to_string(),
+    ///     "< left / > right\n<a\n>A\n<b\n>B\n c\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_replace_order(mut self, replace_order: ReplaceOrder) -> Self {
+        self.replace_order = replace_order;
+        self
+    }
+
+    /// Add a pass that runs over the ops of the diff before it is rendered
+    ///
+    /// Passes run in the order they were added. See [`OpTransform`] for an
+    /// example.
+    #[must_use]
+    pub fn with_transform(mut self, transform: &'input dyn OpTransform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Render ops that have already been worked out, rather than diffing the
+    /// text again
+    ///
+    /// The ops need to have come from diffing this old and new text. See
+    /// [`IncrementalDiff`] for an example.
+    ///
+    /// [`IncrementalDiff`]: crate::IncrementalDiff
+    #[must_use]
+    pub fn with_ops(mut self, ops: &'input [DiffOp]) -> Self {
+        self.ops = Some(ops);
+        self
+    }
+
+    /// What to do when one text ends with a newline and the other doesn't,
+    /// see [`NewlinePolicy`]
+    #[must_use]
+    pub fn with_newline_policy(mut self, newline_policy: NewlinePolicy) -> Self {
+        self.newline_policy = newline_policy;
+        self
+    }
+
+    /// Compare lines without their line endings, and mark lines where only
+    /// the line ending changed with the theme's [`line_ending_change`]
+    ///
+    /// This replaces the [`trailing_lf_marker`], as a missing newline at the
+    /// end of the text is marked as a line ending change too. See
+    /// [`LineEnding`] for an example.
+    ///
+    /// [`line_ending_change`]: Theme::line_ending_change
+    /// [`trailing_lf_marker`]: Theme::trailing_lf_marker
+    /// [`LineEnding`]: crate::LineEnding
+    #[must_use]
+    pub fn with_line_ending_markers(mut self, line_ending_markers: bool) -> Self {
+        self.line_ending_markers = line_ending_markers;
+        self
+    }
+
+    /// Render trailing spaces and tabs on changed lines with the theme's
+    /// [`whitespace_marker`], so they can be seen
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new("a\nb\n", "a \t\nb\n", &theme).with_trailing_whitespace(true)
+    ///     ),
+    ///     "< left / > right\n<a\n>a·→\n b\n"
+    /// );
+    /// ```
+    ///
+    /// [`whitespace_marker`]: Theme::whitespace_marker
+    #[must_use]
+    pub fn with_trailing_whitespace(mut self, trailing_whitespace: bool) -> Self {
+        self.trailing_whitespace = trailing_whitespace;
+        self
+    }
+
+    /// Keep right-to-left text, like Arabic or Hebrew, from scrambling the
+    /// diff
+    ///
+    /// Each changed word is wrapped in Unicode first strong isolate and pop
+    /// directional isolate characters, so the terminal lays it out on its own
+    /// rather than reordering it with the words around it. A left-to-right
+    /// mark follows each prefix, so the prefixes stay on the left whichever
+    /// direction the line is in. Laying out the text is still left to the
+    /// terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("שלום עולם\n", "שלום חבר\n", &theme)
+    ///         .with_bidi_isolates(true)
+    ///         .to_string(),
+    ///     "< left / > right
+    /// <\u{200e}שלום \u{2068}עולם\u{2069}
+    /// >\u{200e}שלום \u{2068}חבר\u{2069}
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_bidi_isolates(mut self, bidi_isolates: bool) -> Self {
+        self.bidi_isolates = bidi_isolates;
+        self
+    }
+
+    /// Only write ASCII characters, for tools reading the diff that can't
+    /// handle anything else
+    ///
+    /// Symbols the themes use get ASCII stand ins, like `\n` for `␊` and
+    /// `->` for `→`, and any other characters, including those in the text
+    /// being diffed, are escaped like `\u{e9}`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("café", "cafe\n", &theme)
+    ///         .with_ascii_only(true)
+    ///         .to_string(),
+    ///     "< left / > right\n<caf\\u{e9}\n>cafe\\n\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Start every line written, including the header, with a prefix, so the
+    /// diff can be put inside other output like a comment or a YAML block
+    ///
+    /// Blank lines aren't given the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("a\n", "b\n", &theme).with_indent("// ").to_string(),
+    ///     "// < left / > right\n// <a\n// >b\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_indent(mut self, prefix: &'input str) -> Self {
+        self.indent = Some(prefix);
+        self
+    }
+
+    /// Compare lines under a Unicode normalization form, see
+    /// [`Normalization`]
+    ///
+    /// Needs the `normalization` feature.
+    #[cfg(feature = "normalization")]
+    #[must_use]
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = Some(normalization);
+        self
+    }
+
+    /// Soft wrap lines wider than this many columns, starting each wrapped
+    /// part with the theme's [`continuation_prefix`]
+    ///
+    /// Wide characters, like most CJK ones, count as two columns, and are
+    /// moved to the next part rather than split over the edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new("abcdefgh\n", "abcdefgh\nijk\n", &theme).with_wrap_width(3)
+    ///     ),
+    ///     "< left / > right\n abc\n↪def\n↪gh\n>ijk\n"
+    /// );
+    /// ```
+    ///
+    /// [`continuation_prefix`]: Theme::continuation_prefix
+    #[must_use]
+    pub fn with_wrap_width(mut self, wrap_width: usize) -> Self {
+        self.wrap_width = Some(wrap_width.max(1));
+        self
+    }
+
+    /// Choose when to write the theme's header, see [`Header`]
+    #[must_use]
+    pub fn with_header(mut self, header: Header) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Only draw the lines that changed, leaving out the unchanged lines
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new("a\nb\nc\n", "a\nB\nc\n", &theme).with_only_changes(true)
+    ///     ),
+    ///     "< left / > right\n<b\n>B\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_only_changes(mut self, only_changes: bool) -> Self {
+        self.only_changes = only_changes;
+        self
+    }
+
+    /// Work out the diff with a different [`Algorithm`]
+    ///
+    /// This has no effect if the ops have been given with [`with_ops`].
+    ///
+    /// [`with_ops`]: DrawDiff::with_ops
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{Algorithm, ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new("a\nb\n", "b\na\n", &theme).with_algorithm(Algorithm::Lcs)
+    ///     ),
+    ///     "< left / > right\n<a\n b\n>a\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: Algorithm<'input>) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Tell a callback how far through the diff the algorithm has got, for
+    /// large diffs, see [`Progress`]
+    #[must_use]
+    pub fn with_progress(mut self, progress: Progress<'input>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Stop working out the diff once the token is cancelled, and draw a full
+    /// replace under the theme's [`cancelled`] line instead
+    ///
+    /// The token is only looked at while the diff is being worked out, so a
+    /// diff that was finished, or given with [`with_ops`], is drawn as it is
+    /// even if the token is cancelled later.
+    ///
+    /// [`cancelled`]: Theme::cancelled
+    /// [`with_ops`]: DrawDiff::with_ops
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{CancellationToken, DrawDiff, SnapshotTheme};
+    /// let theme = SnapshotTheme::default();
+    /// let token = CancellationToken::new();
+    /// token.cancel();
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("a\nb\n", "a\nc\n", &theme)
+    ///         .with_cancellation(&token)
+    ///         .to_string(),
+    ///     "--- old\n+++ new\n[cancelled]\n-a\n-b\n+a\n+c\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_cancellation(mut self, cancellation: &'input CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// How finely to compare changed lines, see [`Granularity`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{DrawDiff, Granularity, Theme};
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Debug)]
+    /// struct BracketsTheme {}
+    /// impl Theme for BracketsTheme {
+    ///     fn highlight_insert<'this>(&self, input: &'this str) -> Cow<'this, str> {
+    ///         format!("[{}]", input).into()
+    ///     }
+    ///
+    ///     fn highlight_delete<'this>(&self, input: &'this str) -> Cow<'this, str> {
+    ///         format!("[{}]", input).into()
+    ///     }
+    ///
+    ///     fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         " ".into()
+    ///     }
+    ///
+    ///     fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "-".into()
+    ///     }
+    ///
+    ///     fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "+".into()
+    ///     }
+    ///
+    ///     fn header<'this>(&self) -> Cow<'this, str> {
+    ///         "".into()
+    ///     }
+    /// }
+    ///
+    /// let theme = BracketsTheme {};
+    /// let diff = |granularity| {
+    ///     DrawDiff::new("red fish\n", "red dish\n", &theme)
+    ///         .with_granularity(granularity)
+    ///         .to_string()
+    /// };
+    ///
+    /// assert_eq!(diff(Granularity::Line), "-red fish\n+red dish\n");
+    /// assert_eq!(diff(Granularity::Word), "-red [fish]\n+red [dish]\n");
+    /// assert_eq!(diff(Granularity::Char), "-red [f]ish\n+red [d]ish\n");
+    /// ```
+    #[must_use]
+    pub fn with_granularity(mut self, granularity: Granularity) -> Self {
+        self.granularity = granularity;
+        self
+    }
+
+    /// Only draw unchanged lines within this many lines of a change,
+    /// separating the groups of lines that are drawn with the theme's
+    /// [`hunk_separator`]
+    ///
+    /// With no context at all, each group is drawn under the theme's
+    /// [`hunk_position`] instead, so it is clear where the changes are.
+    ///
+    /// [`hunk_separator`]: Theme::hunk_separator
+    /// [`hunk_position`]: Theme::hunk_position
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let old = "a\nb\nc\nd\ne\nf\ng\n";
+    /// let new = "A\nb\nc\nd\ne\nf\nG\n";
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new(old, new, &theme).with_context(1).to_string(),
+    ///     "< left / > right\n<a\n>A\n b\n⋯\n f\n<g\n>G\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_context(mut self, context: usize) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Draw the theme's [`hunk_header`] above each group of lines, giving the
+    /// lines it covers, in place of the [`hunk_separator`] and
+    /// [`hunk_position`]
+    ///
+    /// This only matters when some unchanged lines are left out, with
+    /// [`with_context`] or [`with_only_changes`].
+    ///
+    /// [`hunk_header`]: Theme::hunk_header
+    /// [`hunk_separator`]: Theme::hunk_separator
+    /// [`hunk_position`]: Theme::hunk_position
+    /// [`with_context`]: DrawDiff::with_context
+    /// [`with_only_changes`]: DrawDiff::with_only_changes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{DrawDiff, SnapshotTheme};
+    /// let theme = SnapshotTheme::default();
+    /// let old = "a\nb\nc\nd\ne\nf\ng\n";
+    /// let new = "A\nb\nc\nd\ne\nf\ng\nh\n";
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new(old, new, &theme)
+    ///         .with_context(1)
+    ///         .with_hunk_headers(true)
+    ///         .to_string(),
+    ///     "--- old\n+++ new\n@@ -1,2 +1,2 @@\n-[-a-]\n+{+A+}\n b\n@@ -7,1 +7,2 @@\n g\n+h\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_hunk_headers(mut self, hunk_headers: bool) -> Self {
+        self.hunk_headers = hunk_headers;
+        self
+    }
+
+    /// Whether to end the last line with a newline, which is the default
+    ///
+    /// Leaving it off lets the diff be put inside a sentence or a table cell.
+    /// Any escape codes after the newline are kept, so styles are still
+    /// reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("a\nb\n", "a\nc\n", &theme)
+    ///         .with_final_newline(false)
+    ///         .to_string(),
+    ///     "< left / > right\n a\n<b\n>c"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_final_newline(mut self, final_newline: bool) -> Self {
+        self.final_newline = final_newline;
+        self
+    }
+
+    /// Draw the unchanged lines between two groups of lines, rather than a
+    /// [`hunk_separator`], when fewer than this many of them would be left
+    /// out
+    ///
+    /// This only matters when some unchanged lines are left out, with
+    /// [`with_context`] or [`with_only_changes`].
+    ///
+    /// [`hunk_separator`]: Theme::hunk_separator
+    /// [`with_context`]: DrawDiff::with_context
+    /// [`with_only_changes`]: DrawDiff::with_only_changes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let old = "a\nb\nc\nd\ne\n";
+    /// let new = "A\nb\nc\nd\nE\n";
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new(old, new, &theme)
+    ///         .with_context(0)
+    ///         .with_merge_hunks_within(4)
+    ///         .to_string(),
+    ///     "< left / > right\n@@ line 1 @@\n<a\n>A\n b\n c\n d\n<e\n>E\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_merge_hunks_within(mut self, lines: usize) -> Self {
+        self.merge_hunks_within = lines;
+        self
+    }
+
+    /// Whether to keep the colors and styles of the theme, see
+    /// [`ColorChoice`]
+    ///
+    /// By default they are always kept. Without color any escape sequences
+    /// are left out of the output, including any in the text being diffed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsColorTheme, ColorChoice, DrawDiff};
+    /// let theme = ArrowsColorTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new("a\n", "b\n", &theme).with_color(ColorChoice::Never)
+    ///     ),
+    ///     "< left / > right\n<a\n>b\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Which color codes to write, see [`Palette`]
+    ///
+    /// By default colors are written as the theme writes them.
+    #[must_use]
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Show the diff as every old line removed and every new line added,
+    /// under the theme's [`substantially_different`] line, when less than
+    /// this share of the lines is unchanged
+    ///
+    /// When two texts have little in common, matching up the few lines they
+    /// share gives a confusing mix of changes.
+    ///
+    /// [`substantially_different`]: Theme::substantially_different
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DrawDiff::new("a\nb\nc\n", "x\nb\ny\n", &theme).with_similarity_threshold(0.5)
+    ///     ),
+    ///     "< left / > right
+    /// substantially different, shown as a full replace
+    /// <a
+    /// <b
+    /// <c
+    /// >x
+    /// >b
+    /// >y
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_similarity_threshold(mut self, threshold: f64) -> Self {
+        self.similarity_threshold = Some(threshold);
+        self
+    }
+
+    /// Render the diff as a series of chunks, the header then each line, so
+    /// it can be streamed without building one large string
+    ///
+    /// The diff is worked out up front, but each chunk is only drawn when
+    /// the iterator gets to it. Markers like hunk separators come in the
+    /// same chunk as the line after them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let diff = DrawDiff::new("a\nb\n", "a\nc\n", &theme);
+    ///
+    /// assert_eq!(
+    ///     diff.render_chunks().collect::<Result<Vec<_>, _>>().unwrap(),
+    ///     vec!["< left / > right\n", " a\n", "<b\n", ">c\n"]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A chunk is an error if the theme fails to format it, after which
+    /// there are no more chunks.
+    pub fn render_chunks(
+        &self,
+    ) -> impl Iterator<Item = Result<Cow<'_, str>, std::fmt::Error>> + '_ {
+        let mut old: Vec<&str> = self.old.lines().collect();
+        let mut new: Vec<&str> = self.new.lines().collect();
+        let (old_last, new_last) = self.replace_trailing_if_needed(
+            old.last().copied().unwrap_or_default(),
+            new.last().copied().unwrap_or_default(),
+        );
+        replace_last(&mut old, &old_last);
+        replace_last(&mut new, &new_last);
+
+        Chunks {
+            diff: self,
+            steps: self
+                .steps(&old, &new, &mut DiffScratch::default())
+                .into_owned(),
+            colors: self.colors(),
+            line_start: Cell::new(true),
+            held: None,
+            finished: false,
+        }
+    }
+
+    /// Render the diff into a string, or say why it can't be drawn
+    ///
+    /// Unlike formatting the diff, an [`Algorithm::Custom`] that isn't
+    /// registered is an error rather than falling back to
+    /// [`Algorithm::Myers`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{Algorithm, ArrowsTheme, DrawDiff, DrawError};
+    /// let theme = ArrowsTheme::default();
+    /// let diff = DrawDiff::new("a\n", "b\n", &theme);
+    ///
+    /// assert_eq!(diff.try_to_string(), Ok(diff.to_string()));
+    /// assert_eq!(
+    ///     diff.with_algorithm(Algorithm::Custom("missing"))
+    ///         .try_to_string(),
+    ///     Err(DrawError::UnknownAlgorithm("missing".to_string()))
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Errors if the algorithm isn't registered, or if a theme or highlighter
+    /// failed to format something.
+    pub fn try_to_string(&self) -> Result<String, DrawError> {
+        match self.algorithm {
+            Algorithm::Custom(name) if self.ops.is_none() && !self.algorithm.is_available() => {
+                Err(DrawError::UnknownAlgorithm(name.to_string()))
+            }
+            _ => {
+                let mut buffer = String::new();
+                self.render_onto(&mut buffer, &mut DiffScratch::default())?;
+                Ok(buffer)
+            }
+        }
+    }
+
+    /// Render the diff into a string, sized up front from an estimate of how
+    /// long the output will be
+    ///
+    /// The estimate is made once the diff is worked out, from how many lines
+    /// of each kind will be drawn and how long they are. This avoids the
+    /// string being reallocated over and over as it grows, which matters for
+    /// large diffs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsColorTheme, DrawDiff};
+    /// let theme = ArrowsColorTheme::default();
+    /// let diff = DrawDiff::new("a\nb\n", "a\nc\n", &theme);
+    ///
+    /// assert_eq!(diff.to_string_with_capacity_estimate(), diff.to_string());
+    /// ```
+    #[must_use]
+    pub fn to_string_with_capacity_estimate(&self) -> String {
+        let mut buffer = String::new();
+        self.render_into(&mut buffer);
+        buffer
+    }
+
+    /// Render the diff onto the end of a string
+    ///
+    /// Reusing the same string for many diffs, clearing it between them, saves
+    /// allocating a new one each time. Room for the diff is reserved up front.
+    /// To reuse the buffers used while working out the diff too, see
+    /// [`DrawDiff::render_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let mut buffer = String::new();
+    ///
+    /// for (old, new) in [("a\n", "b\n"), ("c\n", "d\n")] {
+    ///     buffer.clear();
+    ///     DrawDiff::new(old, new, &theme).render_into(&mut buffer);
+    ///
+    ///     assert_eq!(buffer, DrawDiff::new(old, new, &theme).to_string());
+    /// }
+    /// ```
+    pub fn render_into(&self, buffer: &mut String) {
+        let _ = self.render_onto(buffer, &mut DiffScratch::default());
+    }
+
+    /// Render the diff with buffers kept from earlier renders, returning the
+    /// drawn diff
+    ///
+    /// The lines, operations and changes worked out along the way are kept in
+    /// the scratch buffers as well as the output, so drawing many diffs with
+    /// the same [`DiffScratch`] only allocates when one is bigger than any
+    /// before it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffScratch, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let mut scratch = DiffScratch::new();
+    ///
+    /// for (old, new) in [("a\n", "b\n"), ("c\n", "d\n")] {
+    ///     let drawn = DrawDiff::new(old, new, &theme).render_with(&mut scratch);
+    ///
+    ///     assert_eq!(drawn, DrawDiff::new(old, new, &theme).to_string());
+    /// }
+    /// ```
+    pub fn render_with<'scratch>(&self, scratch: &'scratch mut DiffScratch) -> &'scratch str {
+        let mut output = take(&mut scratch.output);
+        output.clear();
+        let _ = self.render_onto(&mut output, scratch);
+        scratch.output = output;
+        &scratch.output
+    }
+
+    fn render_onto(&self, buffer: &mut String, scratch: &mut DiffScratch) -> std::fmt::Result {
+        let wanted = Cell::new(0);
+        self.render_sized(
+            &mut |chunk| {
+                buffer.reserve(wanted.take());
+                write!(buffer, "{chunk}")
+            },
+            &mut |size| wanted.set(size),
+            scratch,
+        )
+    }
+
+    /// Roughly how many bytes the drawn lines will take, from the kind of
+    /// each line, how long it is, how many parts it is drawn in and how many
+    /// of those are emphasized, with what the theme adds to prefix and style
+    /// them
+    fn capacity_estimate(
+        &self,
+        header: usize,
+        lines: impl Iterator<Item = (ChangeTag, usize, usize, usize)>,
+    ) -> usize {
+        let tags = [
+            ChangeTag::Equal,
+            ChangeTag::Delete,
+            ChangeTag::Insert,
+            ChangeTag::MovedFrom,
+            ChangeTag::MovedTo,
+        ];
+        // Themes may not style empty text, so measure a space and take it
+        // off again
+        let overheads = tags.map(|tag| {
+            let prefix = self.prefix(tag).len();
+            let part = self.format_line(" ", tag).len() - 1;
+            let emphasis = self.highlight(" ", tag).len() - 1;
+            (tag, prefix, part, emphasis)
+        });
+
+        lines.fold(header, |size, (tag, length, parts, emphasized)| {
+            let (prefix, part, emphasis) = overheads
+                .iter()
+                .find(|(overhead_tag, _, _, _)| *overhead_tag == tag)
+                .map_or((0, 0, 0), |(_, prefix, part, emphasis)| {
+                    (*prefix, *part, *emphasis)
+                });
+
+            size + prefix + length + parts * part + emphasized * emphasis
+        })
+    }
+
+    /// The diff has no changes in it, so there is nothing worth printing
+    ///
+    /// Identical text is spotted without running the diff. Otherwise the diff
+    /// is worked out, so options like ignoring whitespace changes are taken
+    /// into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff, WhitespaceChanges};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert!(DrawDiff::new("a\nb\n", "a\nb\n", &theme).is_empty());
+    /// assert!(!DrawDiff::new("a\nb\n", "a\n  b\n", &theme).is_empty());
+    /// assert!(DrawDiff::new("a\nb\n", "a\n  b\n", &theme)
+    ///     .with_whitespace_changes(WhitespaceChanges::Ignore)
+    ///     .is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        if self.identical() {
+            return true;
+        }
+
+        let old: Vec<&str> = self.old.lines().collect();
+        let new: Vec<&str> = self.new.lines().collect();
+
+        self.changes(&old, &new, &mut DiffScratch::default())
+            .is_ok_and(|changes| unchanged(&changes))
+    }
+
+    /// The inputs are the same and nothing could add changes to them, so
+    /// there is no need to run the diff
+    fn identical(&self) -> bool {
+        self.transforms.is_empty() && self.ops.is_none() && self.old.lines().eq(self.new.lines())
+    }
+
+    fn ops(
+        &self,
+        old: &[&str],
+        new: &[&str],
+        mut merged: Vec<DiffOp>,
+    ) -> Result<Vec<DiffOp>, Cancelled> {
+        let mut ops = match self.ops {
+            Some(ops) => ops.to_vec(),
+            None if self.line_ending_markers
+                || self.normalizes()
+                || self.ignore_markers.is_some() =>
+            {
+                let old = self.comparable(old);
+                let new = self.comparable(new);
+                let old: Vec<&str> = old.iter().map(AsRef::as_ref).collect();
+                let new: Vec<&str> = new.iter().map(AsRef::as_ref).collect();
+                self.diff_lines(&old, &new)?
+            }
+            None => self.diff_lines(old, new)?,
+        };
+
+        for transform in &self.transforms {
+            ops = transform.transform(old, new, ops);
+        }
+
+        if let Some(min_lines) = self.move_detection {
+            ops = MoveDetection::new(min_lines).transform(old, new, ops);
+        }
+
+        merged.clear();
+        merge_into(ops, &mut merged);
+        Ok(merged)
+    }
+
+    fn diff_lines(&self, old: &[&str], new: &[&str]) -> Result<Vec<DiffOp>, Cancelled> {
+        self.algorithm
+            .diff_observed(old, new, self.progress, self.cancellation)
+    }
+
+    /// The lines as they should be compared, without their endings if line
+    /// ending changes are marked, normalized if normalizing, and blank
+    /// between ignore markers
+    fn comparable<'lines>(&self, lines: &[&'lines str]) -> Vec<Cow<'lines, str>> {
+        let mut comparable: Vec<Cow<'lines, str>> = lines
+            .iter()
+            .map(|line| {
+                let line = if self.line_ending_markers {
+                    split_ending(line).0
+                } else {
+                    line
+                };
+
+                #[cfg(feature = "normalization")]
+                if let Some(normalization) = self.normalization {
+                    return normalization.normalize(line);
+                }
+
+                Cow::Borrowed(line)
+            })
+            .collect();
+
+        if let Some((start, end)) = self.ignore_markers {
+            mask_regions(&mut comparable, start, end);
+        }
+
+        comparable
+    }
+
+    #[cfg(feature = "normalization")]
+    fn normalizes(&self) -> bool {
+        self.normalization.is_some()
+    }
+
+    #[cfg(not(feature = "normalization"))]
+    fn normalizes(&self) -> bool {
+        false
+    }
+
+    fn changes<'lines>(
+        &self,
+        old: &[&'lines str],
+        new: &[&'lines str],
+        scratch: &mut DiffScratch,
+    ) -> Result<Vec<Change<'lines>>, Cancelled> {
+        let mut merged = self.ops(old, new, take(&mut scratch.ops))?;
+        let mut changes = recycle(take(&mut scratch.changes));
+        let mut ops = merged.drain(..).peekable();
+
+        while let Some(op) = ops.next() {
+            let old_range = clamp(op.old_range(), old.len());
+            let new_range = clamp(op.new_range(), new.len());
+            let start = changes.len();
+            let (old_start, new_start) = (old_range.start, new_range.start);
+
+            match op.tag() {
+                ChangeTag::Delete if matches!(ops.peek(), Some(next) if next.tag() == ChangeTag::Insert) =>
+                {
+                    let new_range = ops
+                        .next()
+                        .map_or(new_range, |insert| clamp(insert.new_range(), new.len()));
+                    let (old, new) = (&old[old_range], &new[new_range]);
+                    changes.extend(order_replaced(
+                        self.replace_changes(old, new),
+                        old,
+                        new,
+                        self.replace_order,
+                    ));
+                }
+                ChangeTag::Equal if self.line_ending_markers || self.normalizes() => {
+                    changes.extend(
+                        old[old_range]
+                            .iter()
+                            .zip(&new[new_range])
+                            .map(|(old_line, new_line)| self.equal_change(old_line, new_line)),
+                    );
+                }
+                ChangeTag::Equal | ChangeTag::Delete | ChangeTag::MovedFrom => {
+                    changes.extend(
+                        old[old_range]
+                            .iter()
+                            .map(|line| Change::new(op.tag(), vec![(false, (*line).into())])),
+                    );
+                }
+                ChangeTag::Insert | ChangeTag::MovedTo => {
+                    changes.extend(
+                        new[new_range]
+                            .iter()
+                            .map(|line| Change::new(op.tag(), vec![(false, (*line).into())])),
+                    );
+                }
+            }
+
+            number_lines(&mut changes[start..], old_start, new_start);
+        }
+        drop(ops);
+        scratch.ops = merged;
+
+        mark_missing_newlines(&mut changes, old, new);
+
+        if let Some((start, end)) = self.ignore_markers {
+            changes = collapse_regions(changes, start, end, self.theme);
+        }
+
+        Ok(match self.whitespace_changes {
+            WhitespaceChanges::Show => changes,
+            WhitespaceChanges::Highlight => {
+                classify_whitespace(&mut changes);
+                changes
+            }
+            WhitespaceChanges::Ignore => {
+                classify_whitespace(&mut changes);
+                ignore_whitespace(changes)
+            }
+        })
+    }
+
+    /// The change for a pair of lines the diff found equal, which may still
+    /// differ in their endings, or in how their text is encoded
+    fn equal_change<'lines>(&self, old_line: &'lines str, new_line: &str) -> Change<'lines> {
+        let (content, old_ending) = split_ending(old_line);
+        let (new_content, new_ending) = split_ending(new_line);
+        let ending_changed = self.line_ending_markers && old_ending != new_ending;
+        let normalization_changed = content != new_content;
+
+        if !ending_changed && !normalization_changed {
+            return Change::new(ChangeTag::Equal, vec![(false, old_line.into())]);
+        }
+
+        let change = Change::new(ChangeTag::Equal, vec![(false, content.into())]);
+        let change = if normalization_changed {
+            change.with_normalization_changed()
+        } else {
+            change
+        };
+
+        if ending_changed {
+            change.with_line_ending_change(old_ending, new_ending)
+        } else {
+            change
+        }
+    }
+
+    /// The changes for a block of deleted lines followed by inserted lines,
+    /// with the parts that differ emphasized
+    fn replace_changes<'lines>(
+        &self,
+        old_lines: &[&'lines str],
+        new_lines: &[&'lines str],
+    ) -> Vec<Change<'lines>> {
+        match (self.line_pairing, self.granularity) {
+            (_, Granularity::Line) => unemphasized(old_lines, new_lines),
+            (LinePairing::Similarity, granularity) => {
+                paired_inline_changes(old_lines, new_lines, self.minimal_inline, granularity)
+            }
+            (LinePairing::Block, granularity)
+                if self.minimal_inline || granularity == Granularity::Char =>
+            {
+                inline_changes(old_lines, new_lines, self.minimal_inline, granularity)
+            }
+            (LinePairing::Block, _) => {
+                let diff = TextDiff::configure().diff_slices(old_lines, new_lines);
+
+                diff.iter_inline_changes(&similar::DiffOp::Replace {
+                    old_index: 0,
+                    old_len: old_lines.len(),
+                    new_index: 0,
+                    new_len: new_lines.len(),
+                })
+                .map(|change| Change::from(change).into_owned())
+                .collect()
+            }
+        }
+    }
+
+    fn write_line(
+        &self,
+        f: &mut Formatter<'_>,
+        line: &LineInfo,
+        values: &[(bool, Cow<'_, str>)],
+        whitespace_only: bool,
+        line_ending_change: Option<(LineEnding, LineEnding)>,
+        normalization_changed: bool,
+    ) -> std::fmt::Result {
+        let values = match self.redactor {
+            Some(redactor) => redact_line(redactor, values),
+            None => Cow::Borrowed(values),
+        };
+        let values = values.as_ref();
+        let tag = line.tag();
+        write!(f, "{}", self.theme.line_start(line))?;
+        write!(f, "{}", self.prefix(tag))?;
+        self.write_direction_mark(f)?;
+
+        let spans = self.highlighter.map(|highlighter| {
+            let line: String = values.iter().map(|(_, value)| value.as_ref()).collect();
+            highlighter.highlight_line(&line)
+        });
+        let trailing = self.trailing_whitespace(tag, values);
+        let mut offset = 0;
+        let mut column = 0;
+
+        for (highlight, value) in values {
+            let segment = Segment {
+                tag,
+                highlight: *highlight,
+                whitespace_only,
+                spans: spans.as_deref(),
+                trailing: trailing.as_ref(),
+            };
+            let mut rest: &str = value;
+
+            loop {
+                let end = match self.wrap_width {
+                    Some(width) => {
+                        let visible = rest.trim_end_matches(['\r', '\n']);
+                        let mut end = fit_width(visible, width.saturating_sub(column));
+                        if end == Some(0) && column > 0 {
+                            write!(f, "{}", self.theme.line_end())?;
+                            write!(
+                                f,
+                                "{}",
+                                prefix_column(
+                                    self.theme,
+                                    self.theme.continuation_prefix(),
+                                    self.prefix_width
+                                )
+                            )?;
+                            self.write_direction_mark(f)?;
+                            column = 0;
+                            end = fit_width(visible, width);
+                        }
+
+                        // A character wider than the whole line still has to
+                        // go somewhere
+                        let end = match end {
+                            Some(0) => visible.chars().next().map_or(0, char::len_utf8),
+                            Some(end) => end,
+                            None => rest.len(),
+                        };
+                        column += display_width(rest[..end].trim_end_matches(['\r', '\n']));
+                        end
+                    }
+                    None => rest.len(),
+                };
+
+                let (piece, remaining) = rest.split_at(end);
+                self.write_piece(f, segment, piece, offset)?;
+                offset += piece.len();
+
+                if remaining.is_empty() {
+                    break;
+                }
+                rest = remaining;
+            }
+        }
+
+        if normalization_changed {
+            write!(f, "{}", self.theme.normalization_change())?;
+        }
+        if let Some((old, new)) = line_ending_change {
+            write!(f, "{}", self.theme.line_ending_change(old, new))?;
+            write!(f, "{}", self.theme.line_end())?;
+        } else if normalization_changed
+            || !matches!(values.last(), Some((_, value)) if value.ends_with(['\r', '\n']))
+        {
+            write!(f, "{}", self.theme.line_end())?;
+        }
+
+        write!(f, "{}", self.theme.line_finish(line))
+    }
+
+    /// Write part of a line, which starts `offset` bytes into the line,
+    /// marking any trailing whitespace in it
+    fn write_piece(
+        &self,
+        f: &mut Formatter<'_>,
+        segment: Segment<'_>,
+        value: &str,
+        offset: usize,
+    ) -> std::fmt::Result {
+        let end = offset + value.len();
+
+        match segment.trailing {
+            Some(trailing) if trailing.start < end && trailing.end > offset => {
+                let marked = trailing.start.max(offset) - offset..trailing.end.min(end) - offset;
+
+                if marked.start > 0 {
+                    self.write_segment(f, segment, &value[..marked.start], offset)?;
+                }
+                write!(
+                    f,
+                    "{}",
+                    self.theme.whitespace_marker(&value[marked.clone()])
+                )?;
+                if marked.end < value.len() {
+                    self.write_segment(f, segment, &value[marked.end..], offset + marked.end)?;
+                }
+
+                Ok(())
+            }
+            _ => self.write_segment(f, segment, value, offset),
+        }
+    }
+
+    /// Write part of a line, which starts `offset` bytes into the line
+    fn write_segment(
+        &self,
+        f: &mut Formatter<'_>,
+        segment: Segment<'_>,
+        value: &str,
+        offset: usize,
+    ) -> std::fmt::Result {
+        let inline_change: Cow<'_, str> = match segment.spans {
+            Some(spans) => apply_spans(value, offset, spans, &self.segment_style(segment)).into(),
+            None => Cow::Borrowed(value),
+        };
+
+        let formatted = if segment.highlight {
+            let highlighted = self.highlight(inline_change.borrow(), segment.tag);
+            self.format_line(highlighted.borrow(), segment.tag)
+                .into_owned()
+                .into()
+        } else {
+            self.format_line(inline_change.borrow(), segment.tag)
+        };
+
+        let isolate = self.bidi_isolates && segment.highlight;
+        if isolate {
+            f.write_char(FIRST_STRONG_ISOLATE)?;
+        }
+        if segment.whitespace_only {
+            write!(f, "{}", self.theme.whitespace_only_content(&formatted))?;
+        } else {
+            write!(f, "{formatted}")?;
+        }
+        if isolate {
+            f.write_char(POP_DIRECTIONAL_ISOLATE)?;
+        }
+
+        Ok(())
+    }
+
+    /// The escape codes the theme starts a segment with, to put back after a
+    /// highlighter's span resets them
+    fn segment_style(&self, segment: Segment<'_>) -> String {
+        const MARKER: &str = "\u{0}";
+        let formatted = if segment.highlight {
+            let highlighted = self.highlight(MARKER, segment.tag);
+            self.format_line(highlighted.borrow(), segment.tag)
+                .into_owned()
+        } else {
+            self.format_line(MARKER, segment.tag).into_owned()
+        };
+
+        match formatted.split_once(MARKER) {
+            Some((style, _)) => style.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Keep what comes after a prefix from being laid out right to left, when
+    /// isolating bidirectional text
+    fn write_direction_mark(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.bidi_isolates {
+            f.write_char(LEFT_TO_RIGHT_MARK)?;
+        }
+
+        Ok(())
+    }
+
+    /// Where the trailing spaces and tabs are in a changed line, if they are
+    /// being highlighted
+    fn trailing_whitespace(
+        &self,
+        tag: ChangeTag,
+        values: &[(bool, Cow<'_, str>)],
+    ) -> Option<Range<usize>> {
+        if !self.trailing_whitespace || tag == ChangeTag::Equal {
+            return None;
+        }
+
+        let line: String = values.iter().map(|(_, value)| value.as_ref()).collect();
+        let content = split_ending(&line).0;
+        let start = content.trim_end_matches([' ', '\t']).len();
+
+        (start < content.len()).then_some(start..content.len())
     }
 
     fn highlight(&self, text: &'input str, tag: ChangeTag) -> Cow<'input, str> {
         match tag {
-            ChangeTag::Equal => text.into(),
+            ChangeTag::Equal | ChangeTag::MovedFrom | ChangeTag::MovedTo => text.into(),
             ChangeTag::Delete => self.theme.highlight_delete(text),
             ChangeTag::Insert => self.theme.highlight_insert(text),
         }
@@ -60,74 +1751,754 @@ impl<'input> DrawDiff<'input> {
             ChangeTag::Equal => self.theme.equal_content(line),
             ChangeTag::Delete => self.theme.delete_content(line),
             ChangeTag::Insert => self.theme.insert_line(line),
+            ChangeTag::MovedFrom => self.theme.moved_from_content(line),
+            ChangeTag::MovedTo => self.theme.moved_to_content(line),
         }
     }
 
     fn prefix(&self, tag: ChangeTag) -> Cow<'input, str> {
-        match tag {
+        let prefix = match tag {
             ChangeTag::Equal => self.theme.equal_prefix(),
             ChangeTag::Delete => self.theme.delete_prefix(),
             ChangeTag::Insert => self.theme.insert_prefix(),
-        }
+            ChangeTag::MovedFrom => self.theme.moved_from_prefix(),
+            ChangeTag::MovedTo => self.theme.moved_to_prefix(),
+        };
+
+        prefix_column(self.theme, prefix, self.prefix_width)
+    }
+
+    /// The changes to draw, as a full replace if the sides have too little
+    /// in common or the diff was cancelled, with the line that says why
+    fn thresholded<'lines>(
+        &self,
+        old: &[&'lines str],
+        new: &[&'lines str],
+        changes: Result<Vec<Change<'lines>>, Cancelled>,
+    ) -> (Vec<Change<'lines>>, Option<Cow<'static, str>>) {
+        let notice = match changes {
+            Err(Cancelled) => self.theme.cancelled(),
+            Ok(changes) => {
+                let equal = changes
+                    .iter()
+                    .filter(|change| change.tag() == ChangeTag::Equal)
+                    .count();
+
+                match self.similarity_threshold {
+                    Some(threshold) if similarity(equal * 2, old.len() + new.len()) < threshold => {
+                        self.theme.substantially_different()
+                    }
+                    _ => return (changes, None),
+                }
+            }
+        };
+
+        let mut replaced = unemphasized(old, new);
+        number_lines(&mut replaced, 0, 0);
+        mark_missing_newlines(&mut replaced, old, new);
+        (replaced, Some(notice))
     }
 
+    /// The last lines of the old and new text, changed to follow the
+    /// [`NewlinePolicy`] if only one of them ends with a newline
     fn replace_trailing_if_needed(
         &self,
         old: &'input str,
         new: &'input str,
     ) -> (Cow<'input, str>, Cow<'input, str>) {
-        if old.chars().last() == new.chars().last() {
-            (old.into(), new.into())
-        } else {
-            return (self.replace_trailing_nl(old), self.replace_trailing_nl(new));
+        let (old_content, old_ending) = split_ending(old);
+        let (new_content, new_ending) = split_ending(new);
+        let one_sided = (old_ending == LineEnding::None) != (new_ending == LineEnding::None);
+
+        if old.is_empty() || new.is_empty() || self.line_ending_markers || !one_sided {
+            return (old.into(), new.into());
+        }
+
+        match self.newline_policy {
+            NewlinePolicy::Mark => (self.replace_trailing_nl(old), self.replace_trailing_nl(new)),
+            NewlinePolicy::Ignore if old_ending == LineEnding::None => (
+                format!("{old}{}", &new[new_content.len()..]).into(),
+                new.into(),
+            ),
+            NewlinePolicy::Ignore => (
+                old.into(),
+                format!("{new}{}", &old[old_content.len()..]).into(),
+            ),
+            NewlinePolicy::Explicit => (old.into(), new.into()),
         }
     }
 
     fn replace_trailing_nl(&self, x: &'input str) -> Cow<'input, str> {
-        if x.ends_with('\n') {
-            let mut buffer = x.to_string();
-            let popped = buffer.pop().unwrap();
-            buffer.push_str(&self.theme.trailing_lf_marker());
-            buffer.push(popped);
-            buffer.into()
+        match split_ending(x) {
+            (_, LineEnding::None) => x.into(),
+            (content, _) => format!(
+                "{content}{}{}",
+                self.theme.trailing_lf_marker(),
+                &x[content.len()..]
+            )
+            .into(),
+        }
+    }
+}
+
+/// None of the lines have changed
+fn unchanged(changes: &[Change<'_>]) -> bool {
+    changes
+        .iter()
+        .all(|change| change.tag() == ChangeTag::Equal && !altered(change))
+}
+
+/// The line is unchanged, but is drawn with a marker showing how it differs
+fn altered(change: &Change<'_>) -> bool {
+    change.line_ending_change().is_some() || change.normalization_changed()
+}
+
+/// Mark the lines that are the last line of a text without a newline, going
+/// by their line numbers
+fn mark_missing_newlines(changes: &mut [Change<'_>], old: &[&str], new: &[&str]) {
+    let missing = |lines: &[&str], number: Option<usize>| {
+        number
+            .and_then(|number| lines.get(number.checked_sub(1)?))
+            .is_some_and(|line| split_ending(line).1 == LineEnding::None)
+    };
+
+    for change in changes {
+        let (old_number, new_number) = change.line_numbers();
+        if missing(old, old_number) || missing(new, new_number) {
+            change.mark_missing_newline();
+        }
+    }
+}
+
+/// The line numbers in the old and new text covered by the group of visible
+/// changes starting at `start`, see [`Theme::hunk_header`]
+fn group_ranges(
+    changes: &[Change<'_>],
+    visible: &[bool],
+    start: usize,
+) -> (Range<usize>, Range<usize>) {
+    let length = visible[start..]
+        .iter()
+        .take_while(|visible| **visible)
+        .count();
+    let count = |side: fn(&Change<'_>) -> Option<usize>, range: Range<usize>| {
+        changes[range].iter().filter_map(side).count()
+    };
+    let old = |change: &Change<'_>| change.line_numbers().0;
+    let new = |change: &Change<'_>| change.line_numbers().1;
+
+    let old_start = count(old, 0..start) + 1;
+    let new_start = count(new, 0..start) + 1;
+    let old_length = count(old, start..start + length);
+    let new_length = count(new, start..start + length);
+
+    (
+        old_start..old_start + old_length,
+        new_start..new_start + new_length,
+    )
+}
+
+/// Swap the last line for another
+fn replace_last<'lines>(lines: &mut [&'lines str], last: &'lines str) {
+    if let Some(line) = lines.last_mut() {
+        *line = last;
+    }
+}
+
+/// Number the lines of the changes for one op, which start at these lines of
+/// the old and new text
+fn number_lines(changes: &mut [Change<'_>], mut old: usize, mut new: usize) {
+    for change in changes {
+        match change.tag() {
+            ChangeTag::Equal => {
+                old += 1;
+                new += 1;
+                change.set_line_numbers(Some(old), Some(new));
+            }
+            ChangeTag::Delete | ChangeTag::MovedFrom => {
+                old += 1;
+                change.set_line_numbers(Some(old), None);
+            }
+            ChangeTag::Insert | ChangeTag::MovedTo => {
+                new += 1;
+                change.set_line_numbers(None, Some(new));
+            }
+        }
+    }
+}
+
+/// How to write the parts of a line
+#[derive(Debug, Clone, Copy)]
+struct Segment<'a> {
+    tag: ChangeTag,
+    highlight: bool,
+    whitespace_only: bool,
+    spans: Option<&'a [(Range<usize>, ContentStyle)]>,
+    trailing: Option<&'a Range<usize>>,
+}
+
+/// Limit a range to the lines that exist, in case a pass produced an op that
+/// is out of bounds
+fn clamp(range: Range<usize>, length: usize) -> Range<usize> {
+    let end = range.end.min(length);
+    range.start.min(end)..end
+}
+
+impl DrawDiff<'_> {
+    /// Render the diff a chunk at a time, the header then each line
+    fn render(&self, emit: &mut dyn FnMut(&dyn Display) -> std::fmt::Result) -> std::fmt::Result {
+        self.render_sized(emit, &mut |_| {}, &mut DiffScratch::default())
+    }
+
+    /// Render the diff a chunk at a time, telling `size_hint` roughly how
+    /// many bytes it will take before the first chunk
+    fn render_sized(
+        &self,
+        emit: &mut dyn FnMut(&dyn Display) -> std::fmt::Result,
+        size_hint: &mut dyn FnMut(usize),
+        scratch: &mut DiffScratch,
+    ) -> std::fmt::Result {
+        if self.final_newline {
+            return self.render_framed(emit, size_hint, scratch);
+        }
+
+        // Hold each chunk back until the next one comes, so the newline can
+        // be taken off the last
+        let mut held: Option<String> = None;
+        self.render_framed(
+            &mut |chunk| match held.replace(chunk.to_string()) {
+                Some(previous) => emit(&previous),
+                None => Ok(()),
+            },
+            size_hint,
+            scratch,
+        )?;
+
+        match held {
+            Some(last) => emit(&without_final_newline(&last)),
+            None => Ok(()),
+        }
+    }
+
+    fn render_framed(
+        &self,
+        emit: &mut dyn FnMut(&dyn Display) -> std::fmt::Result,
+        size_hint: &mut dyn FnMut(usize),
+        scratch: &mut DiffScratch,
+    ) -> std::fmt::Result {
+        self.framed(self.colors(), &Cell::new(true), emit, |frame| {
+            self.render_styled(frame, size_hint, scratch)
+        })
+    }
+
+    /// The palette to draw with, or `None` if colors are off
+    ///
+    /// Working this out can look at the environment and the terminal, so it
+    /// is done once for each render.
+    fn colors(&self) -> Option<Palette> {
+        self.color.enabled().then_some(self.palette)
+    }
+
+    /// Run a render with its chunks colored, made ASCII and indented as
+    /// asked for, before they are emitted
+    fn framed<R>(
+        &self,
+        colors: Option<Palette>,
+        line_start: &Cell<bool>,
+        emit: &mut dyn FnMut(&dyn Display) -> std::fmt::Result,
+        render: impl FnOnce(&mut Frame<'_, '_>) -> Result<R, std::fmt::Error>,
+    ) -> Result<R, std::fmt::Error> {
+        render(&mut Frame {
+            diff: self,
+            colors,
+            line_start,
+            emit,
+        })
+    }
+
+    /// Emit a chunk colored with the palette, or uncolored without one, then
+    /// made ASCII and indented as asked for
+    fn styled(
+        &self,
+        chunk: &dyn Display,
+        colors: Option<Palette>,
+        line_start: &Cell<bool>,
+        emit: &mut dyn FnMut(&dyn Display) -> std::fmt::Result,
+    ) -> std::fmt::Result {
+        let mut indented = |chunk: &dyn Display| match self.indent {
+            Some(prefix) => emit(&Indented {
+                chunk,
+                prefix,
+                line_start,
+            }),
+            None => emit(chunk),
+        };
+        let mut ascii = |chunk: &dyn Display| {
+            if self.ascii_only {
+                indented(&Ascii(chunk))
+            } else {
+                indented(chunk)
+            }
+        };
+
+        match colors {
+            None => ascii(&Uncolored(chunk)),
+            Some(Palette::Ansi16) => ascii(&Ansi16(chunk)),
+            Some(_) => ascii(chunk),
+        }
+    }
+
+    /// Render identical inputs, where every line is unchanged
+    fn render_identical(&self, frame: &mut Frame<'_, '_>) -> std::fmt::Result {
+        if self.header == Header::Always {
+            frame.chunk(&self.theme.header_with_context(&self.context(&[])))?;
+        }
+
+        let lines = self
+            .old
+            .lines()
+            .take_while(|_| !self.only_changes && self.context.is_none());
+        for (index, line) in lines.enumerate() {
+            let missing_newline = split_ending(line).1 == LineEnding::None;
+            let info = LineInfo::new(
+                ChangeTag::Equal,
+                Some(index + 1),
+                Some(index + 1),
+                index,
+                index == 0,
+            )
+            .with_missing_newline(missing_newline);
+            let written = frame.line(&DrawnLine {
+                diff: self,
+                line: &info,
+                values: &[(false, line.into())],
+                whitespace_only: false,
+                line_ending_change: None,
+                normalization_changed: false,
+            })?;
+            if written && self.newline_policy == NewlinePolicy::Explicit && missing_newline {
+                frame.chunk(&self.theme.no_newline())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn render_styled(
+        &self,
+        frame: &mut Frame<'_, '_>,
+        size_hint: &mut dyn FnMut(usize),
+        scratch: &mut DiffScratch,
+    ) -> std::fmt::Result {
+        let header = self.theme.header().len();
+
+        // Identical inputs can't have changes for passes to act on, so skip
+        // the diff and render the lines straight from the input
+        if self.identical() {
+            let lines = self
+                .old
+                .lines()
+                .map(|line| (ChangeTag::Equal, line.len(), 1, 0));
+            size_hint(self.capacity_estimate(header, lines));
+            return self.render_identical(frame);
+        }
+
+        let mut old: Vec<&str> = take(&mut scratch.old);
+        let mut new: Vec<&str> = take(&mut scratch.new);
+        old.extend(self.old.lines());
+        new.extend(self.new.lines());
+        let (old_last, new_last) = self.replace_trailing_if_needed(
+            old.last().copied().unwrap_or_default(),
+            new.last().copied().unwrap_or_default(),
+        );
+        replace_last(&mut old, &old_last);
+        replace_last(&mut new, &new_last);
+        let mut steps = self.steps(&old, &new, scratch);
+        let drawn = steps
+            .changes
+            .iter()
+            .zip(&steps.visible)
+            .filter(|(_, visible)| **visible)
+            .map(|(change, _)| {
+                let values = change.values();
+                let length = values.iter().map(|(_, value)| value.len()).sum();
+                let emphasized = values.iter().filter(|(emphasized, _)| *emphasized).count();
+                (change.tag(), length, values.len(), emphasized)
+            });
+        let notice = steps.notice.as_ref().map_or(0, |notice| notice.len());
+        size_hint(self.capacity_estimate(header + notice, drawn));
+        while self.step(&mut steps, frame)? {}
+
+        scratch.changes = recycle(steps.changes);
+        scratch.visible = steps.visible;
+        scratch.old = recycle(old);
+        scratch.new = recycle(new);
+        Ok(())
+    }
+
+    /// Work out the changes to draw, ready to be drawn a step at a time
+    fn steps<'lines>(
+        &self,
+        old: &[&'lines str],
+        new: &[&'lines str],
+        scratch: &mut DiffScratch,
+    ) -> Steps<'lines> {
+        let changes = self.changes(old, new, scratch);
+        let (changes, notice) = self.thresholded(old, new, changes);
+        let header = match self.header {
+            Header::Always => true,
+            Header::Never => false,
+            Header::IfChanges => !unchanged(&changes),
+        };
+        let visible = self.visible(&changes, take(&mut scratch.visible));
+
+        Steps {
+            leaves_lines_out: visible.contains(&false),
+            changes,
+            visible,
+            header,
+            notice,
+            started: false,
+            position: 0,
+            drawn: 0,
+            previous: None,
+            last_drawn: None,
+        }
+    }
+
+    /// Emit the header, or the next line with any marker before it, saying
+    /// whether there was anything left to emit
+    fn step(
+        &self,
+        steps: &mut Steps<'_>,
+        frame: &mut Frame<'_, '_>,
+    ) -> Result<bool, std::fmt::Error> {
+        if !steps.started {
+            steps.started = true;
+            if steps.header {
+                frame.chunk(
+                    &self
+                        .theme
+                        .header_with_context(&self.context(&steps.changes)),
+                )?;
+            }
+            if let Some(notice) = &steps.notice {
+                frame.chunk(notice)?;
+            }
+            return Ok(true);
+        }
+
+        let Some(position) =
+            (steps.position..steps.changes.len()).find(|position| steps.visible[*position])
+        else {
+            steps.position = steps.changes.len();
+            return Ok(false);
+        };
+        steps.position = position + 1;
+
+        let change = &steps.changes[position];
+        let (old_line_number, new_line_number) = change.line_numbers();
+        let starts_group = !matches!(steps.last_drawn, Some(last) if last + 1 == position);
+        if self.hunk_headers && steps.leaves_lines_out && starts_group {
+            let (old_range, new_range) = group_ranges(&steps.changes, &steps.visible, position);
+            frame.chunk(&self.theme.hunk_header(old_range, new_range))?;
+        } else if self.context == Some(0) && starts_group {
+            let line = old_line_number.or(new_line_number).unwrap_or_default();
+            frame.chunk(&self.theme.hunk_position(line))?;
+        } else if starts_group && steps.last_drawn.is_some() {
+            frame.chunk(&self.theme.hunk_separator())?;
+        }
+        let info = LineInfo::new(
+            change.tag(),
+            old_line_number,
+            new_line_number,
+            steps.drawn,
+            steps.previous != Some(change.tag()),
+        )
+        .with_missing_newline(change.missing_newline());
+        let written = frame.line(&DrawnLine {
+            diff: self,
+            line: &info,
+            values: change.values(),
+            whitespace_only: change.whitespace_only(),
+            line_ending_change: change.line_ending_change(),
+            normalization_changed: change.normalization_changed(),
+        })?;
+        let explicit = written && self.newline_policy == NewlinePolicy::Explicit;
+        if explicit && info.missing_newline() && change.line_ending_change().is_none() {
+            frame.chunk(&self.theme.no_newline())?;
+        }
+        steps.previous = Some(change.tag());
+        steps.last_drawn = Some(position);
+        steps.drawn += 1;
+
+        Ok(true)
+    }
+}
+
+impl DrawDiff<'_> {
+    /// What is being diffed, for the header
+    fn context(&self, changes: &[Change<'_>]) -> DiffContext<'_> {
+        let count = |tags: [ChangeTag; 2]| {
+            changes
+                .iter()
+                .filter(|change| tags.contains(&change.tag()))
+                .count()
+        };
+        let context = DiffContext::new(
+            count([ChangeTag::Insert, ChangeTag::MovedTo]),
+            count([ChangeTag::Delete, ChangeTag::MovedFrom]),
+            self.algorithm,
+        );
+
+        match self.labels {
+            Some((old, new)) => context.with_labels(old, new),
+            None => context,
+        }
+    }
+
+    /// Which of the changes should be drawn, leaving out unchanged lines
+    /// that are too far from a change
+    fn visible(&self, changes: &[Change<'_>], mut visible: Vec<bool>) -> Vec<bool> {
+        visible.clear();
+        visible.extend(
+            changes
+                .iter()
+                .map(|change| change.tag() != ChangeTag::Equal || altered(change)),
+        );
+
+        match self.context {
+            _ if self.only_changes => {}
+            None => {
+                visible.fill(true);
+                return visible;
+            }
+            Some(context) => show_context(&mut visible, context),
+        }
+
+        merge_gaps(&mut visible, self.merge_hunks_within);
+        visible
+    }
+}
+
+/// Show the lines within `context` lines of an altered one, given which
+/// lines are altered
+fn show_context(visible: &mut [bool], context: usize) {
+    let mut last_altered = None;
+
+    for index in 0..visible.len() {
+        // Lines from here on still say whether they are altered
+        if visible[index] {
+            visible[index.saturating_sub(context)..index].fill(true);
+            last_altered = Some(index);
         } else {
-            x.into()
+            visible[index] = last_altered.is_some_and(|last| index - last <= context);
         }
     }
 }
 
-impl Display for DrawDiff<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let (old, new): (Cow<'_, str>, Cow<'_, str>) =
-            self.replace_trailing_if_needed(self.old, self.new);
-        write!(f, "{}", self.theme.header())?;
-        let diff = TextDiff::from_lines(&old, &new);
-
-        for op in diff.ops() {
-            for change in diff.iter_inline_changes(op) {
-                write!(f, "{}", self.prefix(change.tag()))?;
-
-                for (highlight, inline_change) in change.values() {
-                    if *highlight {
-                        let cow = inline_change.to_string_lossy();
-                        let highlighted = self.highlight(cow.borrow(), change.tag());
-                        write!(
-                            f,
-                            "{}",
-                            self.format_line(highlighted.borrow(), change.tag())
-                        )?;
-                    } else {
-                        write!(f, "{}", self.format_line(inline_change, change.tag()))?;
+/// Show the lines in gaps between shown lines that are shorter than
+/// `within`
+fn merge_gaps(visible: &mut [bool], within: usize) {
+    let mut last_shown = None;
+
+    for index in 0..visible.len() {
+        if !visible[index] {
+            continue;
+        }
+        if let Some(last) = last_shown {
+            if index - last - 1 < within {
+                visible[last..index].fill(true);
+            }
+        }
+        last_shown = Some(index);
+    }
+}
+
+/// Where drawing a diff has got to, so it can be drawn a step at a time
+struct Steps<'lines> {
+    changes: Vec<Change<'lines>>,
+    visible: Vec<bool>,
+    leaves_lines_out: bool,
+    header: bool,
+    notice: Option<Cow<'static, str>>,
+    started: bool,
+    position: usize,
+    drawn: usize,
+    previous: Option<ChangeTag>,
+    last_drawn: Option<usize>,
+}
+
+impl Steps<'_> {
+    fn into_owned(self) -> Steps<'static> {
+        Steps {
+            changes: self.changes.into_iter().map(Change::into_owned).collect(),
+            visible: self.visible,
+            leaves_lines_out: self.leaves_lines_out,
+            header: self.header,
+            notice: self.notice,
+            started: self.started,
+            position: self.position,
+            drawn: self.drawn,
+            previous: self.previous,
+            last_drawn: self.last_drawn,
+        }
+    }
+}
+
+/// The chunks of a diff, drawn as they are asked for, see
+/// [`DrawDiff::render_chunks`]
+struct Chunks<'a, 'diff> {
+    diff: &'a DrawDiff<'diff>,
+    steps: Steps<'static>,
+    colors: Option<Palette>,
+    line_start: Cell<bool>,
+    // Each chunk is held back until the next one is drawn, so the newline
+    // can be taken off the last
+    held: Option<String>,
+    finished: bool,
+}
+
+impl<'a> Iterator for Chunks<'a, '_> {
+    type Item = Result<Cow<'a, str>, std::fmt::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Self {
+            diff,
+            steps,
+            colors,
+            line_start,
+            held,
+            finished,
+        } = self;
+
+        while !*finished {
+            let mut chunk = String::new();
+            let drawn = diff.framed(
+                *colors,
+                line_start,
+                &mut |part| write!(chunk, "{part}"),
+                |frame| diff.step(steps, frame),
+            );
+            match drawn {
+                Ok(true) if chunk.is_empty() => {}
+                Ok(true) => {
+                    if let Some(previous) = held.replace(chunk) {
+                        return Some(Ok(previous.into()));
                     }
                 }
-
-                if change.missing_newline() {
-                    write!(f, "{}", self.theme.line_end())?;
+                Ok(false) => *finished = true,
+                Err(error) => {
+                    *finished = true;
+                    *held = None;
+                    return Some(Err(error));
                 }
             }
         }
 
-        Ok(())
+        held.take().map(|last| {
+            if diff.final_newline {
+                Ok(last.into())
+            } else {
+                Ok(without_final_newline(&last).into_owned().into())
+            }
+        })
+    }
+}
+
+/// Where a render's chunks go, colored, made ASCII and indented as asked
+/// for, with drawn lines then passed through the post processor
+struct Frame<'a, 'diff> {
+    diff: &'a DrawDiff<'diff>,
+    colors: Option<Palette>,
+    line_start: &'a Cell<bool>,
+    emit: &'a mut dyn FnMut(&dyn Display) -> std::fmt::Result,
+}
+
+impl Frame<'_, '_> {
+    fn chunk(&mut self, chunk: &dyn Display) -> std::fmt::Result {
+        self.diff
+            .styled(chunk, self.colors, self.line_start, self.emit)
+    }
+
+    /// Emit a drawn line, saying whether it was written or left out by the
+    /// post processor
+    fn line(&mut self, line: &DrawnLine<'_, '_>) -> Result<bool, std::fmt::Error> {
+        let Some(post_processor) = self.diff.post_processor else {
+            self.chunk(line)?;
+            return Ok(true);
+        };
+
+        // The processor sees the line as it will be written, and what it
+        // gives back is written as it is
+        let line_start = self.line_start.get();
+        let mut drawn = String::new();
+        self.diff
+            .styled(line, self.colors, self.line_start, &mut |chunk| {
+                write!(drawn, "{chunk}")
+            })?;
+        let Some(processed) = post_processor.process(line.line.tag(), &drawn) else {
+            self.line_start.set(line_start);
+            return Ok(false);
+        };
+
+        self.line_start.set(match processed.chars().last() {
+            Some(last) => last == '\n',
+            None => line_start,
+        });
+        (self.emit)(&processed).map(|()| true)
+    }
+}
+
+/// A line of a diff, ready to be written
+struct DrawnLine<'a, 'diff> {
+    diff: &'a DrawDiff<'diff>,
+    line: &'a LineInfo,
+    values: &'a [(bool, Cow<'a, str>)],
+    whitespace_only: bool,
+    line_ending_change: Option<(LineEnding, LineEnding)>,
+    normalization_changed: bool,
+}
+
+impl Display for DrawnLine<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.diff.write_line(
+            f,
+            self.line,
+            self.values,
+            self.whitespace_only,
+            self.line_ending_change,
+            self.normalization_changed,
+        )
+    }
+}
+
+/// Formatting flags are used to fit the diff into other output. A width
+/// indents every line by that many of the fill character, and a precision
+/// cuts lines off after that many characters.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff};
+/// let theme = ArrowsTheme::default();
+/// let diff = DrawDiff::new("a long line\n", "a longer line\n", &theme);
+///
+/// assert_eq!(
+///     format!("{diff:>2.8}"),
+///     "  < left /\n  <a long \n  >a longe\n"
+/// );
+/// ```
+impl Display for DrawDiff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if f.width().is_none() && f.precision().is_none() {
+            return self.render(&mut |chunk| write!(f, "{chunk}"));
+        }
+
+        let indent = f.fill().to_string().repeat(f.width().unwrap_or_default());
+        let precision = f.precision();
+        let mut framed = Framed::new(f, &indent, precision);
+        self.render(&mut |chunk| write!(framed, "{chunk}"))
     }
 }
 
@@ -139,8 +2510,40 @@ impl From<DrawDiff<'_>> for String {
 
 #[cfg(test)]
 mod test {
+    use std::borrow::Cow;
+
     use super::DrawDiff;
-    use crate::{ArrowsColorTheme, ArrowsTheme};
+    use crate::{
+        ArrowsColorTheme, ArrowsTheme, CancellationToken, ChangeTag, ColorChoice, DiffOp,
+        DiffScratch, LineInfo, NewlinePolicy, PostProcessor, Theme,
+    };
+
+    #[test]
+    fn cancelling_does_not_discard_given_ops() {
+        let theme = ArrowsTheme::default();
+        let token = CancellationToken::new();
+        token.cancel();
+        let ops = [DiffOp::equal(0..1, 0..1), DiffOp::delete(1..2, 1)];
+
+        assert_eq!(
+            DrawDiff::new("a\nb\n", "a\n", &theme)
+                .with_ops(&ops)
+                .with_cancellation(&token)
+                .to_string(),
+            "< left / > right\n a\n<b\n"
+        );
+    }
+
+    #[test]
+    fn wide_characters_wrap_by_columns() {
+        let theme = ArrowsTheme::default();
+        let diff = DrawDiff::new("日本語\n", "a日本\n", &theme).with_wrap_width(3);
+
+        assert_eq!(
+            diff.to_string(),
+            "< left / > right\n<日\n↪本\n↪語\n>a日\n↪本\n"
+        );
+    }
 
     #[test]
     fn single_characters() {
@@ -238,4 +2641,194 @@ mod test {
 "
         );
     }
+
+    #[test]
+    fn pre_split_lines_match_text() {
+        let old = "The quick brown fox and\njumps over the sleepy dog";
+        let new = "The quick red fox and\njumps over the lazy dog\n";
+        let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+        let theme = ArrowsColorTheme {};
+
+        assert_eq!(
+            format!("{}", DrawDiff::from_lines(&old_lines, &new_lines, &theme)),
+            format!("{}", DrawDiff::new(old, new, &theme))
+        );
+    }
+
+    #[test]
+    fn context_groups_nearby_changes() {
+        let old = "a\nb\nc\nd\ne\nf\n";
+        let new = "a\nB\nc\nD\ne\nf\n";
+        let theme = ArrowsTheme {};
+
+        assert_eq!(
+            format!("{}", DrawDiff::new(old, new, &theme).with_context(1)),
+            "< left / > right\n a\n<b\n>B\n c\n<d\n>D\n e\n"
+        );
+        assert_eq!(
+            format!("{}", DrawDiff::new(old, old, &theme).with_context(1)),
+            "< left / > right\n"
+        );
+    }
+
+    #[test]
+    fn no_context_gives_the_position_of_each_group() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nB\nc\nd\ne\n";
+        let theme = ArrowsTheme {};
+
+        assert_eq!(
+            format!("{}", DrawDiff::new(old, new, &theme).with_context(0)),
+            "< left / > right\n@@ line 2 @@\n<b\n>B\n@@ line 5 @@\n>e\n"
+        );
+    }
+
+    #[test]
+    fn only_gaps_shorter_than_the_limit_are_merged() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "A\nb\nc\nd\nE\n";
+        let theme = ArrowsTheme {};
+        let diff = |within| {
+            DrawDiff::new(old, new, &theme)
+                .with_only_changes(true)
+                .with_merge_hunks_within(within)
+                .to_string()
+        };
+
+        assert_eq!(diff(3), "< left / > right\n<a\n>A\n⋯\n<e\n>E\n");
+        assert_eq!(diff(4), "< left / > right\n<a\n>A\n b\n c\n d\n<e\n>E\n");
+    }
+
+    #[test]
+    fn lines_without_a_newline_are_known_to_the_theme() {
+        #[derive(Debug)]
+        struct MissingTheme {}
+        impl Theme for MissingTheme {
+            fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+                " ".into()
+            }
+
+            fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+                "-".into()
+            }
+
+            fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+                "+".into()
+            }
+
+            fn header<'this>(&self) -> Cow<'this, str> {
+                "".into()
+            }
+
+            fn line_start<'this>(&self, line: &LineInfo) -> Cow<'this, str> {
+                if line.missing_newline() {
+                    "!".into()
+                } else {
+                    "".into()
+                }
+            }
+        }
+
+        let theme = MissingTheme {};
+        let draw = |old, new| {
+            DrawDiff::new(old, new, &theme)
+                .with_newline_policy(NewlinePolicy::Explicit)
+                .to_string()
+        };
+
+        assert_eq!(
+            draw("a\nb", "a\nc\n"),
+            " a\n!-b\n\\ No newline at end of file\n+c\n"
+        );
+        assert_eq!(
+            draw("a\nb", "a\nb"),
+            " a\n! b\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn post_processors_see_lines_as_they_are_written() {
+        #[derive(Debug, Default)]
+        struct Seen {
+            lines: std::cell::RefCell<Vec<String>>,
+        }
+        impl PostProcessor for Seen {
+            fn process<'line>(&self, tag: ChangeTag, line: &'line str) -> Option<Cow<'line, str>> {
+                self.lines.borrow_mut().push(format!("{tag:?} {line}"));
+                Some(line.into())
+            }
+        }
+
+        let theme = ArrowsColorTheme::default();
+        let seen = Seen::default();
+        DrawDiff::new("a\nb\n", "a\nc\n", &theme)
+            .with_color(ColorChoice::Never)
+            .with_post_processor(&seen)
+            .to_string();
+
+        assert_eq!(
+            seen.lines.into_inner(),
+            vec!["Equal  a\n", "Delete <b\n", "Insert >c\n"]
+        );
+    }
+
+    #[test]
+    fn post_processors_run_after_indenting_and_ascii() {
+        #[derive(Debug)]
+        struct Marked;
+        impl PostProcessor for Marked {
+            fn process<'line>(&self, _: ChangeTag, line: &'line str) -> Option<Cow<'line, str>> {
+                Some(format!("é{line}").into())
+            }
+        }
+
+        let theme = ArrowsTheme::default();
+        let actual = DrawDiff::new("é\n", "e\n", &theme)
+            .with_indent("  ")
+            .with_ascii_only(true)
+            .with_post_processor(&Marked)
+            .to_string();
+
+        assert_eq!(actual, "  < left / > right\né  <\\u{e9}\né  >e\n");
+    }
+
+    #[test]
+    fn chunks_add_up_to_the_whole_diff() {
+        let theme = ArrowsColorTheme::default();
+        let diffs = [
+            DrawDiff::new("a\nb\nc\n", "a\nB\nc", &theme),
+            DrawDiff::new("a\nb\n", "a\nb\n", &theme),
+            DrawDiff::new("a\nb\nc\nd\n", "A\nb\nc\nD\n", &theme)
+                .with_context(0)
+                .with_indent("  ")
+                .with_final_newline(false),
+        ];
+
+        for diff in diffs {
+            let chunks: String = diff.render_chunks().collect::<Result<_, _>>().unwrap();
+            assert_eq!(chunks, diff.to_string());
+        }
+    }
+
+    #[test]
+    fn the_capacity_estimate_covers_the_drawn_diff() {
+        let theme = ArrowsColorTheme::default();
+        let diff = DrawDiff::new("a\nb b\nc\n", "a\nb c\nd\ne\n", &theme);
+        let mut buffer = String::new();
+        diff.render_into(&mut buffer);
+        let capacity = buffer.capacity();
+
+        buffer.clear();
+        let wanted = std::cell::Cell::new(0);
+        diff.render_sized(
+            &mut |_| Ok(()),
+            &mut |size| wanted.set(size),
+            &mut DiffScratch::default(),
+        )
+        .unwrap();
+
+        assert!(wanted.get() >= diff.to_string().len());
+        assert_eq!(capacity, wanted.get(), "The string was only sized once");
+    }
 }
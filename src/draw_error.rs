@@ -0,0 +1,34 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+/// Why a diff couldn't be drawn
+///
+/// See [`DrawDiff::try_to_string`](crate::DrawDiff::try_to_string).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawError {
+    /// The diff was to be worked out with an
+    /// [`Algorithm::Custom`](crate::Algorithm::Custom) that no algorithm is
+    /// registered under, with its name
+    UnknownAlgorithm(String),
+    /// A theme or highlighter failed to format something
+    Format,
+}
+
+impl Display for DrawError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownAlgorithm(name) => write!(f, "no algorithm is registered as {name:?}"),
+            Self::Format => write!(f, "the diff could not be formatted"),
+        }
+    }
+}
+
+impl Error for DrawError {}
+
+impl From<std::fmt::Error> for DrawError {
+    fn from(_: std::fmt::Error) -> Self {
+        Self::Format
+    }
+}
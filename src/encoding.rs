@@ -0,0 +1,180 @@
+//! The text format diffs are written in by [`DiffResult::to_writer`]
+//!
+//! ```text
+//! termdiff 1
+//! old <length in bytes>
+//! <old text>
+//! new <length in bytes>
+//! <new text>
+//! <tag> <old start> <old end> <new start> <new end>
+//! ...
+//! ```
+//!
+//! Each text is followed by a newline, and there is an op on each line after
+//! them.
+//!
+//! [`DiffResult::to_writer`]: crate::DiffResult::to_writer
+
+use std::io::{Error, ErrorKind, Read, Write};
+
+use super::{change::ChangeTag, ops::DiffOp, verify::verify};
+
+const VERSION: &str = "termdiff 1";
+
+pub(crate) fn write_result(
+    mut writer: impl Write,
+    old: &str,
+    new: &str,
+    ops: &[DiffOp],
+) -> std::io::Result<()> {
+    writeln!(writer, "{VERSION}")?;
+    writeln!(writer, "old {}\n{old}", old.len())?;
+    writeln!(writer, "new {}\n{new}", new.len())?;
+
+    for op in ops {
+        let (old_range, new_range) = (op.old_range(), op.new_range());
+        writeln!(
+            writer,
+            "{} {} {} {} {}",
+            tag_name(op.tag()),
+            old_range.start,
+            old_range.end,
+            new_range.start,
+            new_range.end
+        )?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn read_result(mut reader: impl Read) -> std::io::Result<(String, String, Vec<DiffOp>)> {
+    let mut buffer = String::new();
+    reader.read_to_string(&mut buffer)?;
+
+    let rest = buffer
+        .strip_prefix(VERSION)
+        .and_then(|rest| rest.strip_prefix('\n'))
+        .ok_or_else(|| invalid("not a diff in a version that can be read"))?;
+    let (old, rest) = read_text(rest, "old")?;
+    let (new, rest) = read_text(rest, "new")?;
+    let ops = rest
+        .lines()
+        .map(read_op)
+        .collect::<Option<Vec<DiffOp>>>()
+        .ok_or_else(|| invalid("an op could not be read"))?;
+
+    verify(old, new, &ops).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+    Ok((old.to_string(), new.to_string(), ops))
+}
+
+/// Read a text and the newline after it, giving what comes after them
+fn read_text<'a>(input: &'a str, name: &str) -> std::io::Result<(&'a str, &'a str)> {
+    let (line, rest) = input
+        .split_once('\n')
+        .ok_or_else(|| invalid("a text is missing"))?;
+    let length: usize = line
+        .strip_prefix(name)
+        .and_then(|length| length.strip_prefix(' '))
+        .and_then(|length| length.parse().ok())
+        .ok_or_else(|| invalid("a text is missing its length"))?;
+
+    let text = rest
+        .get(..length)
+        .ok_or_else(|| invalid("a text is shorter than its length"))?;
+    let rest = rest[length..]
+        .strip_prefix('\n')
+        .ok_or_else(|| invalid("a text is longer than its length"))?;
+
+    Ok((text, rest))
+}
+
+fn read_op(line: &str) -> Option<DiffOp> {
+    let mut fields = line.split(' ');
+    let tag = match fields.next()? {
+        "equal" => ChangeTag::Equal,
+        "delete" => ChangeTag::Delete,
+        "insert" => ChangeTag::Insert,
+        "moved_from" => ChangeTag::MovedFrom,
+        "moved_to" => ChangeTag::MovedTo,
+        _ => return None,
+    };
+    let mut number = || fields.next()?.parse::<usize>().ok();
+    let (old_start, old_end, new_start, new_end) = (number()?, number()?, number()?, number()?);
+
+    Some(DiffOp::new(tag, old_start..old_end, new_start..new_end))
+}
+
+fn tag_name(tag: ChangeTag) -> &'static str {
+    match tag {
+        ChangeTag::Equal => "equal",
+        ChangeTag::Delete => "delete",
+        ChangeTag::Insert => "insert",
+        ChangeTag::MovedFrom => "moved_from",
+        ChangeTag::MovedTo => "moved_to",
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::DiffResult;
+
+    #[test]
+    fn written_diffs_read_back_the_same() {
+        let result = DiffResult::new("a\r\nb\nno newline", "a\r\nc\n\n");
+        let mut buffer = vec![];
+        result.to_writer(&mut buffer).unwrap();
+
+        assert_eq!(DiffResult::from_reader(buffer.as_slice()).unwrap(), result);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "termdiff 1\nold 15\na\r\nb\nno newline\nnew 6\na\r\nc\n\n\n\
+             equal 0 1 0 1\ndelete 1 3 1 1\ninsert 3 3 1 3\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips() {
+        let result = DiffResult::new("a\nb\n", "a\nc\n").with_byte_ranges();
+        let json = serde_json::to_string(&result).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<DiffResult<'static>>(&json).unwrap(),
+            result
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_ops_that_do_not_fit_the_texts() {
+        let json = serde_json::to_string(&DiffResult::new("a\n", "a\n")).unwrap();
+        let tampered = json.replacen(r#""new":"a\n""#, r#""new":"""#, 1);
+
+        assert_ne!(tampered, json);
+        assert!(serde_json::from_str::<DiffResult<'static>>(&tampered).is_err());
+    }
+
+    #[test]
+    fn broken_diffs_are_rejected() {
+        for input in [
+            "termdiff 2\nold 0\n\nnew 0\n\n",
+            "termdiff 1\nold 5\na\n",
+            "termdiff 1\nold 2\na\n\nnew 0\n\nequal 0 1 0 1\n",
+        ] {
+            assert_eq!(
+                DiffResult::from_reader(input.as_bytes())
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidData,
+                "Reading {input:?}"
+            );
+        }
+    }
+}
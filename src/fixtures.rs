@@ -0,0 +1,71 @@
+//! Generated inputs for benchmarking diffs, so the numbers from the crate's
+//! benchmarks can be reproduced, or compared with your own
+//!
+//! Needs the `bench-fixtures` feature. Every fixture is generated by a
+//! [`Generator`] with a fixed seed, so it is the same each time.
+//!
+//! # Examples
+//!
+//! ```
+//! use termdiff::{fixtures, ArrowsTheme, DrawDiff};
+//! let theme = ArrowsTheme::default();
+//!
+//! for fixture in fixtures::all() {
+//!     let _ = DrawDiff::new(&fixture.old, &fixture.new, &theme).to_string();
+//! }
+//! ```
+
+use super::testgen::Generator;
+
+/// A pair of texts to diff, with a name for them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    /// What kind of diff this is
+    pub name: &'static str,
+    /// The old text
+    pub old: String,
+    /// The new text
+    pub new: String,
+}
+
+impl Fixture {
+    fn generated(name: &'static str, lines: usize, density: f64) -> Self {
+        let (old, new) = Generator::new(0)
+            .with_lines(lines)
+            .with_change_density(density)
+            .pair();
+
+        Self { name, old, new }
+    }
+}
+
+/// Ten lines with one in ten changed
+#[must_use]
+pub fn small() -> Fixture {
+    Fixture::generated("small", 10, 0.1)
+}
+
+/// A thousand lines with one in fifty changed
+#[must_use]
+pub fn medium() -> Fixture {
+    Fixture::generated("medium", 1_000, 0.02)
+}
+
+/// Ten thousand lines with one in a thousand changed
+#[must_use]
+pub fn large() -> Fixture {
+    Fixture::generated("large", 10_000, 0.001)
+}
+
+/// Two thousand lines with every one changed, the worst case for most
+/// algorithms
+#[must_use]
+pub fn pathological() -> Fixture {
+    Fixture::generated("pathological", 2_000, 1.0)
+}
+
+/// Every fixture, smallest first
+#[must_use]
+pub fn all() -> Vec<Fixture> {
+    vec![small(), medium(), large(), pathological()]
+}
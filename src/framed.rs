@@ -0,0 +1,125 @@
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    fmt::{Display, Formatter, Write},
+};
+
+/// Writes text on to a formatter, indenting each line and cutting lines
+/// off after a number of characters
+///
+/// Escape sequences aren't counted as characters, and are kept even when
+/// the text around them is cut off, so styles are still reset.
+pub(crate) struct Framed<'a, 'b, 'c> {
+    inner: &'a mut Formatter<'b>,
+    indent: &'c str,
+    max_width: Option<usize>,
+    column: usize,
+    line_start: bool,
+    in_escape: bool,
+}
+
+impl<'a, 'b, 'c> Framed<'a, 'b, 'c> {
+    pub(crate) fn new(
+        inner: &'a mut Formatter<'b>,
+        indent: &'c str,
+        max_width: Option<usize>,
+    ) -> Self {
+        Self {
+            inner,
+            indent,
+            max_width,
+            column: 0,
+            line_start: true,
+            in_escape: false,
+        }
+    }
+}
+
+impl Write for Framed<'_, '_, '_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for character in s.chars() {
+            if self.line_start && character != '\n' {
+                self.inner.write_str(self.indent)?;
+                self.line_start = false;
+            }
+
+            if self.in_escape {
+                self.in_escape = !character.is_ascii_alphabetic() && character != '~';
+                self.inner.write_char(character)?;
+            } else if character == '\u{1b}' {
+                self.in_escape = true;
+                self.inner.write_char(character)?;
+            } else if character == '\n' {
+                self.inner.write_char(character)?;
+                self.column = 0;
+                self.line_start = true;
+            } else {
+                if !matches!(self.max_width, Some(width) if self.column >= width) {
+                    self.inner.write_char(character)?;
+                }
+                self.column += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays a chunk of output with a prefix at the start of each line,
+/// carrying over whether the last chunk ended a line
+pub(crate) struct Indented<'a> {
+    pub(crate) chunk: &'a dyn Display,
+    pub(crate) prefix: &'a str,
+    pub(crate) line_start: &'a Cell<bool>,
+}
+
+impl Display for Indented<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut framed = Framed::new(f, self.prefix, None);
+        framed.line_start = self.line_start.get();
+        write!(framed, "{}", self.chunk)?;
+        self.line_start.set(framed.line_start);
+
+        Ok(())
+    }
+}
+
+/// Text without the newline it ends with, keeping any escape sequences after
+/// the newline so styles are still reset
+pub(crate) fn without_final_newline(text: &str) -> Cow<'_, str> {
+    let Some(index) = text.rfind('\n') else {
+        return text.into();
+    };
+    let (before, after) = (&text[..index], &text[index + 1..]);
+
+    let mut in_escape = false;
+    let only_escapes = after.chars().all(|character| {
+        if in_escape {
+            in_escape = !character.is_ascii_alphabetic() && character != '~';
+            true
+        } else {
+            in_escape = character == '\u{1b}';
+            in_escape
+        }
+    });
+    if !only_escapes {
+        return text.into();
+    }
+
+    format!("{}{after}", before.strip_suffix('\r').unwrap_or(before)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::without_final_newline;
+
+    #[test]
+    fn styles_after_the_final_newline_are_kept() {
+        assert_eq!(without_final_newline("a\nb\r\n"), "a\nb");
+        assert_eq!(
+            without_final_newline("\u{1b}[31mb\n\u{1b}[39m"),
+            "\u{1b}[31mb\u{1b}[39m"
+        );
+        assert_eq!(without_final_newline("a\nb"), "a\nb");
+    }
+}
@@ -0,0 +1,87 @@
+use std::{fs::File, io::Write, path::Path};
+
+use memmap2::Mmap;
+
+use super::{bytes::diff_bytes, hex::HexDiff, themes::Theme};
+
+/// How far into a file to look for a nul byte when deciding if it is binary,
+/// the same as git
+const BINARY_CHECK_LENGTH: usize = 8000;
+
+/// Print a diff of two files to a writer, memory mapping them rather than
+/// reading them into memory
+///
+/// Files with a nul byte near their start are treated as binary and shown as
+/// a [`HexDiff`]. Anything else is diffed as text, like [`diff_bytes`], and
+/// written out as it is drawn. Needs the `fs` feature.
+///
+/// The files must not be changed while they are being diffed, as the memory
+/// they are mapped into would change underneath the diff.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_mmap, ArrowsTheme};
+/// let directory = std::env::temp_dir();
+/// let (old, new) = (directory.join("termdiff-old"), directory.join("termdiff-new"));
+/// std::fs::write(&old, "a\nb\n").unwrap();
+/// std::fs::write(&new, "a\nc\n").unwrap();
+/// let mut buffer: Vec<u8> = Vec::new();
+/// let theme = ArrowsTheme::default();
+/// diff_mmap(&mut buffer, &old, &new, &theme).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// assert_eq!(actual, "< left / > right\n a\n<b\n>c\n");
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to open or map either file, or on failing to write to
+/// the writer.
+pub fn diff_mmap(
+    w: &mut dyn Write,
+    old: impl AsRef<Path>,
+    new: impl AsRef<Path>,
+    theme: &dyn Theme,
+) -> std::io::Result<()> {
+    let old = map(old.as_ref())?;
+    let new = map(new.as_ref())?;
+    let old: &[u8] = old.as_deref().unwrap_or_default();
+    let new: &[u8] = new.as_deref().unwrap_or_default();
+
+    if is_binary(old) || is_binary(new) {
+        write!(w, "{}", HexDiff::new(old, new, theme))
+    } else {
+        diff_bytes(w, old, new, theme)
+    }
+}
+
+/// Map a file into memory, or nothing if it is empty, as empty files can't
+/// be mapped on every platform
+fn map(path: &Path) -> std::io::Result<Option<Mmap>> {
+    let file = File::open(path)?;
+
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+
+    // SAFETY: The map is only read from, and the caller is told not to change
+    // the file while it is being diffed
+    unsafe { Mmap::map(&file) }.map(Some)
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_CHECK_LENGTH)].contains(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_binary;
+
+    #[test]
+    fn nul_bytes_near_the_start_mean_binary() {
+        assert!(is_binary(b"a\0b"));
+        assert!(!is_binary(b"a\nb"));
+        assert!(!is_binary(&[b"a".repeat(8000), b"\0".to_vec()].concat()));
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt::Write;
+
+use super::{
+    bytes::diff_bytes_escaped,
+    color::{ColorChoice, Palette},
+    draw_diff::DrawDiff,
+    granularity::Granularity,
+    hex::HexDiff,
+    incremental::IncrementalDiff,
+    lsp::text_edits,
+    newline::NewlinePolicy,
+    pairing::LinePairing,
+    ranges::changed_ranges,
+    result::DiffResult,
+    themes::{ArrowsColorTheme, SignsTheme},
+    unified::parse_unified,
+    verify::verify,
+    whitespace::WhitespaceChanges,
+};
+
+/// Render the diff of two inputs every way the crate can, throwing the output
+/// away
+///
+/// No input should make any of this panic, so this is what the fuzz target in
+/// `fuzz/` runs. Use it to check that inputs from your own fuzzing or
+/// property tests are handled. The inputs are also read as unified diffs,
+/// which panics if a patch is read into ops that don't fit its texts.
+///
+/// Each new way of drawing or reading a diff belongs in here too.
+///
+/// # Panics
+///
+/// Only on finding a bug, such as a patch read into ops that don't fit its
+/// texts.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::fuzz_check;
+///
+/// fuzz_check("a\r\n\r\rb", "\u{feff}\n\t a \n");
+/// ```
+pub fn fuzz_check(old: &str, new: &str) {
+    let mut sink = String::new();
+    let color = ArrowsColorTheme::default();
+    let signs = SignsTheme::default();
+
+    let _ = write!(sink, "{}", DrawDiff::new(old, new, &color));
+    let _ = write!(
+        sink,
+        "{}",
+        DrawDiff::new(old, new, &signs)
+            .with_minimal_inline(true)
+            .with_move_detection(1)
+            .with_whitespace_changes(WhitespaceChanges::Highlight)
+            .with_line_pairing(LinePairing::Similarity)
+            .with_line_ending_markers(true)
+            .with_trailing_whitespace(true)
+            .with_wrap_width(3)
+            .with_only_changes(true)
+    );
+    let _ = write!(
+        sink,
+        "{}",
+        DrawDiff::new(old, new, &signs).with_whitespace_changes(WhitespaceChanges::Ignore)
+    );
+    let _ = write!(
+        sink,
+        "{}",
+        DrawDiff::new(old, new, &color)
+            .with_color(ColorChoice::Always)
+            .with_palette(Palette::Ansi16)
+            .with_ascii_only(true)
+            .with_indent("  ")
+            .with_bidi_isolates(true)
+            .with_hunk_headers(true)
+            .with_context(1)
+            .with_newline_policy(NewlinePolicy::Explicit)
+            .with_granularity(Granularity::Char)
+            .with_final_newline(false)
+    );
+    let _ = write!(
+        sink,
+        "{}",
+        DrawDiff::new(old, new, &signs)
+            .with_similarity_threshold(0.5)
+            .with_ignore_markers("a", "\r")
+            .with_newline_policy(NewlinePolicy::Explicit)
+    );
+
+    let result = DiffResult::new(old, new);
+    let _ = write!(sink, "{}", result.draw(&color));
+    sink.push_str(&result.old_annotated(&signs));
+    sink.push_str(&result.new_annotated(&signs));
+
+    let mut incremental = IncrementalDiff::new(old, old);
+    incremental.update(old, new);
+    incremental.update(new, old);
+
+    let _ = changed_ranges(old, new);
+    let _ = text_edits(old, new);
+    for patch in [old, &format!("@@ -1,2 +1 @@\n{old}\n{new}")] {
+        if let Ok(result) = parse_unified(patch) {
+            let (old, new) = result.texts();
+            assert_eq!(verify(old, new, result.ops()), Ok(()), "{patch:?}");
+            let _ = write!(sink, "{}", result.with_byte_ranges().draw(&signs));
+        }
+    }
+    let _ = diff_bytes_escaped(&mut std::io::sink(), old.as_bytes(), new.as_bytes(), &signs);
+    let _ = write!(
+        sink,
+        "{}",
+        HexDiff::new(old.as_bytes(), new.as_bytes(), &signs).with_bytes_per_row(3)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzz_check;
+
+    #[test]
+    fn awkward_inputs_do_not_panic() {
+        let pieces = ["", "a", " ", "\t", "\n", "\r", "\r\n", "é", "\u{200d}"];
+
+        for first in pieces {
+            for second in pieces {
+                for third in pieces {
+                    let text = format!("{first}{second}{third}");
+                    for other in pieces {
+                        fuzz_check(&text, other);
+                        fuzz_check(other, &text);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn awkward_patches_do_not_panic() {
+        let pieces = [
+            "-a\n",
+            "+b\n",
+            " c\n",
+            " ",
+            "\r\n",
+            "\\ No newline at end of file\n",
+            "@@ -1 +1 @@\n",
+        ];
+
+        for first in pieces {
+            for second in pieces {
+                for third in pieces {
+                    for fourth in pieces {
+                        let patch = format!("@@ -1,2 +1,2 @@\n{first}{second}{third}{fourth}");
+                        fuzz_check(&patch, "");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    io::{Error, Write},
+    ops::Range,
+};
+
+use git2::{Blob, Diff, Patch};
+
+use super::{
+    bytes::diff_bytes, draw_diff::DrawDiff, header::Header, newline::NewlinePolicy, themes::Theme,
+};
+
+/// Print a diff of two git blobs to a writer
+///
+/// A missing blob, for a file that was added or deleted, is treated as
+/// empty. Needs the `git` feature.
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer.
+pub fn diff_blobs(
+    w: &mut dyn Write,
+    old: Option<&Blob<'_>>,
+    new: Option<&Blob<'_>>,
+    theme: &dyn Theme,
+) -> std::io::Result<()> {
+    diff_bytes(
+        w,
+        old.map_or(&[], Blob::content),
+        new.map_or(&[], Blob::content),
+        theme,
+    )
+}
+
+/// Print every file in a git diff to a writer
+///
+/// Each file starts with the theme's [`entry_header`], naming it, and each
+/// hunk is drawn under the theme's [`hunk_header`], with the lines git gives
+/// for it. A last line without a
+/// newline is followed by the theme's [`no_newline`] line. Files git sees as
+/// binary only get their header. Needs the `git` feature.
+///
+/// [`entry_header`]: Theme::entry_header
+/// [`hunk_header`]: Theme::hunk_header
+/// [`no_newline`]: Theme::no_newline
+///
+/// # Examples
+///
+/// ```
+/// use git2::Diff;
+/// use termdiff::{diff_git, AnsiAssert, ArrowsTheme};
+/// let patch = "diff --git a/greeting.txt b/greeting.txt
+/// --- a/greeting.txt
+/// +++ b/greeting.txt
+/// @@ -1,2 +1,2 @@
+///  hello
+/// -world
+/// +there
+/// ";
+/// let diff = Diff::from_buffer(patch.as_bytes()).unwrap();
+/// let mut buffer: Vec<u8> = Vec::new();
+/// diff_git(&mut buffer, &diff, &ArrowsTheme::default()).unwrap();
+/// let actual: String = String::from_utf8(buffer).expect("Not valid UTF-8");
+///
+/// // The hunk header is dimmed
+/// assert_eq!(
+///     AnsiAssert::new(&actual).plain(),
+///     "=== greeting.txt\n@@ -1,2 +1,2 @@\n hello\n<world\n>there\n"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors on failing to write to the writer, or if git fails to give the
+/// changes to a file.
+pub fn diff_git(w: &mut dyn Write, diff: &Diff<'_>, theme: &dyn Theme) -> std::io::Result<()> {
+    for index in 0..diff.deltas().len() {
+        let Some(patch) = Patch::from_diff(diff, index).map_err(git_error)? else {
+            continue;
+        };
+        let delta = patch.delta();
+        let name = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        write!(w, "{}", theme.entry_header(&name))?;
+
+        for hunk_index in 0..patch.num_hunks() {
+            let (hunk, lines) = patch.hunk(hunk_index).map_err(git_error)?;
+            let (mut old, mut new) = (String::new(), String::new());
+
+            for line_index in 0..lines {
+                let line = patch
+                    .line_in_hunk(hunk_index, line_index)
+                    .map_err(git_error)?;
+                let content = String::from_utf8_lossy(line.content());
+
+                // Git marks a last line without a newline with a line of its
+                // own, saying which sides are missing it
+                match line.origin() {
+                    ' ' => {
+                        old.push_str(&content);
+                        new.push_str(&content);
+                    }
+                    '-' => old.push_str(&content),
+                    '+' => new.push_str(&content),
+                    '=' => {
+                        strip_newline(&mut old);
+                        strip_newline(&mut new);
+                    }
+                    '>' => strip_newline(&mut old),
+                    '<' => strip_newline(&mut new),
+                    _ => {}
+                }
+            }
+
+            write!(
+                w,
+                "{}",
+                theme.hunk_header(
+                    lines_of(hunk.old_start(), hunk.old_lines()),
+                    lines_of(hunk.new_start(), hunk.new_lines())
+                )
+            )?;
+            write!(
+                w,
+                "{}",
+                DrawDiff::new(&old, &new, theme)
+                    .with_header(Header::Never)
+                    .with_newline_policy(NewlinePolicy::Explicit)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The lines a side of a git hunk covers, where git gives the line before
+/// for a side with no lines
+fn lines_of(start: u32, lines: u32) -> Range<usize> {
+    let (start, lines) = (start as usize, lines as usize);
+    let start = if lines == 0 { start + 1 } else { start };
+
+    start..start + lines
+}
+
+/// Take the newline off the end of the last line
+fn strip_newline(text: &mut String) {
+    if text.ends_with('\n') {
+        text.pop();
+    }
+}
+
+fn git_error(error: git2::Error) -> Error {
+    Error::other(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::Diff;
+
+    use super::diff_git;
+    use crate::{AnsiAssert, ArrowsTheme};
+
+    #[test]
+    fn every_file_and_hunk_is_drawn() {
+        let patch = "diff --git a/a b/a\n--- a/a\n+++ b/a\n\
+                     @@ -1 +1 @@\n-x\n+y\n@@ -9 +9 @@\n-z\n+Z\n\
+                     diff --git a/b b/b\nnew file mode 100644\n--- /dev/null\n+++ b/b\n\
+                     @@ -0,0 +1 @@\n+new\n";
+        let diff = Diff::from_buffer(patch.as_bytes()).unwrap();
+        let mut buffer = vec![];
+        diff_git(&mut buffer, &diff, &ArrowsTheme {}).unwrap();
+
+        assert_eq!(
+            AnsiAssert::new(&String::from_utf8(buffer).unwrap()).plain(),
+            "=== a\n@@ -1,1 +1,1 @@\n<x\n>y\n@@ -9,1 +9,1 @@\n<z\n>Z\n=== b\n@@ -0,0 +1,1 @@\n>new\n"
+        );
+    }
+
+    #[test]
+    fn missing_newlines_are_written_out() {
+        let patch = "diff --git a/a b/a\n--- a/a\n+++ b/a\n\
+                     @@ -1 +1 @@\n-x\n\\ No newline at end of file\n+y\n\
+                     diff --git a/b b/b\n--- a/b\n+++ b/b\n\
+                     @@ -1,2 +1,2 @@\n-q\n+r\n z\n\\ No newline at end of file\n\
+                     diff --git a/c b/c\n--- a/c\n+++ b/c\n\
+                     @@ -1 +1 @@\n-x\n+y\n\\ No newline at end of file\n";
+        let diff = Diff::from_buffer(patch.as_bytes()).unwrap();
+        let mut buffer = vec![];
+        diff_git(&mut buffer, &diff, &ArrowsTheme {}).unwrap();
+
+        assert_eq!(
+            AnsiAssert::new(&String::from_utf8(buffer).unwrap()).plain(),
+            "=== a\n@@ -1,1 +1,1 @@\n<x\n\\ No newline at end of file\n>y\n\
+             === b\n@@ -1,2 +1,2 @@\n<q\n>r\n z\n\\ No newline at end of file\n\
+             === c\n@@ -1,1 +1,1 @@\n<x\n>y\n\\ No newline at end of file\n"
+        );
+    }
+}
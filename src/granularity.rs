@@ -0,0 +1,14 @@
+/// How finely to compare changed lines when emphasizing the parts of them
+/// that changed
+///
+/// See [`DrawDiff::with_granularity`](crate::DrawDiff::with_granularity).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Granularity {
+    /// Don't emphasize anything, only show which lines changed
+    Line,
+    /// Emphasize the words that changed
+    #[default]
+    Word,
+    /// Emphasize the characters that changed
+    Char,
+}
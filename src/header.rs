@@ -0,0 +1,33 @@
+/// When to write the theme's header above the diff
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff, Header};
+/// let theme = ArrowsTheme::default();
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DrawDiff::new("a\n", "a\n", &theme).with_header(Header::IfChanges)
+///     ),
+///     " a\n"
+/// );
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DrawDiff::new("a\n", "b\n", &theme).with_header(Header::IfChanges)
+///     ),
+///     "< left / > right\n<a\n>b\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Header {
+    /// Always write the header
+    #[default]
+    Always,
+    /// Never write the header
+    Never,
+    /// Only write the header if there are changes in the diff
+    IfChanges,
+}
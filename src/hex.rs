@@ -0,0 +1,218 @@
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter},
+};
+
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+use super::themes::{prefix_column, Theme};
+
+/// Draws the difference between two pieces of binary data as side by side
+/// hex dumps
+///
+/// Only rows with a changed byte in them are drawn, and a `*` marks where
+/// unchanged rows were skipped. Each row shows the offset, the bytes in hex
+/// and the bytes as ASCII for the old data, then the same for the new data.
+/// Changed bytes are styled by the theme.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, HexDiff};
+/// let theme = ArrowsTheme::default();
+/// let old = b"GET /index.html HTTP/1.1";
+/// let new = b"GET /index.html HTTP/1.0";
+///
+/// assert_eq!(
+///     format!("{}", HexDiff::new(old, new, &theme)),
+///     "< left / > right
+/// *
+/// <00000010  48 54 54 50 2f 31 2e 31  |HTTP/1.1|  >00000010  48 54 54 50 2f 31 2e 30  |HTTP/1.0|
+/// "
+/// );
+/// ```
+#[derive(Debug)]
+pub struct HexDiff<'a> {
+    old: &'a [u8],
+    new: &'a [u8],
+    theme: &'a dyn Theme,
+    bytes_per_row: usize,
+}
+
+/// A byte from the old data lined up with a byte from the new data, either
+/// of which may be missing
+#[derive(Debug, Clone, Copy)]
+struct Pair {
+    old: Option<u8>,
+    new: Option<u8>,
+    changed: bool,
+}
+
+impl HexDiff<'_> {
+    /// Make a new instance of the hex diff drawer
+    #[must_use]
+    pub fn new<'a>(old: &'a [u8], new: &'a [u8], theme: &'a dyn Theme) -> HexDiff<'a> {
+        HexDiff {
+            old,
+            new,
+            theme,
+            bytes_per_row: 8,
+        }
+    }
+
+    /// How many bytes to show on each row of the dumps, 8 by default
+    #[must_use]
+    pub fn with_bytes_per_row(mut self, bytes_per_row: usize) -> Self {
+        self.bytes_per_row = bytes_per_row.max(1);
+        self
+    }
+
+    /// Line the bytes of the old and new data up with each other
+    fn pairs(&self) -> Vec<Pair> {
+        let mut pairs = vec![];
+
+        for op in capture_diff_slices(Algorithm::Myers, self.old, self.new) {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            let length = old_range.len().max(new_range.len());
+
+            for offset in 0..length {
+                let old = old_range.clone().nth(offset).map(|index| self.old[index]);
+                let new = new_range.clone().nth(offset).map(|index| self.new[index]);
+
+                pairs.push(Pair {
+                    old,
+                    new,
+                    changed: tag != DiffTag::Equal,
+                });
+            }
+        }
+
+        pairs
+    }
+
+    fn write_side(
+        &self,
+        f: &mut Formatter<'_>,
+        offset: usize,
+        row: &[Pair],
+        side: fn(&Pair) -> Option<u8>,
+        style: fn(&dyn Theme, &str) -> String,
+    ) -> std::fmt::Result {
+        write!(f, "{offset:08x} ")?;
+
+        for index in 0..self.bytes_per_row {
+            let cell: Cow<'_, str> = match row.get(index).and_then(side) {
+                Some(byte) => format!("{byte:02x}").into(),
+                None => "  ".into(),
+            };
+
+            match row.get(index) {
+                Some(pair) if pair.changed => write!(f, " {}", style(self.theme, &cell))?,
+                _ => write!(f, " {cell}")?,
+            }
+        }
+
+        write!(f, "  |")?;
+
+        for index in 0..self.bytes_per_row {
+            let cell = match row.get(index).and_then(side) {
+                Some(byte) if byte.is_ascii_graphic() || byte == b' ' => char::from(byte),
+                Some(_) => '.',
+                None => ' ',
+            };
+
+            match row.get(index) {
+                Some(pair) if pair.changed => {
+                    write!(f, "{}", style(self.theme, cell.encode_utf8(&mut [0; 4])))?;
+                }
+                _ => write!(f, "{cell}")?,
+            }
+        }
+
+        write!(f, "|")
+    }
+}
+
+fn style_delete(theme: &dyn Theme, input: &str) -> String {
+    theme
+        .delete_content(&theme.highlight_delete(input))
+        .into_owned()
+}
+
+fn style_insert(theme: &dyn Theme, input: &str) -> String {
+    theme
+        .insert_line(&theme.highlight_insert(input))
+        .into_owned()
+}
+
+impl Display for HexDiff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.theme.header())?;
+
+        let mut old_offset = 0;
+        let mut new_offset = 0;
+        let mut skipped = false;
+        let prefix_width = self.theme.prefix_width();
+
+        for row in self.pairs().chunks(self.bytes_per_row) {
+            let old_length = row.iter().filter(|pair| pair.old.is_some()).count();
+            let new_length = row.iter().filter(|pair| pair.new.is_some()).count();
+
+            if row.iter().any(|pair| pair.changed) {
+                if skipped {
+                    writeln!(f, "*")?;
+                    skipped = false;
+                }
+
+                write!(
+                    f,
+                    "{}",
+                    prefix_column(self.theme, self.theme.delete_prefix(), prefix_width)
+                )?;
+                self.write_side(f, old_offset, row, |pair| pair.old, style_delete)?;
+                write!(
+                    f,
+                    "  {}",
+                    prefix_column(self.theme, self.theme.insert_prefix(), prefix_width)
+                )?;
+                self.write_side(f, new_offset, row, |pair| pair.new, style_insert)?;
+                writeln!(f)?;
+            } else {
+                skipped = true;
+            }
+
+            old_offset += old_length;
+            new_offset += new_length;
+        }
+
+        if skipped {
+            writeln!(f, "*")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HexDiff;
+    use crate::ArrowsTheme;
+
+    #[test]
+    fn inserted_bytes_shift_the_new_offsets() {
+        let theme = ArrowsTheme::default();
+        let actual = format!(
+            "{}",
+            HexDiff::new(b"\x00\x01\x02\x03\x04", b"\x00\xff\x01\x02\x03\x05", &theme)
+                .with_bytes_per_row(4)
+        );
+
+        assert_eq!(
+            actual,
+            "< left / > right
+<00000000  00    01 02  |. ..|  >00000000  00 ff 01 02  |....|
+<00000003  03 04        |..  |  >00000004  03 05        |..  |
+"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::{fmt::Debug, ops::Range};
+
+use crossterm::style::ContentStyle;
+
+/// A [`Highlighter`] styles the content of lines before the [`Theme`] is
+/// applied
+///
+/// This sits between the change stream and the theme, so content can be
+/// syntax highlighted and then have the diff colors layered on top, much like
+/// `delta` does.
+///
+/// [`Theme`]: crate::Theme
+pub trait Highlighter: Debug {
+    /// Split a whole line into styled spans
+    ///
+    /// The ranges are byte offsets into `line`, and any text that isn't
+    /// covered by a span is left unstyled. Lines are passed with their
+    /// trailing newline if they have one.
+    fn highlight_line(&self, line: &str) -> Vec<(Range<usize>, ContentStyle)>;
+}
+
+/// Apply the spans from a [`Highlighter`] to a fragment of a line
+///
+/// The fragment starts at `offset` bytes into the line the spans were
+/// computed for. Spans that start or end inside a character are widened to
+/// take in the whole character. Styled spans end by resetting the style, so
+/// `restore` is written after each of them to put back the style of the line.
+pub(crate) fn apply_spans(
+    fragment: &str,
+    offset: usize,
+    spans: &[(Range<usize>, ContentStyle)],
+    restore: &str,
+) -> String {
+    let end = offset + fragment.len();
+    let snap = |index: usize, step: fn(usize) -> usize| {
+        let mut index = index.clamp(offset, end) - offset;
+        while !fragment.is_char_boundary(index) {
+            index = step(index);
+        }
+        index
+    };
+    let mut buffer = String::with_capacity(fragment.len());
+    let mut position = 0;
+
+    for (range, style) in spans {
+        let start = snap(range.start, |index| index - 1).max(position);
+        let stop = snap(range.end, |index| index + 1);
+        if start >= stop {
+            continue;
+        }
+
+        buffer.push_str(&fragment[position..start]);
+        buffer.push_str(&style.apply(&fragment[start..stop]).to_string());
+        buffer.push_str(restore);
+        position = stop;
+    }
+
+    buffer.push_str(&fragment[position..]);
+    buffer
+}
+
+#[cfg(feature = "syntect")]
+pub use self::syntect_highlighter::SyntectHighlighter;
+
+#[cfg(feature = "syntect")]
+mod syntect_highlighter {
+    use std::{
+        fmt::{Debug, Formatter},
+        ops::Range,
+    };
+
+    use crossterm::style::{Attribute, Color, ContentStyle};
+    use syntect::{
+        easy::HighlightLines,
+        highlighting::{FontStyle, Style, Theme as SyntectTheme, ThemeSet},
+        parsing::{SyntaxReference, SyntaxSet},
+    };
+
+    use super::Highlighter;
+
+    /// A [`Highlighter`] backed by [`syntect`]
+    ///
+    /// Each line is highlighted in isolation, so constructs that span several
+    /// lines (such as block comments) are not tracked between lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{DrawDiff, SignsTheme, SyntectHighlighter};
+    /// let highlighter = SyntectHighlighter::for_token("rs");
+    /// let theme = SignsTheme::default();
+    /// let actual = format!(
+    ///     "{}",
+    ///     DrawDiff::new("let a = 1;\n", "let a = 2;\n", &theme).with_highlighter(&highlighter)
+    /// );
+    ///
+    /// assert!(actual.starts_with("--- remove | insert +++\n-"));
+    /// assert!(actual.contains("\u{1b}["));
+    /// ```
+    pub struct SyntectHighlighter {
+        syntax_set: SyntaxSet,
+        syntax: SyntaxReference,
+        theme: SyntectTheme,
+    }
+
+    impl SyntectHighlighter {
+        /// Use the bundled syntaxes and the `base16-ocean.dark` theme
+        ///
+        /// The token is a file extension or language name, like `rs` or
+        /// `Rust`. Unknown tokens fall back to plain text.
+        #[must_use]
+        pub fn for_token(token: &str) -> Self {
+            let syntax_set = SyntaxSet::load_defaults_newlines();
+            let theme = ThemeSet::load_defaults()
+                .themes
+                .remove("base16-ocean.dark")
+                .unwrap_or_default();
+
+            Self::new(syntax_set, token, theme)
+        }
+
+        /// Use a custom syntax set and theme
+        ///
+        /// The syntax set should be one that was loaded with newlines
+        /// included, as lines are passed with their newlines.
+        #[must_use]
+        pub fn new(syntax_set: SyntaxSet, token: &str, theme: SyntectTheme) -> Self {
+            let syntax = syntax_set
+                .find_syntax_by_token(token)
+                .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+                .clone();
+
+            Self {
+                syntax_set,
+                syntax,
+                theme,
+            }
+        }
+    }
+
+    impl Debug for SyntectHighlighter {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("SyntectHighlighter")
+                .field("syntax", &self.syntax.name)
+                .field("theme", &self.theme.name)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl Highlighter for SyntectHighlighter {
+        fn highlight_line(&self, line: &str) -> Vec<(Range<usize>, ContentStyle)> {
+            let mut highlighter = HighlightLines::new(&self.syntax, &self.theme);
+            let Ok(regions) = highlighter.highlight_line(line, &self.syntax_set) else {
+                return vec![];
+            };
+
+            let mut offset = 0;
+            regions
+                .into_iter()
+                .map(|(style, text)| {
+                    let range = offset..offset + text.len();
+                    offset = range.end;
+                    (range, to_content_style(style))
+                })
+                .collect()
+        }
+    }
+
+    fn to_content_style(style: Style) -> ContentStyle {
+        let mut content_style = ContentStyle::new();
+        content_style.foreground_color = Some(Color::Rgb {
+            r: style.foreground.r,
+            g: style.foreground.g,
+            b: style.foreground.b,
+        });
+
+        if style.font_style.contains(FontStyle::BOLD) {
+            content_style.attributes.set(Attribute::Bold);
+        }
+        if style.font_style.contains(FontStyle::ITALIC) {
+            content_style.attributes.set(Attribute::Italic);
+        }
+        if style.font_style.contains(FontStyle::UNDERLINE) {
+            content_style.attributes.set(Attribute::Underlined);
+        }
+
+        content_style
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{ContentStyle, Stylize};
+
+    use super::apply_spans;
+
+    #[test]
+    fn unstyled_without_spans() {
+        assert_eq!(apply_spans("abc", 0, &[], ""), "abc");
+    }
+
+    #[test]
+    fn spans_are_offset_into_the_line() {
+        let style = ContentStyle::new().red();
+        let actual = apply_spans("cdef", 2, &[(0..3, style), (5..6, style)], "");
+
+        assert_eq!(
+            actual,
+            format!("{}de{}", "c".red(), "f".red()),
+            "Only the overlapping parts of spans are styled"
+        );
+    }
+
+    #[test]
+    fn spans_inside_a_character_take_in_the_whole_character() {
+        let style = ContentStyle::new().red();
+        let actual = apply_spans("aéb", 0, &[(2..4, style)], "");
+
+        assert_eq!(actual, format!("a{}", "éb".red()));
+    }
+
+    #[test]
+    fn the_line_style_is_put_back_after_each_span() {
+        let style = ContentStyle::new().red();
+        let actual = apply_spans("ab", 0, &[(0..1, style)], "<restore>");
+
+        assert_eq!(actual, format!("{}<restore>b", "a".red()));
+    }
+}
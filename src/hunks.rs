@@ -0,0 +1,196 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use super::{change::ChangeTag, ops::DiffOp};
+
+/// Which group of changes in a diff is meant, by the lines it covers and
+/// what they say
+///
+/// An id doesn't depend on where the hunk comes in the diff or which other
+/// hunks there are, so it can be kept while the user picks which changes they
+/// want, like `git add -p`, and still pick out the same hunk if the diff is
+/// worked out again. Ids order by where their hunks are in the old text.
+///
+/// Get them from [`DiffResult::hunk_ids`].
+///
+/// [`DiffResult::hunk_ids`]: crate::DiffResult::hunk_ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HunkId {
+    old_start: usize,
+    old_end: usize,
+    new_start: usize,
+    new_end: usize,
+    content: u64,
+}
+
+impl HunkId {
+    /// The lines of the old text the hunk replaces, counting from 0
+    #[must_use]
+    pub fn old_range(&self) -> Range<usize> {
+        self.old_start..self.old_end
+    }
+
+    /// The lines of the new text the hunk puts in their place, counting from
+    /// 0
+    #[must_use]
+    pub fn new_range(&self) -> Range<usize> {
+        self.new_start..self.new_end
+    }
+}
+
+/// A group of ops next to each other that change something, with no
+/// unchanged lines between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hunk<'ops> {
+    ops: &'ops [DiffOp],
+}
+
+impl Hunk<'_> {
+    /// The lines of the old text the group covers
+    pub(crate) fn old_range(&self) -> Range<usize> {
+        self.ops[0].old_range().start..self.ops[self.ops.len() - 1].old_range().end
+    }
+
+    /// The lines of the new text the group covers
+    pub(crate) fn new_range(&self) -> Range<usize> {
+        self.ops[0].new_range().start..self.ops[self.ops.len() - 1].new_range().end
+    }
+
+    /// The id of the group, given the lines of the texts it is from
+    pub(crate) fn id(&self, old: &[&str], new: &[&str]) -> HunkId {
+        let (old_range, new_range) = (self.old_range(), self.new_range());
+        let mut hasher = DefaultHasher::new();
+        (&old[old_range.clone()], &new[new_range.clone()]).hash(&mut hasher);
+
+        HunkId {
+            old_start: old_range.start,
+            old_end: old_range.end,
+            new_start: new_range.start,
+            new_end: new_range.end,
+            content: hasher.finish(),
+        }
+    }
+
+    /// The ops in the group, with their ranges counted from the start of the
+    /// group rather than the start of the texts
+    pub(crate) fn rebased_ops(&self) -> Vec<DiffOp> {
+        let (old_start, new_start) = (self.old_range().start, self.new_range().start);
+        let rebase = |range: Range<usize>, start: usize| range.start - start..range.end - start;
+
+        self.ops
+            .iter()
+            .map(|op| {
+                DiffOp::new(
+                    op.tag(),
+                    rebase(op.old_range(), old_start),
+                    rebase(op.new_range(), new_start),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Each group of changes in a diff, in order
+pub(crate) fn hunks(ops: &[DiffOp]) -> impl Iterator<Item = Hunk<'_>> {
+    ops.split(|op| op.tag() == ChangeTag::Equal)
+        .filter(|ops| !ops.is_empty())
+        .map(|ops| Hunk { ops })
+}
+
+/// The old text with the changes in some of the hunks made, and the rest
+/// left out
+pub(crate) fn apply_hunks(
+    old: &[&str],
+    new: &[&str],
+    ops: &[DiffOp],
+    selected: &[HunkId],
+) -> String {
+    let mut text = String::new();
+    let mut copied = 0;
+
+    for hunk in hunks(ops) {
+        let old_range = hunk.old_range();
+        text.extend(old[copied..old_range.start].iter().copied());
+
+        if selected.contains(&hunk.id(old, new)) {
+            text.extend(new[hunk.new_range()].iter().copied());
+        } else {
+            text.extend(old[old_range.clone()].iter().copied());
+        }
+        copied = old_range.end;
+    }
+
+    text.extend(old[copied..].iter().copied());
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_hunks, hunks};
+    use crate::DiffOp;
+
+    #[test]
+    fn changes_split_by_unchanged_lines_are_separate_hunks() {
+        let ops = [
+            DiffOp::delete(0..1, 0),
+            DiffOp::insert(1, 0..1),
+            DiffOp::equal(1..3, 1..3),
+            DiffOp::insert(3, 3..5),
+        ];
+        let hunks: Vec<_> = hunks(&ops).collect();
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].old_range(), hunks[0].new_range()), (0..1, 0..1));
+        assert_eq!((hunks[1].old_range(), hunks[1].new_range()), (3..3, 3..5));
+        assert_eq!(hunks[1].rebased_ops(), vec![DiffOp::insert(0, 0..2)]);
+    }
+
+    #[test]
+    fn only_the_selected_hunks_are_applied() {
+        let old = ["a\n", "b\n", "c\n"];
+        let new = ["A\n", "b\n", "c\n", "d\n"];
+        let ops = [
+            DiffOp::delete(0..1, 0),
+            DiffOp::insert(1, 0..1),
+            DiffOp::equal(1..3, 1..3),
+            DiffOp::insert(3, 3..4),
+        ];
+
+        let ids: Vec<_> = hunks(&ops).map(|hunk| hunk.id(&old, &new)).collect();
+
+        assert_eq!(apply_hunks(&old, &new, &ops, &[]), "a\nb\nc\n");
+        assert_eq!(apply_hunks(&old, &new, &ops, &ids[1..]), "a\nb\nc\nd\n");
+        assert_eq!(apply_hunks(&old, &new, &ops, &ids), "A\nb\nc\nd\n");
+    }
+
+    #[test]
+    fn ids_do_not_depend_on_the_other_hunks() {
+        let old = ["a\n", "b\n", "c\n"];
+        let new = ["A\n", "b\n", "c\n", "d\n"];
+        let both = [
+            DiffOp::delete(0..1, 0),
+            DiffOp::insert(1, 0..1),
+            DiffOp::equal(1..3, 1..3),
+            DiffOp::insert(3, 3..4),
+        ];
+        let last_only = [DiffOp::equal(0..3, 0..3), DiffOp::insert(3, 3..4)];
+        let id = |ops: &[DiffOp]| hunks(ops).last().map(|hunk| hunk.id(&old, &new));
+
+        assert_eq!(id(&both), id(&last_only));
+        assert_eq!(
+            id(&both).map(|id| (id.old_range(), id.new_range())),
+            Some((3..3, 3..4))
+        );
+    }
+
+    #[test]
+    fn ids_of_hunks_with_other_lines_differ() {
+        let ops = [DiffOp::delete(0..1, 0), DiffOp::insert(1, 0..1)];
+        let id = |new: &[&str]| hunks(&ops).next().map(|hunk| hunk.id(&["a\n"], new));
+
+        assert_ne!(id(&["b\n"]), id(&["c\n"]));
+    }
+}
@@ -0,0 +1,128 @@
+use std::borrow::Cow;
+
+use super::{
+    change::{Change, ChangeTag},
+    themes::Theme,
+};
+
+/// Blank out the lines between each start and end marker, so the diff lines
+/// up the markers rather than what is between them
+pub(crate) fn mask_regions(lines: &mut [Cow<'_, str>], start: &str, end: &str) {
+    let mut inside = false;
+
+    for line in lines {
+        if inside && line.contains(end) {
+            inside = false;
+        } else if inside {
+            *line = Cow::Borrowed("");
+        } else if line.contains(start) {
+            inside = true;
+        }
+    }
+}
+
+/// Replace the lines between each unchanged start marker and the next
+/// unchanged end marker with one unchanged line, labelling them with the
+/// theme's [`ignored_region`](Theme::ignored_region)
+///
+/// A region with no end marker is left as it is.
+pub(crate) fn collapse_regions<'lines>(
+    changes: Vec<Change<'lines>>,
+    start: &str,
+    end: &str,
+    theme: &dyn Theme,
+) -> Vec<Change<'lines>> {
+    let mut collapsed = Vec::with_capacity(changes.len());
+    let mut region: Option<Vec<Change<'lines>>> = None;
+
+    for change in changes {
+        let unchanged = change.tag() == ChangeTag::Equal;
+
+        match region.as_mut() {
+            Some(lines) if unchanged && change.content().contains(end) => {
+                collapsed.extend(label(lines, theme));
+                collapsed.push(change);
+                region = None;
+            }
+            Some(lines) => lines.push(change),
+            None => {
+                if unchanged && change.content().contains(start) {
+                    region = Some(vec![]);
+                }
+                collapsed.push(change);
+            }
+        }
+    }
+
+    collapsed.extend(region.into_iter().flatten());
+    collapsed
+}
+
+/// The unchanged line standing in for the lines of a region, if it has any
+fn label<'lines>(lines: &[Change<'_>], theme: &dyn Theme) -> Option<Change<'lines>> {
+    let first = lines.first()?;
+    let old = lines
+        .iter()
+        .filter(|change| {
+            matches!(
+                change.tag(),
+                ChangeTag::Equal | ChangeTag::Delete | ChangeTag::MovedFrom
+            )
+        })
+        .count();
+    let new = lines
+        .iter()
+        .filter(|change| {
+            matches!(
+                change.tag(),
+                ChangeTag::Equal | ChangeTag::Insert | ChangeTag::MovedTo
+            )
+        })
+        .count();
+
+    let mut label = Change::new(
+        ChangeTag::Equal,
+        vec![(
+            false,
+            format!("{}\n", theme.ignored_region(old, new)).into(),
+        )],
+    );
+    let (old_number, new_number) = first.line_numbers();
+    label.set_line_numbers(old_number, new_number);
+
+    Some(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{collapse_regions, mask_regions};
+    use crate::{change::Change, ChangeTag, SnapshotTheme};
+
+    #[test]
+    fn only_lines_between_markers_are_masked() {
+        let mut lines: Vec<Cow<'_, str>> = ["a\n", "<\n", "b\n", ">\n", "c\n", "<\n", "d\n"]
+            .iter()
+            .map(|line| Cow::Borrowed(*line))
+            .collect();
+        mask_regions(&mut lines, "<", ">");
+
+        assert_eq!(lines, ["a\n", "<\n", "", ">\n", "c\n", "<\n", ""]);
+    }
+
+    #[test]
+    fn regions_without_an_end_are_left_alone() {
+        let change = |tag, line: &'static str| Change::new(tag, vec![(false, line.into())]);
+        let changes = vec![
+            change(ChangeTag::Equal, "<\n"),
+            change(ChangeTag::Delete, "a\n"),
+            change(ChangeTag::Insert, "b\n"),
+        ];
+
+        assert_eq!(
+            collapse_regions(changes.clone(), "<", ">", &SnapshotTheme::default()),
+            changes
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use super::{
+    algorithm::Algorithm,
+    cache::DiffCache,
+    intern::diff_lines,
+    lines::split_lines,
+    ops::{from_similar, DiffOp, MergeOps, OpTransform},
+};
+
+/// A diff that is kept up to date as the text changes
+///
+/// The hash of each line and the ops from the last diff are kept, so when
+/// the text changes only the region around the change is diffed again. This
+/// is much faster than a full diff when re-rendering after every small edit,
+/// such as on each keystroke in an editor.
+///
+/// The ops may not be exactly the ones a full diff would give, but they
+/// always describe how to turn the old text into the new text.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff, IncrementalDiff};
+/// let theme = ArrowsTheme::default();
+/// let old = "a\nb\nc\n";
+/// let mut diff = IncrementalDiff::new(old, "a\nb\nc\n");
+///
+/// let new = "a\nB\nc\n";
+/// diff.update(old, new);
+///
+/// assert_eq!(
+///     format!("{}", DrawDiff::new(old, new, &theme).with_ops(diff.ops())),
+///     "< left / > right\n a\n<b\n>B\n c\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalDiff {
+    old_hashes: Vec<u64>,
+    new_hashes: Vec<u64>,
+    ops: Vec<DiffOp>,
+}
+
+impl IncrementalDiff {
+    /// Diff some text, ready to be updated as it changes
+    #[must_use]
+    pub fn new(old: &str, new: &str) -> Self {
+        let old_lines: Vec<&str> = split_lines(old).collect();
+        let new_lines: Vec<&str> = split_lines(new).collect();
+
+        Self {
+            old_hashes: old_lines.iter().map(|line| hash(line)).collect(),
+            new_hashes: new_lines.iter().map(|line| hash(line)).collect(),
+            ops: from_similar(&diff_lines(&old_lines, &new_lines)),
+        }
+    }
+
+    /// Diff some text, ready to be updated as it changes, taking the diff
+    /// from a [`DiffCache`] if it has already been worked out
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{DiffCache, IncrementalDiff};
+    /// let mut cache = DiffCache::new(16);
+    /// let first = IncrementalDiff::new_cached("a\n", "b\n", &mut cache);
+    /// let second = IncrementalDiff::new_cached("a\n", "b\n", &mut cache);
+    ///
+    /// assert_eq!(first.ops(), second.ops());
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn new_cached(old: &str, new: &str, cache: &mut DiffCache) -> Self {
+        let ops = cache.diff(old, new, Algorithm::Myers).ops().to_vec();
+
+        Self {
+            old_hashes: split_lines(old).map(hash).collect(),
+            new_hashes: split_lines(new).map(hash).collect(),
+            ops,
+        }
+    }
+
+    /// The ops of the diff, ready to be rendered with [`DrawDiff::with_ops`]
+    ///
+    /// [`DrawDiff::with_ops`]: crate::DrawDiff::with_ops
+    #[must_use]
+    pub fn ops(&self) -> &[DiffOp] {
+        &self.ops
+    }
+
+    /// Bring the diff up to date with the new version of the text
+    ///
+    /// Either or both sides can have changed. Only the lines between the
+    /// first and last changed line, widened to the ops they touch, are
+    /// diffed again.
+    pub fn update(&mut self, old: &str, new: &str) {
+        let old_lines: Vec<&str> = split_lines(old).collect();
+        let new_lines: Vec<&str> = split_lines(new).collect();
+        let old_hashes: Vec<u64> = old_lines.iter().map(|line| hash(line)).collect();
+        let new_hashes: Vec<u64> = new_lines.iter().map(|line| hash(line)).collect();
+
+        if old_hashes == self.old_hashes && new_hashes == self.new_hashes {
+            return;
+        }
+
+        let (old_prefix, old_suffix) = unchanged(&self.old_hashes, &old_hashes);
+        let (new_prefix, new_suffix) = unchanged(&self.new_hashes, &new_hashes);
+
+        // Keep the ops that lie entirely before or after the changes, apart from
+        // the ones next to them, so changes can join up with their neighbours
+        let before = self
+            .ops
+            .iter()
+            .take_while(|op| op.old_range().end <= old_prefix && op.new_range().end <= new_prefix)
+            .count()
+            .saturating_sub(1);
+        let after = self.ops[before..]
+            .iter()
+            .rev()
+            .take_while(|op| {
+                op.old_range().start >= self.old_hashes.len() - old_suffix
+                    && op.new_range().start >= self.new_hashes.len() - new_suffix
+            })
+            .count()
+            .saturating_sub(1);
+
+        let old_start = self.ops[..before].last().map_or(0, |op| op.old_range().end);
+        let new_start = self.ops[..before].last().map_or(0, |op| op.new_range().end);
+        let (old_end, new_end) = self.ops[self.ops.len() - after..].first().map_or(
+            (old_lines.len(), new_lines.len()),
+            |op| {
+                (
+                    op.old_range().start + old_lines.len() - self.old_hashes.len(),
+                    op.new_range().start + new_lines.len() - self.new_hashes.len(),
+                )
+            },
+        );
+
+        let changed = from_similar(&diff_lines(
+            &old_lines[old_start..old_end],
+            &new_lines[new_start..new_end],
+        ))
+        .into_iter()
+        .map(|op| {
+            DiffOp::new(
+                op.tag(),
+                offset(op.old_range(), old_start, 0),
+                offset(op.new_range(), new_start, 0),
+            )
+        });
+        let unchanged_after = self.ops[self.ops.len() - after..].iter().map(|op| {
+            DiffOp::new(
+                op.tag(),
+                offset(op.old_range(), old_lines.len(), self.old_hashes.len()),
+                offset(op.new_range(), new_lines.len(), self.new_hashes.len()),
+            )
+        });
+
+        let ops = self.ops[..before]
+            .iter()
+            .cloned()
+            .chain(changed)
+            .chain(unchanged_after)
+            .collect();
+
+        self.ops = MergeOps::default().transform(&old_lines, &new_lines, ops);
+        self.old_hashes = old_hashes;
+        self.new_hashes = new_hashes;
+    }
+}
+
+fn hash(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The number of lines at the start and end that haven't changed, never
+/// overlapping
+fn unchanged(previous: &[u64], current: &[u64]) -> (usize, usize) {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(previous, current)| previous == current)
+        .count();
+
+    (prefix, suffix)
+}
+
+/// Move a range by the difference between two lengths
+fn offset(range: std::ops::Range<usize>, add: usize, subtract: usize) -> std::ops::Range<usize> {
+    range.start + add - subtract..range.end + add - subtract
+}
+
+#[cfg(test)]
+mod tests {
+    use similar::{capture_diff_slices, Algorithm};
+
+    use super::IncrementalDiff;
+    use crate::{lines::split_lines, ops::from_similar, ChangeTag, DiffOp};
+
+    /// Rebuild the new text from the old text and the ops
+    fn apply(old: &str, new: &str, ops: &[DiffOp]) -> String {
+        let old: Vec<&str> = split_lines(old).collect();
+        let new: Vec<&str> = split_lines(new).collect();
+        let mut old_position = 0;
+        let mut new_position = 0;
+        let mut applied = String::new();
+
+        for op in ops {
+            assert_eq!(op.old_range().start, old_position, "{ops:?}");
+            assert_eq!(op.new_range().start, new_position, "{ops:?}");
+            old_position = op.old_range().end;
+            new_position = op.new_range().end;
+
+            match op.tag() {
+                ChangeTag::Equal => {
+                    assert_eq!(old[op.old_range()], new[op.new_range()]);
+                    applied.extend(old[op.old_range()].iter().copied());
+                }
+                ChangeTag::Insert | ChangeTag::MovedTo => {
+                    applied.extend(new[op.new_range()].iter().copied());
+                }
+                ChangeTag::Delete | ChangeTag::MovedFrom => {}
+            }
+        }
+
+        assert_eq!(old_position, old.len());
+        assert_eq!(new_position, new.len());
+        applied
+    }
+
+    #[test]
+    fn small_edits_only_rediff_around_the_edit() {
+        let old = "a\nb\nc\nd\ne\nf\n";
+        let mut diff = IncrementalDiff::new(old, old);
+
+        for new in [
+            "a\nb\nc\nd\ne\nf\n",
+            "a\nb\nC\nd\ne\nf\n",
+            "a\nb\nC\nd\ne\nf\ng\n",
+            "x\na\nb\nC\nd\ne\nf\ng\n",
+            "x\na\nb\nd\ne\nf\ng",
+            "",
+            "a\nb\nc\nd\ne\nf\n",
+        ] {
+            diff.update(old, new);
+
+            assert_eq!(apply(old, new, diff.ops()), new);
+        }
+
+        assert_eq!(
+            diff.ops(),
+            from_similar(&capture_diff_slices(
+                Algorithm::Myers,
+                &split_lines(old).collect::<Vec<_>>(),
+                &split_lines(old).collect::<Vec<_>>()
+            ))
+        );
+    }
+
+    #[test]
+    fn both_sides_can_change() {
+        let mut diff = IncrementalDiff::new("a\nb\nc\n", "a\nc\n");
+
+        diff.update("a\nb\nc\nd\n", "a\nc\nd\n");
+        assert_eq!(apply("a\nb\nc\nd\n", "a\nc\nd\n", diff.ops()), "a\nc\nd\n");
+
+        diff.update("z\nb\nc\nd\n", "a\nc\nd\n");
+        assert_eq!(apply("z\nb\nc\nd\n", "a\nc\nd\n", diff.ops()), "a\nc\nd\n");
+    }
+}
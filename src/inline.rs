@@ -0,0 +1,407 @@
+use std::{borrow::Cow, ops::Range};
+
+use similar::{capture_diff_slices, get_diff_ratio, Algorithm, DiffTag};
+
+use super::{
+    change::{Change, ChangeTag},
+    granularity::Granularity,
+};
+
+/// Below this proportion of shared words lines are too different for inline
+/// highlights to be useful
+const MIN_RATIO: f32 = 0.5;
+
+/// A word, as the index of the line it is on and its byte range in that line
+#[derive(Debug, Clone)]
+struct Token {
+    line: usize,
+    range: Range<usize>,
+}
+
+/// Segments of each line, as byte ranges and whether they are emphasized
+type Segments = Vec<Vec<(bool, Range<usize>)>>;
+
+/// Build the changes for a replaced block of lines, emphasizing the words,
+/// or characters, that differ
+///
+/// When `minimal` is set only the part of each replaced run of words that
+/// actually differs is emphasized. Emphasized words only separated by
+/// whitespace are merged, and the common prefix and suffix of replaced runs
+/// are not emphasized, so `foo(bar),` becoming `foo(baz),` only emphasizes
+/// the `r` and the `z`.
+pub(crate) fn inline_changes<'a>(
+    old_lines: &[&'a str],
+    new_lines: &[&'a str],
+    minimal: bool,
+    granularity: Granularity,
+) -> Vec<Change<'a>> {
+    if granularity == Granularity::Line {
+        return unemphasized(old_lines, new_lines);
+    }
+
+    let old_tokens = tokenize(old_lines, granularity);
+    let new_tokens = tokenize(new_lines, granularity);
+    let old_words = words(old_lines, &old_tokens);
+    let new_words = words(new_lines, &new_tokens);
+    let ops = capture_diff_slices(Algorithm::Patience, &old_words, &new_words);
+
+    if get_diff_ratio(&ops, old_words.len(), new_words.len()) < MIN_RATIO {
+        return unemphasized(old_lines, new_lines);
+    }
+
+    let mut old_segments: Segments = vec![vec![]; old_lines.len()];
+    let mut new_segments: Segments = vec![vec![]; new_lines.len()];
+
+    for op in ops {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        let old_tokens = &old_tokens[old_range.clone()];
+        let new_tokens = &new_tokens[new_range.clone()];
+
+        match tag {
+            DiffTag::Equal => {
+                push_tokens(&mut old_segments, old_lines, old_tokens, None);
+                push_tokens(&mut new_segments, new_lines, new_tokens, None);
+            }
+            DiffTag::Delete => {
+                push_tokens(
+                    &mut old_segments,
+                    old_lines,
+                    old_tokens,
+                    Some(&(0..usize::MAX)),
+                );
+            }
+            DiffTag::Insert => {
+                push_tokens(
+                    &mut new_segments,
+                    new_lines,
+                    new_tokens,
+                    Some(&(0..usize::MAX)),
+                );
+            }
+            DiffTag::Replace => {
+                let old_text = old_words[old_range].concat();
+                let new_text = new_words[new_range].concat();
+                let (prefix, suffix) = if minimal {
+                    common_affixes(&old_text, &new_text)
+                } else {
+                    (0, 0)
+                };
+
+                push_tokens(
+                    &mut old_segments,
+                    old_lines,
+                    old_tokens,
+                    Some(&(prefix..old_text.len() - suffix)),
+                );
+                push_tokens(
+                    &mut new_segments,
+                    new_lines,
+                    new_tokens,
+                    Some(&(prefix..new_text.len() - suffix)),
+                );
+            }
+        }
+    }
+
+    if minimal {
+        bridge_whitespace(old_lines, &mut old_segments);
+        bridge_whitespace(new_lines, &mut new_segments);
+    }
+
+    to_changes(ChangeTag::Delete, old_lines, old_segments)
+        .chain(to_changes(ChangeTag::Insert, new_lines, new_segments))
+        .collect()
+}
+
+/// The changes for a replaced block of lines, with nothing emphasized
+pub(crate) fn unemphasized<'a>(old_lines: &[&'a str], new_lines: &[&'a str]) -> Vec<Change<'a>> {
+    old_lines
+        .iter()
+        .map(|line| Change::new(ChangeTag::Delete, vec![(false, (*line).into())]))
+        .chain(
+            new_lines
+                .iter()
+                .map(|line| Change::new(ChangeTag::Insert, vec![(false, (*line).into())])),
+        )
+        .collect()
+}
+
+/// Split lines into runs of whitespace and runs of everything else, or into
+/// characters, with newlines as tokens of their own
+fn tokenize(lines: &[&str], granularity: Granularity) -> Vec<Token> {
+    let mut tokens = vec![];
+
+    for (line_index, line) in lines.iter().enumerate() {
+        let mut start = 0;
+        let mut last_kind = None;
+
+        for (index, character) in line.char_indices() {
+            let kind = if character == '\n' {
+                0
+            } else if character.is_whitespace() {
+                1
+            } else {
+                2
+            };
+
+            let split = last_kind != Some(kind) || kind == 0 || granularity == Granularity::Char;
+            if last_kind.is_some() && split {
+                tokens.push(Token {
+                    line: line_index,
+                    range: start..index,
+                });
+                start = index;
+            }
+
+            last_kind = Some(kind);
+        }
+
+        if start < line.len() {
+            tokens.push(Token {
+                line: line_index,
+                range: start..line.len(),
+            });
+        }
+    }
+
+    tokens
+}
+
+/// The words, or characters, of some lines
+pub(crate) fn tokens<'a>(lines: &[&'a str], granularity: Granularity) -> Vec<&'a str> {
+    words(lines, &tokenize(lines, granularity))
+}
+
+fn words<'a>(lines: &[&'a str], tokens: &[Token]) -> Vec<&'a str> {
+    tokens
+        .iter()
+        .map(|token| &lines[token.line][token.range.clone()])
+        .collect()
+}
+
+/// The length in bytes of the common prefix and suffix of two strings, where
+/// the suffix doesn't overlap the prefix
+pub(crate) fn common_affixes(old: &str, new: &str) -> (usize, usize) {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(old, new)| old == new)
+        .map(|(character, _)| character.len_utf8())
+        .sum();
+
+    let suffix = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(old, new)| old == new)
+        .map(|(character, _)| character.len_utf8())
+        .sum();
+
+    (prefix, suffix)
+}
+
+/// Add tokens to the segments of their lines
+///
+/// The `emphasized` range is relative to the start of the first token, as if
+/// all the tokens were one string. Newlines are never emphasized.
+fn push_tokens(
+    segments: &mut Segments,
+    lines: &[&str],
+    tokens: &[Token],
+    emphasized: Option<&Range<usize>>,
+) {
+    let mut offset = 0;
+
+    for token in tokens {
+        let length = token.range.len();
+        let (emphasis_start, emphasis_end) = emphasized.map_or((length, length), |range| {
+            let start = range.start.saturating_sub(offset).min(length);
+            let end = range.end.saturating_sub(offset).clamp(start, length);
+            (start, end)
+        });
+        let is_newline = lines[token.line][token.range.clone()] == *"\n";
+        let line = &mut segments[token.line];
+        let start = token.range.start;
+
+        push_segment(line, false, start..start + emphasis_start);
+        push_segment(
+            line,
+            !is_newline,
+            start + emphasis_start..start + emphasis_end,
+        );
+        push_segment(line, false, start + emphasis_end..token.range.end);
+
+        offset += length;
+    }
+}
+
+fn push_segment(line: &mut Vec<(bool, Range<usize>)>, emphasized: bool, range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+
+    match line.last_mut() {
+        Some((last_emphasized, last_range))
+            if *last_emphasized == emphasized && last_range.end == range.start =>
+        {
+            last_range.end = range.end;
+        }
+        _ => line.push((emphasized, range)),
+    }
+}
+
+/// Merge emphasized segments that are only separated by whitespace, so
+/// several changed words in a row read as one change
+fn bridge_whitespace(lines: &[&str], segments: &mut Segments) {
+    for (line, line_segments) in lines.iter().zip(segments.iter_mut()) {
+        *line_segments = bridge_line(line, std::mem::take(line_segments));
+    }
+}
+
+fn bridge_line(line: &str, segments: Vec<(bool, Range<usize>)>) -> Vec<(bool, Range<usize>)> {
+    let mut bridged: Vec<(bool, Range<usize>)> = Vec::with_capacity(segments.len());
+    let mut segments = segments.into_iter().peekable();
+
+    while let Some((emphasized, range)) = segments.next() {
+        let is_gap = !emphasized
+            && line[range.clone()]
+                .chars()
+                .all(|character| character.is_whitespace() && character != '\n');
+        let between_emphasized =
+            matches!(bridged.last(), Some((true, _))) && matches!(segments.peek(), Some((true, _)));
+
+        if is_gap && between_emphasized {
+            if let (Some((_, last_range)), Some((_, next_range))) =
+                (bridged.last_mut(), segments.next())
+            {
+                last_range.end = next_range.end;
+            }
+        } else {
+            bridged.push((emphasized, range));
+        }
+    }
+
+    bridged
+}
+
+fn to_changes<'a, 'lines>(
+    tag: ChangeTag,
+    lines: &'lines [&'a str],
+    segments: Segments,
+) -> impl Iterator<Item = Change<'a>> + 'lines {
+    lines.iter().zip(segments).map(move |(line, segments)| {
+        Change::new(
+            tag,
+            segments
+                .into_iter()
+                .map(|(emphasized, range)| (emphasized, Cow::Borrowed(&line[range])))
+                .collect(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::inline_changes;
+    use crate::{
+        change::{Change, ChangeTag},
+        Granularity,
+    };
+
+    #[test]
+    fn only_the_differing_characters_are_emphasized() {
+        let actual = inline_changes(
+            &["call(foo(bar), x)\n"],
+            &["call(foo(baz), x)\n"],
+            true,
+            Granularity::Word,
+        );
+
+        assert_eq!(
+            actual,
+            vec![
+                Change::new(
+                    ChangeTag::Delete,
+                    vec![
+                        (false, "call(foo(ba".into()),
+                        (true, "r".into()),
+                        (false, "), x)\n".into())
+                    ]
+                ),
+                Change::new(
+                    ChangeTag::Insert,
+                    vec![
+                        (false, "call(foo(ba".into()),
+                        (true, "z".into()),
+                        (false, "), x)\n".into())
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn adjacent_changed_words_are_merged() {
+        let actual = inline_changes(
+            &["the quick brown fox jumps\n"],
+            &["the slow red fox jumps\n"],
+            true,
+            Granularity::Word,
+        );
+
+        assert_eq!(
+            actual,
+            vec![
+                Change::new(
+                    ChangeTag::Delete,
+                    vec![
+                        (false, "the ".into()),
+                        (true, "quick brown".into()),
+                        (false, " fox jumps\n".into())
+                    ]
+                ),
+                Change::new(
+                    ChangeTag::Insert,
+                    vec![
+                        (false, "the ".into()),
+                        (true, "slow red".into()),
+                        (false, " fox jumps\n".into())
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn characters_can_be_compared() {
+        let actual = inline_changes(&["colour\n"], &["color\n"], false, Granularity::Char);
+
+        assert_eq!(
+            actual,
+            vec![
+                Change::new(
+                    ChangeTag::Delete,
+                    vec![
+                        (false, "colo".into()),
+                        (true, "u".into()),
+                        (false, "r\n".into())
+                    ]
+                ),
+                Change::new(ChangeTag::Insert, vec![(false, "color\n".into())]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unrelated_lines_are_not_emphasized() {
+        let actual = inline_changes(&["abc\n"], &["xyz"], true, Granularity::Word);
+
+        assert_eq!(
+            actual,
+            vec![
+                Change::new(ChangeTag::Delete, vec![(false, "abc\n".into())]),
+                Change::new(ChangeTag::Insert, vec![(false, "xyz".into())]),
+            ]
+        );
+    }
+}
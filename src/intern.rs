@@ -0,0 +1,341 @@
+use std::{collections::HashMap, convert::Infallible, hash::Hash};
+
+use similar::{
+    algorithms::{diff_deadline, Capture, Compact, DiffHook, Replace},
+    capture_diff_slices, Algorithm,
+};
+
+use super::cancellation::Cancelled;
+
+/// Diff two lists of lines
+///
+/// Lines are interned into ids first, so the algorithm compares integers
+/// rather than strings, which matters for long lines.
+pub(crate) fn diff_lines<T: Hash + Eq + ?Sized>(old: &[&T], new: &[&T]) -> Vec<similar::DiffOp> {
+    diff_lines_with(Algorithm::Myers, old, new)
+}
+
+/// Diff two lists of lines with a particular algorithm, see [`diff_lines`]
+///
+/// Lines the two lists start and end with are trimmed off first, so only the
+/// part in between is interned and diffed, which is most of the saving when
+/// there's a small change in a large text.
+pub(crate) fn diff_lines_with<T: Hash + Eq + ?Sized>(
+    algorithm: Algorithm,
+    old: &[&T],
+    new: &[&T],
+) -> Vec<similar::DiffOp> {
+    trimmed(old, new, |old_ids, new_ids, _| {
+        Ok::<_, Infallible>(capture_diff_slices(algorithm, old_ids, new_ids))
+    })
+    .unwrap_or_else(|never| match never {})
+}
+
+/// Diff two lists of lines with a particular algorithm, see [`diff_lines`],
+/// telling `observe` how many of the old lines have been worked through as
+/// it goes, out of how many there are
+///
+/// The diff is given up on as soon as `observe` returns `false`.
+pub(crate) fn diff_lines_observed<T: Hash + Eq + ?Sized>(
+    algorithm: Algorithm,
+    old: &[&T],
+    new: &[&T],
+    observe: &mut dyn FnMut(usize, usize) -> bool,
+) -> Result<Vec<similar::DiffOp>, Cancelled> {
+    let total = old.len();
+    let mut done = None;
+    let mut reached = |line: usize| {
+        if done.is_none_or(|done| line > done) {
+            done = Some(line);
+            observe(line, total)
+        } else {
+            true
+        }
+    };
+    if !reached(0) {
+        return Err(Cancelled);
+    }
+
+    let ops = trimmed(old, new, |old_ids, new_ids, prefix| {
+        if !reached(prefix) {
+            return Err(Cancelled);
+        }
+        let mut observed = Observed {
+            inner: Compact::new(Replace::new(Capture::new()), old_ids, new_ids),
+            offset: prefix,
+            reached: &mut reached,
+        };
+        diff_deadline(
+            algorithm,
+            &mut observed,
+            old_ids,
+            0..old_ids.len(),
+            new_ids,
+            0..new_ids.len(),
+            None,
+        )?;
+
+        Ok(observed.inner.into_inner().into_inner().into_ops())
+    })?;
+
+    if reached(total) {
+        Ok(ops)
+    } else {
+        Err(Cancelled)
+    }
+}
+
+/// Trim off the lines the two lists start and end with, then diff the ids
+/// of the lines in between
+fn trimmed<T: Hash + Eq + ?Sized, E>(
+    old: &[&T],
+    new: &[&T],
+    diff: impl FnOnce(&[usize], &[usize], usize) -> Result<Vec<similar::DiffOp>, E>,
+) -> Result<Vec<similar::DiffOp>, E> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut ops = Vec::new();
+    if prefix > 0 {
+        ops.push(similar::DiffOp::Equal {
+            old_index: 0,
+            new_index: 0,
+            len: prefix,
+        });
+    }
+    if !old_middle.is_empty() || !new_middle.is_empty() {
+        let (old_ids, new_ids) = intern(old_middle, new_middle);
+        ops.extend(
+            diff(&old_ids, &new_ids, prefix)?
+                .into_iter()
+                .map(|op| offset(op, prefix)),
+        );
+    }
+    if suffix > 0 {
+        ops.push(similar::DiffOp::Equal {
+            old_index: old.len() - suffix,
+            new_index: new.len() - suffix,
+            len: suffix,
+        });
+    }
+
+    Ok(ops)
+}
+
+/// Passes the ops of a diff on, saying how far through the old lines the
+/// algorithm has got, and stopping it if told to
+struct Observed<'a, D> {
+    inner: D,
+    offset: usize,
+    reached: &'a mut dyn FnMut(usize) -> bool,
+}
+
+impl<D> Observed<'_, D> {
+    fn reached(&mut self, line: usize) -> Result<(), Cancelled> {
+        if (self.reached)(self.offset + line) {
+            Ok(())
+        } else {
+            Err(Cancelled)
+        }
+    }
+}
+
+impl<D: DiffHook<Error = Infallible>> DiffHook for Observed<'_, D> {
+    type Error = Cancelled;
+
+    fn equal(&mut self, old_index: usize, new_index: usize, len: usize) -> Result<(), Cancelled> {
+        let Ok(()) = self.inner.equal(old_index, new_index, len);
+        self.reached(old_index + len)
+    }
+
+    fn delete(
+        &mut self,
+        old_index: usize,
+        old_len: usize,
+        new_index: usize,
+    ) -> Result<(), Cancelled> {
+        let Ok(()) = self.inner.delete(old_index, old_len, new_index);
+        self.reached(old_index + old_len)
+    }
+
+    fn insert(
+        &mut self,
+        old_index: usize,
+        new_index: usize,
+        new_len: usize,
+    ) -> Result<(), Cancelled> {
+        let Ok(()) = self.inner.insert(old_index, new_index, new_len);
+        self.reached(old_index)
+    }
+
+    fn replace(
+        &mut self,
+        old_index: usize,
+        old_len: usize,
+        new_index: usize,
+        new_len: usize,
+    ) -> Result<(), Cancelled> {
+        let Ok(()) = self.inner.replace(old_index, old_len, new_index, new_len);
+        self.reached(old_index + old_len)
+    }
+
+    fn finish(&mut self) -> Result<(), Cancelled> {
+        let Ok(()) = self.inner.finish();
+        Ok(())
+    }
+}
+
+/// Move an op later in both lists of lines
+fn offset(op: similar::DiffOp, by: usize) -> similar::DiffOp {
+    match op {
+        similar::DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } => similar::DiffOp::Equal {
+            old_index: old_index + by,
+            new_index: new_index + by,
+            len,
+        },
+        similar::DiffOp::Delete {
+            old_index,
+            old_len,
+            new_index,
+        } => similar::DiffOp::Delete {
+            old_index: old_index + by,
+            old_len,
+            new_index: new_index + by,
+        },
+        similar::DiffOp::Insert {
+            old_index,
+            new_index,
+            new_len,
+        } => similar::DiffOp::Insert {
+            old_index: old_index + by,
+            new_index: new_index + by,
+            new_len,
+        },
+        similar::DiffOp::Replace {
+            old_index,
+            old_len,
+            new_index,
+            new_len,
+        } => similar::DiffOp::Replace {
+            old_index: old_index + by,
+            old_len,
+            new_index: new_index + by,
+            new_len,
+        },
+    }
+}
+
+/// Give each distinct line an id, shared between the old and new lines
+fn intern<'a, T: Hash + Eq + ?Sized>(old: &[&'a T], new: &[&'a T]) -> (Vec<usize>, Vec<usize>) {
+    let mut ids: HashMap<&'a T, usize> = HashMap::with_capacity(old.len());
+    let mut id = |line: &&'a T| {
+        let next = ids.len();
+        *ids.entry(*line).or_insert(next)
+    };
+
+    let old = old.iter().map(&mut id).collect();
+    let new = new.iter().map(&mut id).collect();
+    (old, new)
+}
+
+#[cfg(test)]
+mod tests {
+    use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+    use super::{diff_lines, diff_lines_observed, intern};
+    use crate::Cancelled;
+
+    #[test]
+    fn equal_lines_share_an_id() {
+        assert_eq!(
+            intern(&["a\n", "b\n", "a\n"], &["b\n", "c\n"]),
+            (vec![0, 1, 0], vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn shared_start_and_end_are_trimmed_off() {
+        let old = ["a\n", "b\n", "c\n", "d\n"];
+        let new = ["a\n", "B\n", "c\n", "d\n"];
+
+        assert_eq!(
+            diff_lines(&old, &new),
+            vec![
+                DiffOp::Equal {
+                    old_index: 0,
+                    new_index: 0,
+                    len: 1
+                },
+                DiffOp::Replace {
+                    old_index: 1,
+                    old_len: 1,
+                    new_index: 1,
+                    new_len: 1
+                },
+                DiffOp::Equal {
+                    old_index: 2,
+                    new_index: 2,
+                    len: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn interning_does_not_change_the_diff() {
+        let old = ["a\n", "b\n", "c\n", "a\n", "b\n", "b\n", "a\n"];
+        let new = ["c\n", "b\n", "a\n", "b\n", "a\n", "c\n"];
+
+        assert_eq!(
+            diff_lines(&old, &new),
+            capture_diff_slices(Algorithm::Myers, &old, &new)
+        );
+    }
+
+    #[test]
+    fn progress_only_goes_forward_and_does_not_change_the_diff() {
+        let old = ["a\n", "b\n", "c\n", "a\n", "b\n", "b\n", "a\n"];
+        let new = ["a\n", "b\n", "a\n", "b\n", "a\n", "c\n"];
+        let mut reported = vec![];
+        let mut observe = |done, total| {
+            reported.push((done, total));
+            true
+        };
+
+        assert_eq!(
+            diff_lines_observed(Algorithm::Myers, &old, &new, &mut observe),
+            Ok(diff_lines(&old, &new))
+        );
+        assert!(reported.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(reported.last(), Some(&(7, 7)));
+    }
+
+    #[test]
+    fn the_diff_stops_when_told_to() {
+        let old = ["a\n", "b\n", "c\n"];
+        let new = ["a\n", "c\n", "d\n"];
+        let mut observe = |done, _| done < 2;
+
+        assert_eq!(
+            diff_lines_observed(Algorithm::Myers, &old, &new, &mut observe),
+            Err(Cancelled)
+        );
+    }
+}
@@ -122,13 +122,160 @@
     missing_docs
 )]
 
-pub use cmd::diff;
+pub use algorithm::{register_algorithm, Algorithm, DiffAlgorithm};
+pub use annotations::{AnnotationLevel, GithubAnnotations};
+pub use ansi::AnsiAssert;
+pub use batch::{diff_batch, diff_batch_cached};
+pub use bytes::{diff_bytes, diff_bytes_escaped};
+pub use cache::DiffCache;
+pub use cancellation::{CancellationToken, Cancelled};
+pub use change::ChangeTag;
+#[cfg(feature = "async")]
+pub use cmd::diff_async;
+pub use cmd::{
+    compare, confirm_with_diff, diff, diff_debug, diff_iter, diff_lines, diff_reader,
+    diff_to_string, diff_with, diff_with_scratch, identical, print_diff,
+};
+pub use color::{enable_ansi_support, ColorChoice, Palette};
+pub use config::DiffConfig;
+pub use diff_context::DiffContext;
+pub use distance::distance;
 pub use draw_diff::DrawDiff;
-pub use themes::{ArrowsColorTheme, ArrowsTheme, SignsColorTheme, SignsTheme, Theme};
+pub use draw_error::DrawError;
+#[cfg(feature = "fs")]
+pub use fs::diff_mmap;
+pub use fuzz::fuzz_check;
+#[cfg(feature = "git")]
+pub use git::{diff_blobs, diff_git};
+pub use granularity::Granularity;
+pub use header::Header;
+pub use hex::HexDiff;
+pub use highlight::Highlighter;
+#[cfg(feature = "syntect")]
+pub use highlight::SyntectHighlighter;
+pub use hunks::HunkId;
+pub use incremental::IncrementalDiff;
+pub use line_endings::LineEnding;
+pub use line_info::LineInfo;
+pub use lsp::{text_edits, Position, TextEdit};
+pub use maps::diff_maps;
+pub use minimap::Minimap;
+pub use newline::NewlinePolicy;
+#[cfg(feature = "normalization")]
+pub use normalization::Normalization;
+pub use ops::{DiffOp, MergeOps, OpTransform};
+pub use pairing::LinePairing;
+pub use post_process::PostProcessor;
+pub use progress::Progress;
+pub use ranges::changed_ranges;
+pub use redact::Redactor;
+pub use replace_order::ReplaceOrder;
+pub use result::DiffResult;
+pub use scratch::DiffScratch;
+pub use sections::SectionDiff;
+pub use sentences::SentenceDiff;
+pub use set::DiffSet;
+pub use single_line::inline;
+pub use stat::DiffStatRenderer;
+#[cfg(feature = "toml")]
+pub use structured::diff_toml;
+#[cfg(feature = "yaml")]
+pub use structured::diff_yaml;
+#[cfg(feature = "table")]
+pub use table::TableDiff;
+pub use template::Template;
+#[cfg(feature = "derive")]
+pub use termdiff_derive::Theme;
+pub use themes::{
+    AccessibleTheme, ArrowsColorTheme, ArrowsTheme, MonochromeTheme, SignsColorTheme, SignsTheme,
+    SnapshotTheme, Theme,
+};
+#[cfg(feature = "tracing")]
+pub use trace::trace_diff;
+pub use unified::{parse_unified, ParseError};
+pub use verify::{verify, VerifyError};
+pub use whitespace::WhitespaceChanges;
+pub use word_frequency::WordFrequency;
 
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crossterm::style::Stylize;
+}
+
+mod algorithm;
+mod annotations;
+mod ansi;
+mod ascii;
+mod batch;
+mod bytes;
+mod cache;
+mod cancellation;
+mod change;
 mod cmd;
+mod color;
+mod config;
+mod diff_context;
+mod distance;
 mod draw_diff;
-mod themes;
+mod draw_error;
+mod encoding;
+#[cfg(feature = "bench-fixtures")]
+pub mod fixtures;
+mod framed;
+#[cfg(feature = "fs")]
+mod fs;
+mod fuzz;
+#[cfg(feature = "git")]
+mod git;
+mod granularity;
+mod header;
+mod hex;
+mod highlight;
+mod hunks;
+mod ignore_regions;
+mod incremental;
+mod inline;
+mod intern;
+mod line_endings;
+mod line_info;
+mod lines;
+mod lsp;
+mod maps;
+mod minimap;
+mod moves;
+mod newline;
+#[cfg(feature = "normalization")]
+mod normalization;
+mod ops;
+mod pairing;
+mod post_process;
+mod progress;
+mod ranges;
+mod redact;
+mod replace_order;
+mod result;
+mod scratch;
+mod sections;
+mod sentences;
+mod set;
+mod single_line;
+mod stat;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+mod structured;
+#[cfg(feature = "table")]
+mod table;
+mod template;
+#[cfg(feature = "testgen")]
+pub mod testgen;
+pub mod themes;
+#[cfg(feature = "tracing")]
+mod trace;
+mod unified;
+mod verify;
+mod whitespace;
+mod width;
+mod word_frequency;
 
 #[cfg(doctest)]
 mod test_readme {
@@ -0,0 +1,56 @@
+/// How a line ends
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff};
+/// let old = "a\r\nb\r\nc\r\n";
+/// let new = "a\nb\r\nc";
+/// let theme = ArrowsTheme::default();
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DrawDiff::new(old, new, &theme).with_line_ending_markers(true)
+///     ),
+///     "< left / > right\n a ␍␊→␊\n b\r\n c ␍␊→∅\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// A line feed, `\n`
+    Lf,
+    /// A carriage return and a line feed, `\r\n`
+    CrLf,
+    /// A carriage return on its own, `\r`
+    Cr,
+    /// The last line of text that doesn't end with a newline
+    None,
+}
+
+/// Split a line into its content and how it ends
+pub(crate) fn split_ending(line: &str) -> (&str, LineEnding) {
+    if let Some(content) = line.strip_suffix("\r\n") {
+        (content, LineEnding::CrLf)
+    } else if let Some(content) = line.strip_suffix('\n') {
+        (content, LineEnding::Lf)
+    } else if let Some(content) = line.strip_suffix('\r') {
+        (content, LineEnding::Cr)
+    } else {
+        (line, LineEnding::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_ending, LineEnding};
+
+    #[test]
+    fn endings_are_split_from_content() {
+        assert_eq!(split_ending("a\r\n"), ("a", LineEnding::CrLf));
+        assert_eq!(split_ending("a\n"), ("a", LineEnding::Lf));
+        assert_eq!(split_ending("a\r"), ("a", LineEnding::Cr));
+        assert_eq!(split_ending("a"), ("a", LineEnding::None));
+        assert_eq!(split_ending("\n\r"), ("\n", LineEnding::Cr));
+    }
+}
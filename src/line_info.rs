@@ -0,0 +1,124 @@
+use super::change::ChangeTag;
+
+/// Where a line is in the diff, for themes that style lines by position
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use termdiff::{DrawDiff, LineInfo, Theme};
+///
+/// /// Number the lines of the new text
+/// #[derive(Debug)]
+/// struct NumberedTheme {}
+/// impl Theme for NumberedTheme {
+///     fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+///         " ".into()
+///     }
+///
+///     fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+///         "-".into()
+///     }
+///
+///     fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+///         "+".into()
+///     }
+///
+///     fn header<'this>(&self) -> Cow<'this, str> {
+///         "".into()
+///     }
+///
+///     fn line_start<'this>(&self, line: &LineInfo) -> Cow<'this, str> {
+///         match line.new_line_number() {
+///             Some(number) => format!("{number:>2} ").into(),
+///             None => "   ".into(),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(
+///     format!("{}", DrawDiff::new("a\nb\nc\n", "a\nc\nd\n", &NumberedTheme {})),
+///     " 1  a\n   -b\n 2  c\n 3 +d\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineInfo {
+    tag: ChangeTag,
+    old_line_number: Option<usize>,
+    new_line_number: Option<usize>,
+    index: usize,
+    starts_block: bool,
+    missing_newline: bool,
+}
+
+impl LineInfo {
+    /// Describe where a line is in the diff
+    #[must_use]
+    pub fn new(
+        tag: ChangeTag,
+        old_line_number: Option<usize>,
+        new_line_number: Option<usize>,
+        index: usize,
+        starts_block: bool,
+    ) -> Self {
+        Self {
+            tag,
+            old_line_number,
+            new_line_number,
+            index,
+            starts_block,
+            missing_newline: false,
+        }
+    }
+
+    /// Set whether the line is the last line of a text that doesn't end with
+    /// a newline
+    #[must_use]
+    pub fn with_missing_newline(mut self, missing_newline: bool) -> Self {
+        self.missing_newline = missing_newline;
+        self
+    }
+
+    /// What happened to the line
+    #[must_use]
+    pub fn tag(&self) -> ChangeTag {
+        self.tag
+    }
+
+    /// The number of the line in the old text, starting from 1, if it is in
+    /// the old text
+    #[must_use]
+    pub fn old_line_number(&self) -> Option<usize> {
+        self.old_line_number
+    }
+
+    /// The number of the line in the new text, starting from 1, if it is in
+    /// the new text
+    #[must_use]
+    pub fn new_line_number(&self) -> Option<usize> {
+        self.new_line_number
+    }
+
+    /// How many lines of the diff were drawn before this one
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The line is the first of a run of lines with the same tag
+    #[must_use]
+    pub fn starts_block(&self) -> bool {
+        self.starts_block
+    }
+
+    /// The line is the last line of a text that doesn't end with a newline
+    ///
+    /// This is worked out from the texts being diffed, so a line that was
+    /// given a newline by [`NewlinePolicy::Ignore`](crate::NewlinePolicy) has
+    /// one.
+    #[must_use]
+    pub fn missing_newline(&self) -> bool {
+        self.missing_newline
+    }
+}
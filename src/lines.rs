@@ -0,0 +1,116 @@
+/// Split text into lines, keeping each line's ending
+///
+/// Lines end with `\n`, `\r\n` or a lone `\r`, which matches how similar
+/// splits lines. Nothing is allocated.
+pub(crate) fn split_lines(text: &str) -> Lines<'_> {
+    Lines { rest: text }
+}
+
+/// Where each line starts in bytes, followed by where the last one ends
+pub(crate) fn line_starts(lines: &[&str]) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(lines.iter().scan(0, |offset, line| {
+            *offset += line.len();
+            Some(*offset)
+        }))
+        .collect()
+}
+
+/// An iterator over the lines of some text, see [`split_lines`]
+#[derive(Debug, Clone)]
+pub(crate) struct Lines<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let bytes = self.rest.as_bytes();
+        let end = bytes
+            .iter()
+            .position(|byte| *byte == b'\n' || *byte == b'\r')
+            .map_or(bytes.len(), |index| {
+                if bytes[index] == b'\r' && bytes.get(index + 1) == Some(&b'\n') {
+                    index + 2
+                } else {
+                    index + 1
+                }
+            });
+
+        let (line, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(line)
+    }
+}
+
+/// Text to diff, either whole or already split into lines
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Input<'a> {
+    Text(&'a str),
+    Lines(&'a [&'a str]),
+}
+
+impl<'a> Input<'a> {
+    /// The lines of the input, splitting it if it isn't already split
+    pub(crate) fn lines(self) -> InputLines<'a> {
+        match self {
+            Input::Text(text) => InputLines::Text(split_lines(text)),
+            Input::Lines(lines) => InputLines::Lines(lines.iter()),
+        }
+    }
+}
+
+/// An iterator over the lines of an [`Input`]
+#[derive(Debug, Clone)]
+pub(crate) enum InputLines<'a> {
+    Text(Lines<'a>),
+    Lines(std::slice::Iter<'a, &'a str>),
+}
+
+impl<'a> Iterator for InputLines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            InputLines::Text(lines) => lines.next(),
+            InputLines::Lines(lines) => lines.next().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use similar::{DiffableStr, TextDiff};
+
+    use super::{split_lines, Input};
+
+    #[test]
+    fn splits_like_similar() {
+        for text in ["", "a", "a\n", "a\nb", "a\r\nb\rc\n\n", "\r\r\n\n", "é\nü"] {
+            assert_eq!(
+                split_lines(text).collect::<Vec<_>>(),
+                text.tokenize_lines(),
+                "Splitting {text:?}"
+            );
+            assert_eq!(
+                split_lines(text).collect::<Vec<_>>(),
+                TextDiff::from_lines(text, text).old_slices(),
+                "Splitting {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn text_and_lines_inputs_agree() {
+        let lines = ["a\n", "b\r\n", "c"];
+
+        assert!(Input::Text("a\nb\r\nc")
+            .lines()
+            .eq(Input::Lines(&lines).lines()));
+    }
+}
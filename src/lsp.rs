@@ -0,0 +1,248 @@
+use std::ops::Range;
+
+use super::{
+    change::ChangeTag,
+    inline::common_affixes,
+    lines::{line_starts, split_lines},
+    result::DiffResult,
+};
+
+/// A place in a text, as a language server would give it
+///
+/// Lines start from 0, and the character is counted in UTF-16 code units
+/// from the start of the line, as in the Language Server Protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    line: usize,
+    character: usize,
+}
+
+impl Position {
+    /// A place in a text
+    #[must_use]
+    pub fn new(line: usize, character: usize) -> Self {
+        Self { line, character }
+    }
+
+    /// The line, starting from 0
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The UTF-16 code unit within the line, starting from 0
+    #[must_use]
+    pub fn character(&self) -> usize {
+        self.character
+    }
+}
+
+/// A change to make to the old text, replacing a range of it with new text
+///
+/// With the `serde` feature this serializes to the same shape as an LSP
+/// `TextEdit`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct TextEdit {
+    range: Range<Position>,
+    new_text: String,
+}
+
+impl TextEdit {
+    /// Replace a range of the old text
+    #[must_use]
+    pub fn new(range: Range<Position>, new_text: String) -> Self {
+        Self { range, new_text }
+    }
+
+    /// The part of the old text to replace
+    #[must_use]
+    pub fn range(&self) -> Range<Position> {
+        self.range.clone()
+    }
+
+    /// The text to replace it with
+    #[must_use]
+    pub fn new_text(&self) -> &str {
+        &self.new_text
+    }
+}
+
+/// The edits that turn the old text into the new text, for an editor or
+/// language server to apply
+///
+/// There is an edit for each group of changed lines, narrowed down to the
+/// characters that changed. All the ranges are in the old text and don't
+/// overlap, so the edits can be applied together as LSP clients do.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{text_edits, Position, TextEdit};
+///
+/// assert_eq!(
+///     text_edits("let a = 1;\nlet b = 2;\n", "let a = 1;\nlet b = 3;\n"),
+///     vec![TextEdit::new(
+///         Position::new(1, 8)..Position::new(1, 9),
+///         "3".to_string()
+///     )]
+/// );
+/// ```
+#[must_use]
+pub fn text_edits(old: &str, new: &str) -> Vec<TextEdit> {
+    let old_lines: Vec<&str> = split_lines(old).collect();
+    let old_starts = line_starts(&old_lines);
+    let result = DiffResult::new(old, new).with_byte_ranges();
+
+    result
+        .ops()
+        .split(|op| op.tag() == ChangeTag::Equal)
+        .filter_map(|hunk| {
+            let (first, last) = (hunk.first()?, hunk.last()?);
+            let old_bytes = first.old_bytes()?.start..last.old_bytes()?.end;
+            let new_bytes = first.new_bytes()?.start..last.new_bytes()?.end;
+            let (mut prefix, mut suffix) =
+                common_affixes(&old[old_bytes.clone()], &new[new_bytes.clone()]);
+            // Clients clamp a position inside a line ending to the end of the
+            // line's text, so never start or end an edit inside a `\r\n`
+            if inside_line_ending(old, old_bytes.start + prefix) {
+                prefix -= 1;
+            }
+            if inside_line_ending(old, old_bytes.end - suffix) {
+                suffix -= 1;
+            }
+
+            let start = position(old, &old_starts, old_bytes.start + prefix);
+            let end = position(old, &old_starts, old_bytes.end - suffix);
+            let new_text = &new[new_bytes.start + prefix..new_bytes.end - suffix];
+
+            Some(TextEdit::new(start..end, new_text.to_string()))
+        })
+        .collect()
+}
+
+/// The position of a byte offset in a text
+///
+/// The end of a text without a final line ending is on its last line, not
+/// the start of one after it, and an offset inside a `\r\n` is the start of
+/// the next line.
+fn position(text: &str, starts: &[usize], offset: usize) -> Position {
+    let mut line = starts.partition_point(|start| *start <= offset) - 1;
+    if inside_line_ending(text, offset) {
+        return Position::new(line + 1, 0);
+    }
+    if line > 0 && line == starts.len() - 1 && !text.ends_with(['\n', '\r']) {
+        line -= 1;
+    }
+    let character = text[starts[line]..offset].encode_utf16().count();
+
+    Position::new(line, character)
+}
+
+/// The offset is between the `\r` and `\n` of a line ending
+fn inside_line_ending(text: &str, offset: usize) -> bool {
+    text[..offset].ends_with('\r') && text[offset..].starts_with('\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{text_edits, Position, TextEdit};
+    use crate::lines::split_lines;
+
+    /// Apply edits the way an LSP client does, clamping each character to
+    /// the length of its line without the line ending
+    fn apply(old: &str, edits: &[TextEdit]) -> String {
+        let lines: Vec<&str> = split_lines(old).collect();
+        let offset = |position: Position| {
+            let Some(line) = lines.get(position.line()) else {
+                return old.len();
+            };
+            let start: usize = lines[..position.line()].iter().map(|line| line.len()).sum();
+            let content = line.trim_end_matches(['\r', '\n']);
+            let mut units = 0;
+            let within = content
+                .char_indices()
+                .find(|(_, character)| {
+                    units += character.len_utf16();
+                    units > position.character()
+                })
+                .map_or(content.len(), |(index, _)| index);
+            start + within
+        };
+
+        let mut text = old.to_string();
+        for edit in edits.iter().rev() {
+            let range = edit.range();
+            text.replace_range(offset(range.start)..offset(range.end), edit.new_text());
+        }
+        text
+    }
+
+    #[test]
+    fn applying_the_edits_gives_the_new_text() {
+        let cases = [
+            ("\r\na", "\na"),
+            ("a  b\r\n", "a\n"),
+            ("a\n", "a\r\n"),
+            ("a\r\nb\r\n", "a\rb\n"),
+            ("a\rb", "a\nb"),
+            ("a\r", "a\r\n"),
+            ("x\r\n😀y\r\n", "x\r\n😀z\n"),
+            ("a\nb", "a\r\nb\r"),
+            ("", "a\r\n"),
+            ("a\r\n", ""),
+        ];
+
+        for (old, new) in cases {
+            assert_eq!(apply(old, &text_edits(old, new)), new, "{old:?} to {new:?}");
+        }
+    }
+
+    #[test]
+    fn whole_lines_and_wide_characters() {
+        assert_eq!(
+            text_edits("a\n😀b\nc\n", "a\n😀x\nc\nd\n"),
+            vec![
+                TextEdit::new(Position::new(1, 2)..Position::new(1, 3), "x".to_string()),
+                TextEdit::new(Position::new(3, 0)..Position::new(3, 0), "d\n".to_string()),
+            ]
+        );
+        assert_eq!(
+            text_edits("a\nb\n", "b\n"),
+            vec![TextEdit::new(
+                Position::new(0, 0)..Position::new(1, 0),
+                String::new()
+            )]
+        );
+    }
+
+    #[test]
+    fn the_end_of_text_without_a_final_newline_is_on_its_last_line() {
+        assert_eq!(
+            text_edits("x\na", "x\nab"),
+            vec![TextEdit::new(
+                Position::new(1, 1)..Position::new(1, 1),
+                "b".to_string()
+            )]
+        );
+        assert_eq!(
+            text_edits("x\n😀", "x\n😀!"),
+            vec![TextEdit::new(
+                Position::new(1, 2)..Position::new(1, 2),
+                "!".to_string()
+            )]
+        );
+        assert_eq!(
+            text_edits("x\na", "x\na\n"),
+            vec![TextEdit::new(
+                Position::new(1, 1)..Position::new(1, 1),
+                "\n".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::{
+    change::ChangeTag,
+    themes::{prefix_column, Theme},
+    width::{display_width, pad},
+};
+
+/// Draw the differences between two sets of keys and values, like
+/// environments or config files
+///
+/// Keys are drawn in order, with their values lined up. Keys that were
+/// removed or added are drawn as deleted or inserted lines, and keys whose
+/// value changed are drawn as a deleted line with the old value followed by
+/// an inserted line with the new one.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use termdiff::{diff_maps, ArrowsTheme};
+/// let old: BTreeMap<String, String> = [("HOST", "localhost"), ("PORT", "80"), ("USER", "me")]
+///     .iter()
+///     .map(|(key, value)| (key.to_string(), value.to_string()))
+///     .collect();
+/// let new: BTreeMap<String, String> = [("HOST", "localhost"), ("PORT", "8080"), ("TIMEOUT", "5")]
+///     .iter()
+///     .map(|(key, value)| (key.to_string(), value.to_string()))
+///     .collect();
+/// let theme = ArrowsTheme::default();
+///
+/// assert_eq!(
+///     diff_maps(&old, &new, &theme),
+///     "< left / > right
+///  HOST    = localhost
+/// <PORT    = 80
+/// >PORT    = 8080
+/// >TIMEOUT = 5
+/// <USER    = me
+/// "
+/// );
+/// ```
+#[must_use]
+pub fn diff_maps(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+    theme: &dyn Theme,
+) -> String {
+    let width = old
+        .keys()
+        .chain(new.keys())
+        .map(|key| display_width(key))
+        .max()
+        .unwrap_or_default();
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let prefix_width = theme.prefix_width();
+    let mut buffer = theme.header().into_owned();
+    for key in keys {
+        match (old.get(key), new.get(key)) {
+            (Some(old_value), Some(new_value)) if old_value == new_value => {
+                write_entry(
+                    &mut buffer,
+                    theme,
+                    ChangeTag::Equal,
+                    key,
+                    old_value,
+                    width,
+                    prefix_width,
+                );
+            }
+            (old_value, new_value) => {
+                if let Some(old_value) = old_value {
+                    write_entry(
+                        &mut buffer,
+                        theme,
+                        ChangeTag::Delete,
+                        key,
+                        old_value,
+                        width,
+                        prefix_width,
+                    );
+                }
+                if let Some(new_value) = new_value {
+                    write_entry(
+                        &mut buffer,
+                        theme,
+                        ChangeTag::Insert,
+                        key,
+                        new_value,
+                        width,
+                        prefix_width,
+                    );
+                }
+            }
+        }
+    }
+
+    buffer
+}
+
+fn write_entry(
+    buffer: &mut String,
+    theme: &dyn Theme,
+    tag: ChangeTag,
+    key: &str,
+    value: &str,
+    width: usize,
+    prefix_width: usize,
+) {
+    let entry = format!("{} = {value}", pad(key, width));
+    let (prefix, content) = match tag {
+        ChangeTag::Delete | ChangeTag::MovedFrom => {
+            (theme.delete_prefix(), theme.delete_content(&entry))
+        }
+        ChangeTag::Insert | ChangeTag::MovedTo => {
+            (theme.insert_prefix(), theme.insert_line(&entry))
+        }
+        ChangeTag::Equal => (theme.equal_prefix(), theme.equal_content(&entry)),
+    };
+
+    let prefix = prefix_column(theme, prefix, prefix_width);
+    let _ = write!(buffer, "{prefix}{content}{}", theme.line_end());
+}
@@ -0,0 +1,117 @@
+use std::fmt::{Display, Formatter};
+
+use super::{change::ChangeTag, result::DiffResult, themes::Theme};
+
+/// Draws an overview of where a diff changes things, with one character for
+/// every few lines, like the minimap in an editor
+///
+/// Each character is shaded by how many of its lines changed, from `·` for
+/// none through `░`, `▒` and `▓` to `█` for all of them. It is styled as
+/// inserted if more lines were added than removed, as deleted if more were
+/// removed, and as unchanged otherwise. Lines are counted as they are
+/// drawn, so a changed line counts once for its old version and once for
+/// its new.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DiffResult, Minimap};
+/// let theme = ArrowsTheme::default();
+/// let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+/// let new = "a\nb\nc\nd\ne\nf\ng\nH\n";
+/// let result = DiffResult::new(old, new);
+///
+/// assert_eq!(
+///     format!("{}", Minimap::new(&result, &theme).with_lines_per_cell(4)),
+///     "·░█\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Minimap<'a> {
+    result: &'a DiffResult<'a>,
+    theme: &'a dyn Theme,
+    lines_per_cell: usize,
+}
+
+impl<'a> Minimap<'a> {
+    /// Make a minimap of a diff, with a character for every 10 lines
+    #[must_use]
+    pub fn new(result: &'a DiffResult<'a>, theme: &'a dyn Theme) -> Self {
+        Self {
+            result,
+            theme,
+            lines_per_cell: 10,
+        }
+    }
+
+    /// How many lines each character stands for, and always at least one
+    #[must_use]
+    pub fn with_lines_per_cell(mut self, lines_per_cell: usize) -> Self {
+        self.lines_per_cell = lines_per_cell.max(1);
+        self
+    }
+
+    /// What happened to each line, in the order they are drawn
+    fn tags(&self) -> Vec<ChangeTag> {
+        self.result
+            .ops()
+            .iter()
+            .flat_map(|op| {
+                let length = match op.tag() {
+                    ChangeTag::Insert | ChangeTag::MovedTo => op.new_range().len(),
+                    _ => op.old_range().len(),
+                };
+                std::iter::repeat_n(op.tag(), length)
+            })
+            .collect()
+    }
+}
+
+impl Display for Minimap<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for cell in self.tags().chunks(self.lines_per_cell) {
+            let count = |tags: &[ChangeTag]| cell.iter().filter(|tag| tags.contains(tag)).count();
+            let inserted = count(&[ChangeTag::Insert, ChangeTag::MovedTo]);
+            let deleted = count(&[ChangeTag::Delete, ChangeTag::MovedFrom]);
+            let shade = shade(inserted + deleted, cell.len());
+
+            let styled = match inserted.cmp(&deleted) {
+                std::cmp::Ordering::Greater => self.theme.insert_line(shade),
+                std::cmp::Ordering::Less => self.theme.delete_content(shade),
+                std::cmp::Ordering::Equal => self.theme.equal_content(shade),
+            };
+            write!(f, "{styled}")?;
+        }
+
+        writeln!(f)
+    }
+}
+
+/// The character for a share of changed lines
+fn shade(changed: usize, total: usize) -> &'static str {
+    if changed == 0 {
+        "·"
+    } else if changed >= total {
+        "█"
+    } else {
+        match (changed * 3).div_ceil(total) {
+            1 => "░",
+            2 => "▒",
+            _ => "▓",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shade;
+
+    #[test]
+    fn only_cells_with_every_line_changed_are_full() {
+        assert_eq!(shade(0, 10), "·");
+        assert_eq!(shade(1, 10), "░");
+        assert_eq!(shade(5, 10), "▒");
+        assert_eq!(shade(9, 10), "▓");
+        assert_eq!(shade(10, 10), "█");
+    }
+}
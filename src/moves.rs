@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use super::{
+    change::ChangeTag,
+    ops::{DiffOp, OpTransform},
+};
+
+/// Tag blocks of deleted lines that are inserted elsewhere as moved
+///
+/// Blocks need to be at least `min_lines` long, and can't start with a blank
+/// line, as those are too common to be a useful signal.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MoveDetection {
+    min_lines: usize,
+}
+
+impl MoveDetection {
+    pub(crate) fn new(min_lines: usize) -> Self {
+        Self {
+            min_lines: min_lines.max(1),
+        }
+    }
+}
+
+impl OpTransform for MoveDetection {
+    fn transform(&self, old: &[&str], new: &[&str], ops: Vec<DiffOp>) -> Vec<DiffOp> {
+        let lines: Vec<DiffOp> = ops.iter().flat_map(DiffOp::split_lines).collect();
+        let tags: Vec<ChangeTag> = lines.iter().map(DiffOp::tag).collect();
+        let contents: Vec<&str> = lines
+            .iter()
+            .map(|line| {
+                let content = match line.tag() {
+                    ChangeTag::Insert | ChangeTag::MovedTo => new.get(line.new_range().start),
+                    _ => old.get(line.old_range().start),
+                };
+                let content = content.copied().unwrap_or_default();
+                content.trim_end_matches(['\r', '\n'])
+            })
+            .collect();
+        let moved = find_moves(&tags, &contents, self.min_lines);
+
+        lines
+            .into_iter()
+            .zip(moved)
+            .map(|(line, moved)| match (line.tag(), moved) {
+                (ChangeTag::Delete, true) => line.with_tag(ChangeTag::MovedFrom),
+                (ChangeTag::Insert, true) => line.with_tag(ChangeTag::MovedTo),
+                _ => line,
+            })
+            .collect()
+    }
+}
+
+fn find_moves(tags: &[ChangeTag], contents: &[&str], min_lines: usize) -> Vec<bool> {
+    let mut inserted: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, tag) in tags.iter().enumerate() {
+        if *tag == ChangeTag::Insert {
+            inserted.entry(contents[index]).or_default().push(index);
+        }
+    }
+
+    let mut moved = vec![false; tags.len()];
+    let mut index = 0;
+
+    while index < tags.len() {
+        if tags[index] != ChangeTag::Delete || contents[index].trim().is_empty() {
+            index += 1;
+            continue;
+        }
+
+        let longest = inserted
+            .get(contents[index])
+            .into_iter()
+            .flatten()
+            .filter(|candidate| !moved[**candidate])
+            .map(|candidate| {
+                let length = (0..tags.len())
+                    .take_while(|offset| {
+                        let from = index + offset;
+                        let to = candidate + offset;
+                        from < tags.len()
+                            && to < tags.len()
+                            && tags[from] == ChangeTag::Delete
+                            && tags[to] == ChangeTag::Insert
+                            && !moved[to]
+                            && contents[from] == contents[to]
+                    })
+                    .count();
+                (*candidate, length)
+            })
+            .fold(
+                None,
+                |longest: Option<(usize, usize)>, (candidate, length)| match longest {
+                    Some((_, longest_length)) if longest_length >= length => longest,
+                    _ => Some((candidate, length)),
+                },
+            );
+
+        match longest {
+            Some((candidate, length)) if length >= min_lines => {
+                for offset in 0..length {
+                    moved[index + offset] = true;
+                    moved[candidate + offset] = true;
+                }
+                index += length;
+            }
+            _ => index += 1,
+        }
+    }
+
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MoveDetection;
+    use crate::{ChangeTag, DiffOp, MergeOps, OpTransform};
+
+    fn detect(old: &[&str], new: &[&str], ops: Vec<DiffOp>, min_lines: usize) -> Vec<DiffOp> {
+        let ops = MoveDetection::new(min_lines).transform(old, new, ops);
+        MergeOps::default().transform(old, new, ops)
+    }
+
+    #[test]
+    fn blocks_moved_elsewhere_are_tagged() {
+        let old = ["fn a() {}\n", "fn b() {}\n", "fn c() {}\n"];
+        let new = ["fn c() {}\n", "fn a() {}\n", "fn b() {}\n", "fn d() {}\n"];
+        let ops = vec![
+            DiffOp::new(ChangeTag::Delete, 0..2, 0..0),
+            DiffOp::new(ChangeTag::Equal, 2..3, 0..1),
+            DiffOp::new(ChangeTag::Insert, 3..3, 1..4),
+        ];
+
+        assert_eq!(
+            detect(&old, &new, ops, 2),
+            vec![
+                DiffOp::new(ChangeTag::MovedFrom, 0..2, 0..0),
+                DiffOp::new(ChangeTag::Equal, 2..3, 0..1),
+                DiffOp::new(ChangeTag::MovedTo, 3..3, 1..3),
+                DiffOp::new(ChangeTag::Insert, 3..3, 3..4),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_blocks_and_blank_lines_are_not_moves() {
+        let old = ["\n", "fn a() {}\n", "fn c() {}\n"];
+        let new = ["fn c() {}\n", "\n", "fn a() {}"];
+        let ops = vec![
+            DiffOp::new(ChangeTag::Delete, 0..2, 0..0),
+            DiffOp::new(ChangeTag::Equal, 2..3, 0..1),
+            DiffOp::new(ChangeTag::Insert, 3..3, 1..3),
+        ];
+
+        assert_eq!(detect(&old, &new, ops.clone(), 2), ops);
+    }
+}
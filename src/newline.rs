@@ -0,0 +1,32 @@
+/// What to do when one text ends with a newline and the other doesn't
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff, NewlinePolicy};
+/// let theme = ArrowsTheme::default();
+/// let draw = |policy| {
+///     DrawDiff::new("a\nb\n", "a\nb", &theme)
+///         .with_newline_policy(policy)
+///         .to_string()
+/// };
+///
+/// assert_eq!(draw(NewlinePolicy::Mark), "< left / > right\n a\n<b␊\n>b\n");
+/// assert_eq!(draw(NewlinePolicy::Ignore), "< left / > right\n a\n b\n");
+/// assert_eq!(
+///     draw(NewlinePolicy::Explicit),
+///     "< left / > right\n a\n<b\n>b\n\\ No newline at end of file\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NewlinePolicy {
+    /// Mark the newline that only one side has with the theme's
+    /// [`trailing_lf_marker`](crate::Theme::trailing_lf_marker)
+    #[default]
+    Mark,
+    /// Compare the texts as if both ended with a newline
+    Ignore,
+    /// Write the theme's [`no_newline`](crate::Theme::no_newline) line after
+    /// the last line of a text without a newline, like GNU diff does
+    Explicit,
+}
@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+
+use unicode_normalization::{is_nfc_quick, is_nfd_quick, IsNormalized, UnicodeNormalization};
+
+/// A Unicode normalization form to compare lines under, so text that is
+/// canonically equivalent but encoded differently isn't shown as changed
+///
+/// The original text is still what gets drawn, and lines that only differ
+/// in how they are encoded are marked with the theme's
+/// [`normalization_change`](crate::Theme::normalization_change). Needs the
+/// `normalization` feature.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff, Normalization};
+/// let theme = ArrowsTheme::default();
+///
+/// assert_eq!(
+///     DrawDiff::new("caf\u{e9}\n", "cafe\u{301}\n", &theme)
+///         .with_normalization(Normalization::Nfc)
+///         .to_string(),
+///     "< left / > right\n caf\u{e9} (normalized differently)\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Normalization {
+    /// Canonical composition, where `e` and a combining acute accent become
+    /// `é`
+    Nfc,
+    /// Canonical decomposition, where `é` becomes `e` and a combining acute
+    /// accent
+    Nfd,
+}
+
+impl Normalization {
+    /// The text in this form, only allocating if it isn't already
+    pub(crate) fn normalize(self, text: &str) -> Cow<'_, str> {
+        match self {
+            Self::Nfc if is_nfc_quick(text.chars()) == IsNormalized::Yes => text.into(),
+            Self::Nfd if is_nfd_quick(text.chars()) == IsNormalized::Yes => text.into(),
+            Self::Nfc => text.nfc().collect::<String>().into(),
+            Self::Nfd => text.nfd().collect::<String>().into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Normalization;
+
+    #[test]
+    fn equivalent_text_normalizes_the_same() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+
+        for form in [Normalization::Nfc, Normalization::Nfd] {
+            assert_eq!(form.normalize(composed), form.normalize(decomposed));
+        }
+        assert_eq!(Normalization::Nfc.normalize(decomposed), composed);
+        assert_eq!(Normalization::Nfd.normalize(composed), decomposed);
+    }
+}
@@ -0,0 +1,451 @@
+use std::{fmt::Debug, ops::Range};
+
+use super::{change::ChangeTag, lines::line_starts};
+
+/// A run of lines that all had the same thing happen to them
+///
+/// The ranges are line indexes into the old and new text. Ops that only
+/// relate to one side, like deletes, have an empty range on the other side
+/// that marks where they happened.
+///
+/// Ops can also carry where their lines are in the texts in bytes, see
+/// [`DiffOp::with_byte_ranges`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffOp {
+    tag: ChangeTag,
+    old_range: Range<usize>,
+    new_range: Range<usize>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    old_bytes: Option<Range<usize>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    new_bytes: Option<Range<usize>>,
+}
+
+impl DiffOp {
+    /// Make a new op
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ChangeTag, DiffOp};
+    /// let op = DiffOp::new(ChangeTag::Delete, 0..2, 0..0);
+    ///
+    /// assert_eq!(op.tag(), ChangeTag::Delete);
+    /// assert_eq!(op.old_range(), 0..2);
+    /// assert_eq!(op.new_range(), 0..0);
+    /// ```
+    #[must_use]
+    pub fn new(tag: ChangeTag, old_range: Range<usize>, new_range: Range<usize>) -> Self {
+        Self {
+            tag,
+            old_range,
+            new_range,
+            old_bytes: None,
+            new_bytes: None,
+        }
+    }
+
+    /// Lines that are the same in the old and new text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ChangeTag, DiffOp};
+    ///
+    /// assert_eq!(
+    ///     DiffOp::equal(0..2, 1..3),
+    ///     DiffOp::new(ChangeTag::Equal, 0..2, 1..3)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn equal(old_range: Range<usize>, new_range: Range<usize>) -> Self {
+        Self::new(ChangeTag::Equal, old_range, new_range)
+    }
+
+    /// Lines removed from the old text, at a line index in the new text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ChangeTag, DiffOp};
+    ///
+    /// assert_eq!(
+    ///     DiffOp::delete(0..2, 1),
+    ///     DiffOp::new(ChangeTag::Delete, 0..2, 1..1)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn delete(old_range: Range<usize>, new_index: usize) -> Self {
+        Self::new(ChangeTag::Delete, old_range, new_index..new_index)
+    }
+
+    /// Lines added to the new text, at a line index in the old text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ChangeTag, DiffOp};
+    ///
+    /// assert_eq!(
+    ///     DiffOp::insert(2, 1..3),
+    ///     DiffOp::new(ChangeTag::Insert, 2..2, 1..3)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn insert(old_index: usize, new_range: Range<usize>) -> Self {
+        Self::new(ChangeTag::Insert, old_index..old_index, new_range)
+    }
+
+    /// What happened to the lines
+    #[must_use]
+    pub fn tag(&self) -> ChangeTag {
+        self.tag
+    }
+
+    /// The lines in the old text this op covers
+    #[must_use]
+    pub fn old_range(&self) -> Range<usize> {
+        self.old_range.clone()
+    }
+
+    /// The lines in the new text this op covers
+    #[must_use]
+    pub fn new_range(&self) -> Range<usize> {
+        self.new_range.clone()
+    }
+
+    /// Where the op's lines are in the old and new text, in bytes, from the
+    /// start of the first line to the end of the last, including its line
+    /// ending
+    ///
+    /// Ops from [`DiffResult::with_byte_ranges`] have these already, so
+    /// changes can be found in the text without scanning it again.
+    ///
+    /// [`DiffResult::with_byte_ranges`]: crate::DiffResult::with_byte_ranges
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::DiffOp;
+    /// let old = "a\nb\n";
+    /// let op = DiffOp::delete(1..2, 1).with_byte_ranges(2..4, 2..2);
+    ///
+    /// assert_eq!(op.old_bytes().map(|bytes| &old[bytes]), Some("b\n"));
+    /// assert_eq!(op.new_bytes(), Some(2..2));
+    /// ```
+    #[must_use]
+    pub fn with_byte_ranges(mut self, old_bytes: Range<usize>, new_bytes: Range<usize>) -> Self {
+        self.old_bytes = Some(old_bytes);
+        self.new_bytes = Some(new_bytes);
+        self
+    }
+
+    /// Where the op's lines are in the old text in bytes, if known
+    #[must_use]
+    pub fn old_bytes(&self) -> Option<Range<usize>> {
+        self.old_bytes.clone()
+    }
+
+    /// Where the op's lines are in the new text in bytes, if known
+    #[must_use]
+    pub fn new_bytes(&self) -> Option<Range<usize>> {
+        self.new_bytes.clone()
+    }
+
+    /// The same lines, with a different thing happening to them
+    #[must_use]
+    pub fn with_tag(self, tag: ChangeTag) -> Self {
+        Self { tag, ..self }
+    }
+
+    /// Split the op into ops of one line each
+    pub(crate) fn split_lines(&self) -> impl Iterator<Item = Self> + '_ {
+        let length = self.old_range.len().max(self.new_range.len());
+
+        (0..length).map(move |offset| {
+            let old_range = if self.old_range.is_empty() {
+                self.old_range.clone()
+            } else {
+                self.old_range.start + offset..self.old_range.start + offset + 1
+            };
+            let new_range = if self.new_range.is_empty() {
+                self.new_range.clone()
+            } else {
+                self.new_range.start + offset..self.new_range.start + offset + 1
+            };
+
+            Self::new(self.tag, old_range, new_range)
+        })
+    }
+}
+
+/// Convert the ops from similar, splitting replaces into a delete followed by
+/// an insert
+///
+/// The ranges are worked out from the lengths of the ops rather than taken
+/// as they are, as similar sometimes gives deletes and inserts a position on
+/// the other side that isn't where they happened.
+pub(crate) fn from_similar(ops: &[similar::DiffOp]) -> Vec<DiffOp> {
+    let mut converted = Vec::with_capacity(ops.len());
+    let (mut old_at, mut new_at) = (0, 0);
+
+    for op in ops {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        let old_range = old_at..old_at + old_range.len();
+        let new_range = new_at..new_at + new_range.len();
+
+        match tag {
+            similar::DiffTag::Equal => {
+                converted.push(DiffOp::new(
+                    ChangeTag::Equal,
+                    old_range.clone(),
+                    new_range.clone(),
+                ));
+            }
+            similar::DiffTag::Delete => {
+                converted.push(DiffOp::new(
+                    ChangeTag::Delete,
+                    old_range.clone(),
+                    new_range.clone(),
+                ));
+            }
+            similar::DiffTag::Insert => {
+                converted.push(DiffOp::new(
+                    ChangeTag::Insert,
+                    old_range.clone(),
+                    new_range.clone(),
+                ));
+            }
+            similar::DiffTag::Replace => {
+                converted.push(DiffOp::new(
+                    ChangeTag::Delete,
+                    old_range.clone(),
+                    new_range.start..new_range.start,
+                ));
+                converted.push(DiffOp::new(
+                    ChangeTag::Insert,
+                    old_range.end..old_range.end,
+                    new_range.clone(),
+                ));
+            }
+        }
+
+        old_at = old_range.end;
+        new_at = new_range.end;
+    }
+
+    deletes_first(converted)
+}
+
+/// Move the deletes in each run of deletes and inserts before the inserts,
+/// so a replaced block is drawn the same way whichever algorithm found it
+pub(crate) fn deletes_first(ops: Vec<DiffOp>) -> Vec<DiffOp> {
+    let mut ordered = Vec::with_capacity(ops.len());
+    let mut inserts: Vec<DiffOp> = vec![];
+
+    for op in ops {
+        match (op.tag, inserts.first()) {
+            (ChangeTag::Insert, _) => inserts.push(op),
+            (ChangeTag::Delete, Some(first)) => {
+                let new_index = first.new_range.start;
+                for insert in &mut inserts {
+                    insert.old_range = op.old_range.end..op.old_range.end;
+                }
+                ordered.push(DiffOp::new(
+                    ChangeTag::Delete,
+                    op.old_range,
+                    new_index..new_index,
+                ));
+            }
+            _ => {
+                ordered.append(&mut inserts);
+                ordered.push(op);
+            }
+        }
+    }
+
+    ordered.append(&mut inserts);
+    ordered
+}
+
+/// Give each op where its lines are in the texts in bytes
+///
+/// Ops with lines past the end of the texts are left without byte ranges.
+pub(crate) fn with_byte_ranges(ops: Vec<DiffOp>, old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let old_starts = line_starts(old);
+    let new_starts = line_starts(new);
+
+    ops.into_iter()
+        .map(|op| {
+            match (
+                byte_range(&old_starts, &op.old_range),
+                byte_range(&new_starts, &op.new_range),
+            ) {
+                (Some(old_bytes), Some(new_bytes)) => op.with_byte_ranges(old_bytes, new_bytes),
+                _ => op,
+            }
+        })
+        .collect()
+}
+
+/// Where a range of lines is in bytes, given where each line starts
+fn byte_range(starts: &[usize], lines: &Range<usize>) -> Option<Range<usize>> {
+    if lines.start > lines.end {
+        return None;
+    }
+
+    Some(*starts.get(lines.start)?..*starts.get(lines.end)?)
+}
+
+/// A pass over the ops of a diff, run after the diff algorithm and before
+/// the diff is rendered
+///
+/// Passes can merge, drop, split or retag ops. The old and new text are given
+/// split into lines, with their newlines, so passes can look at the content.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, ChangeTag, DiffOp, DrawDiff, OpTransform};
+///
+/// /// Don't show any lines that have been removed
+/// #[derive(Debug)]
+/// struct DropDeletes {}
+/// impl OpTransform for DropDeletes {
+///     fn transform(&self, _old: &[&str], _new: &[&str], ops: Vec<DiffOp>) -> Vec<DiffOp> {
+///         ops.into_iter()
+///             .filter(|op| op.tag() != ChangeTag::Delete)
+///             .collect()
+///     }
+/// }
+///
+/// let theme = ArrowsTheme::default();
+/// let transform = DropDeletes {};
+/// let actual = format!(
+///     "{}",
+///     DrawDiff::new("a\nb\n", "a\nc\n", &theme).with_transform(&transform)
+/// );
+///
+/// assert_eq!(actual, "< left / > right\n a\n>c\n");
+/// ```
+pub trait OpTransform: Debug {
+    /// Transform the ops of a diff
+    fn transform(&self, old: &[&str], new: &[&str], ops: Vec<DiffOp>) -> Vec<DiffOp>;
+}
+
+/// Merge adjacent ops that have the same tag and cover consecutive lines
+///
+/// This always runs after any other passes, so the renderer sees the largest
+/// possible blocks of changes.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ChangeTag, DiffOp, MergeOps, OpTransform};
+/// let ops = vec![
+///     DiffOp::new(ChangeTag::Delete, 0..1, 0..0),
+///     DiffOp::new(ChangeTag::Delete, 1..3, 0..0),
+///     DiffOp::new(ChangeTag::Insert, 3..3, 0..1),
+/// ];
+///
+/// assert_eq!(
+///     MergeOps::default().transform(&[], &[], ops),
+///     vec![
+///         DiffOp::new(ChangeTag::Delete, 0..3, 0..0),
+///         DiffOp::new(ChangeTag::Insert, 3..3, 0..1),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MergeOps {}
+
+impl OpTransform for MergeOps {
+    fn transform(&self, _old: &[&str], _new: &[&str], ops: Vec<DiffOp>) -> Vec<DiffOp> {
+        let mut merged: Vec<DiffOp> = Vec::with_capacity(ops.len());
+        merge_into(ops, &mut merged);
+        merged
+    }
+}
+
+/// Merge operations as [`MergeOps`] does, onto the end of `merged`
+pub(crate) fn merge_into(ops: impl IntoIterator<Item = DiffOp>, merged: &mut Vec<DiffOp>) {
+    for op in ops {
+        match merged.last_mut() {
+            Some(last)
+                if last.tag == op.tag
+                    && last.old_range.end == op.old_range.start
+                    && last.new_range.end == op.new_range.start =>
+            {
+                last.old_range.end = op.old_range.end;
+                last.new_range.end = op.new_range.end;
+            }
+            _ => merged.push(op),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use similar::{capture_diff_slices, Algorithm};
+
+    use super::{deletes_first, from_similar, with_byte_ranges, DiffOp};
+    use crate::ChangeTag;
+
+    #[test]
+    fn ops_past_the_end_of_the_texts_have_no_byte_ranges() {
+        let ops = with_byte_ranges(
+            vec![DiffOp::equal(0..1, 0..1), DiffOp::delete(1..3, 1)],
+            &["a\n", "b\n"],
+            &["a\n"],
+        );
+
+        assert_eq!(ops[0].old_bytes(), Some(0..2));
+        assert_eq!(ops[1].old_bytes(), None);
+        assert_eq!(ops[1].new_bytes(), None);
+    }
+
+    #[test]
+    fn deletes_are_placed_where_they_happened() {
+        let ops = capture_diff_slices(Algorithm::Myers, &["a\n", "\n"], &["\n", "\n"]);
+
+        assert_eq!(
+            from_similar(&ops),
+            vec![
+                DiffOp::new(ChangeTag::Delete, 0..1, 0..0),
+                DiffOp::new(ChangeTag::Equal, 1..2, 0..1),
+                DiffOp::new(ChangeTag::Insert, 2..2, 1..2),
+            ]
+        );
+    }
+
+    #[test]
+    fn deletes_come_before_inserts_in_a_replaced_block() {
+        let ops = vec![
+            DiffOp::new(ChangeTag::Equal, 0..1, 0..1),
+            DiffOp::new(ChangeTag::Insert, 1..1, 1..2),
+            DiffOp::new(ChangeTag::Delete, 1..2, 2..2),
+            DiffOp::new(ChangeTag::Insert, 2..2, 2..3),
+            DiffOp::new(ChangeTag::Delete, 2..3, 3..3),
+            DiffOp::new(ChangeTag::Equal, 3..4, 3..4),
+        ];
+
+        assert_eq!(
+            deletes_first(ops),
+            vec![
+                DiffOp::new(ChangeTag::Equal, 0..1, 0..1),
+                DiffOp::new(ChangeTag::Delete, 1..2, 1..1),
+                DiffOp::new(ChangeTag::Delete, 2..3, 1..1),
+                DiffOp::new(ChangeTag::Insert, 3..3, 1..2),
+                DiffOp::new(ChangeTag::Insert, 3..3, 2..3),
+                DiffOp::new(ChangeTag::Equal, 3..4, 3..4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::cmp::Ordering;
+
+use similar::TextDiff;
+
+use super::{
+    change::{Change, ChangeTag},
+    granularity::Granularity,
+    inline::inline_changes,
+};
+
+/// How deleted and inserted lines in a replaced block are matched up when
+/// working out which parts of them to emphasize
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use termdiff::{DrawDiff, LinePairing, Theme};
+///
+/// #[derive(Debug)]
+/// struct BracketsTheme {}
+/// impl Theme for BracketsTheme {
+///     fn highlight_insert<'this>(&self, input: &'this str) -> Cow<'this, str> {
+///         format!("[{}]", input).into()
+///     }
+///
+///     fn highlight_delete<'this>(&self, input: &'this str) -> Cow<'this, str> {
+///         format!("[{}]", input).into()
+///     }
+///
+///     fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+///         " ".into()
+///     }
+///
+///     fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+///         "-".into()
+///     }
+///
+///     fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+///         "+".into()
+///     }
+///
+///     fn header<'this>(&self) -> Cow<'this, str> {
+///         "".into()
+///     }
+/// }
+///
+/// let theme = BracketsTheme {};
+/// let old = "let alpha = 1;\nlet beta = 2;\n";
+/// let new = "let beta = 3;\nlet alpha = 4;\n";
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DrawDiff::new(old, new, &theme).with_line_pairing(LinePairing::Similarity)
+///     ),
+///     "-let alpha = [1;]\n-let beta = [2;]\n+let beta = [3;]\n+let alpha = [4;]\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LinePairing {
+    /// Compare the whole block of deleted lines with the whole block of
+    /// inserted lines
+    #[default]
+    Block,
+    /// Pair each deleted line with the most similar inserted line, and
+    /// compare each pair on its own
+    ///
+    /// This keeps emphasis meaningful when lines have been reordered as well
+    /// as changed.
+    Similarity,
+}
+
+/// Lines sharing less than this proportion of characters are not paired
+const MIN_RATIO: f32 = 0.5;
+
+/// Blocks needing more comparisons than this are compared as a whole, as
+/// comparing every line with every other line would be too slow
+const MAX_COMPARISONS: usize = 10_000;
+
+/// Pair deleted lines with the inserted lines most similar to them
+///
+/// Pairs are ordered by the deleted line. `None` is returned if the block is
+/// too large to compare line by line.
+pub(crate) fn pair_lines(old_lines: &[&str], new_lines: &[&str]) -> Option<Vec<(usize, usize)>> {
+    if old_lines.len().saturating_mul(new_lines.len()) > MAX_COMPARISONS {
+        return None;
+    }
+
+    let mut candidates: Vec<(f32, usize, usize)> = old_lines
+        .iter()
+        .enumerate()
+        .flat_map(|(old_index, old_line)| {
+            new_lines
+                .iter()
+                .enumerate()
+                .map(move |(new_index, new_line)| {
+                    let ratio = TextDiff::from_chars(*old_line, *new_line).ratio();
+                    (ratio, old_index, new_index)
+                })
+        })
+        .filter(|(ratio, _, _)| *ratio >= MIN_RATIO)
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(Ordering::Equal)
+            .then(a.1.cmp(&b.1))
+            .then(a.2.cmp(&b.2))
+    });
+
+    let mut old_paired = vec![false; old_lines.len()];
+    let mut new_paired = vec![false; new_lines.len()];
+    let mut pairs = vec![];
+
+    for (_, old_index, new_index) in candidates {
+        if old_paired[old_index] || new_paired[new_index] {
+            continue;
+        }
+
+        old_paired[old_index] = true;
+        new_paired[new_index] = true;
+        pairs.push((old_index, new_index));
+    }
+
+    pairs.sort_unstable();
+    Some(pairs)
+}
+
+/// Build the changes for a replaced block of lines, emphasizing what differs
+/// between each deleted line and the inserted line most similar to it
+pub(crate) fn paired_inline_changes<'a>(
+    old_lines: &[&'a str],
+    new_lines: &[&'a str],
+    minimal: bool,
+    granularity: Granularity,
+) -> Vec<Change<'a>> {
+    let Some(pairs) = pair_lines(old_lines, new_lines) else {
+        return inline_changes(old_lines, new_lines, minimal, granularity);
+    };
+
+    let mut old_changes: Vec<Change<'a>> = old_lines
+        .iter()
+        .map(|line| Change::new(ChangeTag::Delete, vec![(false, (*line).into())]))
+        .collect();
+    let mut new_changes: Vec<Change<'a>> = new_lines
+        .iter()
+        .map(|line| Change::new(ChangeTag::Insert, vec![(false, (*line).into())]))
+        .collect();
+
+    for (old_index, new_index) in pairs {
+        let mut changes = inline_changes(
+            &old_lines[old_index..=old_index],
+            &new_lines[new_index..=new_index],
+            minimal,
+            granularity,
+        )
+        .into_iter();
+
+        if let (Some(old_change), Some(new_change)) = (changes.next(), changes.next()) {
+            old_changes[old_index] = old_change;
+            new_changes[new_index] = new_change;
+        }
+    }
+
+    old_changes.into_iter().chain(new_changes).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pair_lines;
+
+    #[test]
+    fn lines_are_paired_with_the_most_similar_line() {
+        let actual = pair_lines(
+            &["let alpha = 1;\n", "let beta = 2;\n", "unrelated\n"],
+            &["let beta = 3;\n", "let alpha = 4;\n"],
+        );
+
+        assert_eq!(actual, Some(vec![(0, 1), (1, 0)]));
+    }
+
+    #[test]
+    fn large_blocks_are_not_paired() {
+        let lines = vec!["a\n"; 101];
+
+        assert_eq!(pair_lines(&lines, &lines), None);
+    }
+}
@@ -0,0 +1,53 @@
+use std::{borrow::Cow, fmt::Debug};
+
+use super::change::ChangeTag;
+
+/// A [`PostProcessor`] sees each line of a diff once it has been drawn, just
+/// before it is written
+///
+/// It can leave the line out, rewrite it, or only look at it, such as to
+/// count lines, without needing a renderer of its own. Processors get
+/// `&self`, so one that counts can keep its count in a `Cell` or similar.
+///
+/// Headers, hunk headers and the markers between hunks aren't passed to it.
+///
+/// # Examples
+///
+/// ```
+/// use std::{borrow::Cow, cell::Cell};
+///
+/// use termdiff::{ArrowsTheme, ChangeTag, DrawDiff, PostProcessor};
+///
+/// /// Leave out unchanged lines, counting the changed ones
+/// #[derive(Debug, Default)]
+/// struct ChangedOnly {
+///     changed: Cell<usize>,
+/// }
+/// impl PostProcessor for ChangedOnly {
+///     fn process<'line>(&self, tag: ChangeTag, line: &'line str) -> Option<Cow<'line, str>> {
+///         if tag == ChangeTag::Equal {
+///             return None;
+///         }
+///
+///         self.changed.set(self.changed.get() + 1);
+///         Some(line.into())
+///     }
+/// }
+///
+/// let theme = ArrowsTheme::default();
+/// let processor = ChangedOnly::default();
+/// let actual = DrawDiff::new("a\nb\nc\n", "a\nB\nc\n", &theme)
+///     .with_post_processor(&processor)
+///     .to_string();
+///
+/// assert_eq!(actual, "< left / > right\n<b\n>B\n");
+/// assert_eq!(processor.changed.get(), 2);
+/// ```
+pub trait PostProcessor: Debug {
+    /// What to write in place of a drawn line, or `None` to leave it out
+    ///
+    /// The line is as it would be written, with its prefix, its line ending,
+    /// and any escape codes, indentation or ASCII escapes the diff adds. What
+    /// is given back is written without any of those being added again.
+    fn process<'line>(&self, tag: ChangeTag, line: &'line str) -> Option<Cow<'line, str>>;
+}
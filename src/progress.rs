@@ -0,0 +1,69 @@
+use std::fmt::{Debug, Formatter};
+
+/// A callback told how far through a large diff the algorithm has got, so a
+/// spinner or progress bar can be shown while it works
+///
+/// The callback is given how many of the old lines have been worked
+/// through, and how many there are. It is only called for diffs of at least
+/// 10,000 lines, counting both sides, unless
+/// [`with_min_lines`](Progress::with_min_lines) says otherwise. Progress
+/// isn't reported for [`Algorithm::Custom`](crate::Algorithm::Custom)
+/// algorithms.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::Cell;
+///
+/// use termdiff::{ArrowsTheme, DrawDiff, Progress};
+/// let theme = ArrowsTheme::default();
+/// let reported = Cell::new((0, 0));
+/// let report = |done, total| reported.set((done, total));
+///
+/// DrawDiff::new("a\nb\nc\n", "a\nB\nc\n", &theme)
+///     .with_progress(Progress::new(&report).with_min_lines(0))
+///     .to_string();
+///
+/// assert_eq!(reported.get(), (3, 3));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Progress<'a> {
+    callback: &'a dyn Fn(usize, usize),
+    min_lines: usize,
+}
+
+impl<'a> Progress<'a> {
+    /// Report progress to a callback
+    #[must_use]
+    pub fn new(callback: &'a dyn Fn(usize, usize)) -> Self {
+        Self {
+            callback,
+            min_lines: 10_000,
+        }
+    }
+
+    /// Only report progress for diffs of at least this many lines, counting
+    /// both sides
+    #[must_use]
+    pub fn with_min_lines(mut self, min_lines: usize) -> Self {
+        self.min_lines = min_lines;
+        self
+    }
+
+    /// The diff is large enough to report progress for
+    pub(crate) fn applies(&self, old_lines: usize, new_lines: usize) -> bool {
+        old_lines + new_lines >= self.min_lines
+    }
+
+    pub(crate) fn report(&self, done: usize, total: usize) {
+        (self.callback)(done, total);
+    }
+}
+
+impl Debug for Progress<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress")
+            .field("min_lines", &self.min_lines)
+            .finish_non_exhaustive()
+    }
+}
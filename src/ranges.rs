@@ -0,0 +1,48 @@
+use std::ops::Range;
+
+use similar::DiffTag;
+
+use super::{intern::diff_lines, lines::split_lines};
+
+/// The lines that changed between two pieces of text, without rendering
+/// anything
+///
+/// Each change is given as the range of line indexes, starting from 0, that
+/// it covers in the old text and in the new text. Deletes have an empty new
+/// range and inserts an empty old range, marking where they happened.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::changed_ranges;
+/// let old = "a\nb\nc\nd\n";
+/// let new = "a\nB\nc\nd\ne\n";
+///
+/// assert_eq!(changed_ranges(old, new), vec![(1..2, 1..2), (4..4, 4..5)]);
+/// ```
+#[must_use]
+pub fn changed_ranges(old: &str, new: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    if old == new {
+        return vec![];
+    }
+
+    let old: Vec<&str> = split_lines(old).collect();
+    let new: Vec<&str> = split_lines(new).collect();
+
+    // Positions come from the lengths, as similar doesn't always place
+    // deletes and inserts on the other side where they happened
+    let (mut old_at, mut new_at) = (0, 0);
+    diff_lines(&old, &new)
+        .iter()
+        .map(similar::DiffOp::as_tag_tuple)
+        .map(|(tag, old_range, new_range)| {
+            let old_range = old_at..old_at + old_range.len();
+            let new_range = new_at..new_at + new_range.len();
+            old_at = old_range.end;
+            new_at = new_range.end;
+            (tag, old_range, new_range)
+        })
+        .filter(|(tag, _, _)| *tag != DiffTag::Equal)
+        .map(|(_, old_range, new_range)| (old_range, new_range))
+        .collect()
+}
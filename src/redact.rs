@@ -0,0 +1,83 @@
+use std::{borrow::Cow, fmt::Debug};
+
+use super::line_endings::split_ending;
+
+/// A [`Redactor`] masks the content of lines before they are drawn
+///
+/// The diff is still worked out from the real text, so a changed secret
+/// shows up as a changed line, but what it was changed from and to never
+/// reaches the output. This means diffs of files like `.env` files can be
+/// written to logs.
+///
+/// Lines that are masked lose their inline emphasis, as the emphasized
+/// parts may not line up with the mask. The whole line is emphasized
+/// instead if any of it was.
+pub trait Redactor: Debug {
+    /// Mask the parts of a line that shouldn't be shown
+    ///
+    /// Lines are passed without their line ending, which is added back
+    /// after.
+    fn redact<'line>(&self, line: &'line str) -> Cow<'line, str>;
+}
+
+/// Run a line, split into emphasized and plain segments, through a
+/// [`Redactor`]
+pub(crate) fn redact_line<'values, 'line>(
+    redactor: &dyn Redactor,
+    values: &'values [(bool, Cow<'line, str>)],
+) -> Cow<'values, [(bool, Cow<'line, str>)]> {
+    let line: String = values.iter().map(|(_, value)| value.as_ref()).collect();
+    let (content, _) = split_ending(&line);
+    let ending = &line[content.len()..];
+    let redacted = redactor.redact(content);
+
+    if redacted == content {
+        return Cow::Borrowed(values);
+    }
+
+    let emphasized = values.iter().any(|(emphasized, _)| *emphasized);
+    let mut redacted = vec![(emphasized, Cow::Owned(redacted.into_owned()))];
+    if !ending.is_empty() {
+        redacted.push((false, Cow::Owned(ending.to_string())));
+    }
+
+    Cow::Owned(redacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{redact_line, Redactor};
+
+    #[derive(Debug)]
+    struct Digits;
+    impl Redactor for Digits {
+        fn redact<'line>(&self, line: &'line str) -> Cow<'line, str> {
+            if line.contains(|character: char| character.is_ascii_digit()) {
+                line.replace(|character: char| character.is_ascii_digit(), "*")
+                    .into()
+            } else {
+                line.into()
+            }
+        }
+    }
+
+    #[test]
+    fn masked_lines_are_emphasized_as_a_whole() {
+        let values = [
+            (false, "pin=1".into()),
+            (true, "23".into()),
+            (false, "\n".into()),
+        ];
+
+        assert_eq!(
+            redact_line(&Digits, &values).as_ref(),
+            [(true, "pin=***".into()), (false, "\n".into())]
+        );
+        assert!(matches!(
+            redact_line(&Digits, &[(true, "name\n".into())]),
+            Cow::Borrowed(_)
+        ));
+    }
+}
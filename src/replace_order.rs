@@ -0,0 +1,136 @@
+use super::{
+    change::{Change, ChangeTag},
+    pairing::pair_lines,
+};
+
+/// The order the lines of a replaced block are drawn in
+///
+/// Whichever algorithm found the diff, a replaced block always has every
+/// deleted line before every inserted line, unless this says otherwise.
+///
+/// See [`DrawDiff::with_replace_order`](crate::DrawDiff::with_replace_order).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReplaceOrder {
+    /// Every deleted line, then every inserted line
+    #[default]
+    Blocks,
+    /// Each deleted line followed by the inserted line in the same place in
+    /// the block, with the lines left over on the longer side after them
+    Interleaved,
+    /// Each deleted line followed by the inserted line most like it, when
+    /// there is one, so long replaced blocks can be compared line by line
+    ///
+    /// Lines are paired as they are for
+    /// [`LinePairing::Similarity`](crate::LinePairing::Similarity), keeping
+    /// the lines on each side in order. Lines that aren't paired are drawn
+    /// where they fall between the pairs, and blocks too large to pair are
+    /// drawn as [`ReplaceOrder::Blocks`].
+    Paired,
+}
+
+/// Put the changes for a replaced block of lines in order
+pub(crate) fn order_replaced<'a>(
+    changes: Vec<Change<'a>>,
+    old_lines: &[&str],
+    new_lines: &[&str],
+    order: ReplaceOrder,
+) -> Vec<Change<'a>> {
+    match order {
+        ReplaceOrder::Blocks => changes,
+        ReplaceOrder::Paired => match pair_lines(old_lines, new_lines) {
+            Some(pairs) => paired(changes, &pairs),
+            None => changes,
+        },
+        ReplaceOrder::Interleaved => {
+            let (deletes, inserts): (Vec<_>, Vec<_>) = changes
+                .into_iter()
+                .partition(|change| change.tag() == ChangeTag::Delete);
+            let mut deletes = deletes.into_iter();
+            let mut inserts = inserts.into_iter();
+            let mut ordered = vec![];
+
+            loop {
+                match (deletes.next(), inserts.next()) {
+                    (None, None) => return ordered,
+                    (delete, insert) => ordered.extend(delete.into_iter().chain(insert)),
+                }
+            }
+        }
+    }
+}
+
+/// Draw each pair of lines together, skipping pairs that would put the
+/// inserted lines out of order
+fn paired<'a>(changes: Vec<Change<'a>>, pairs: &[(usize, usize)]) -> Vec<Change<'a>> {
+    let (deletes, inserts): (Vec<_>, Vec<_>) = changes
+        .into_iter()
+        .partition(|change| change.tag() == ChangeTag::Delete);
+    let mut deletes = deletes.into_iter();
+    let mut inserts = inserts.into_iter();
+    let mut ordered = vec![];
+    let (mut old_at, mut new_at) = (0, 0);
+
+    for &(old_index, new_index) in pairs {
+        if new_index < new_at {
+            continue;
+        }
+
+        ordered.extend(deletes.by_ref().take(old_index - old_at));
+        ordered.extend(inserts.by_ref().take(new_index - new_at));
+        ordered.extend(deletes.next().into_iter().chain(inserts.next()));
+        old_at = old_index + 1;
+        new_at = new_index + 1;
+    }
+
+    ordered.extend(deletes);
+    ordered.extend(inserts);
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{order_replaced, ReplaceOrder};
+    use crate::change::{Change, ChangeTag};
+
+    fn ordered(old: &[&str], new: &[&str], order: ReplaceOrder) -> Vec<String> {
+        let changes = old
+            .iter()
+            .map(|line| Change::new(ChangeTag::Delete, vec![(false, (*line).into())]))
+            .chain(
+                new.iter()
+                    .map(|line| Change::new(ChangeTag::Insert, vec![(false, (*line).into())])),
+            )
+            .collect();
+
+        order_replaced(changes, old, new, order)
+            .iter()
+            .map(|change| format!("{:?} {}", change.tag(), change.content().trim_end()))
+            .collect()
+    }
+
+    #[test]
+    fn left_over_lines_come_after_the_interleaved_ones() {
+        assert_eq!(
+            ordered(&["a\n", "b\n", "c\n"], &["A\n"], ReplaceOrder::Interleaved),
+            vec!["Delete a", "Insert A", "Delete b", "Delete c"]
+        );
+    }
+
+    #[test]
+    fn paired_lines_are_drawn_together_and_in_order() {
+        let old = ["let alpha = 1;\n", "removed();\n", "let beta = 2;\n"];
+        let new = ["added();\n", "let alpha = 3;\n", "let beta = 4;\n"];
+
+        assert_eq!(
+            ordered(&old, &new, ReplaceOrder::Paired),
+            vec![
+                "Insert added();",
+                "Delete let alpha = 1;",
+                "Insert let alpha = 3;",
+                "Delete removed();",
+                "Delete let beta = 2;",
+                "Insert let beta = 4;",
+            ]
+        );
+    }
+}
@@ -0,0 +1,434 @@
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    io::{Read, Write as IoWrite},
+};
+
+#[cfg(feature = "serde")]
+use super::verify::{verify, VerifyError};
+use super::{
+    algorithm::Algorithm,
+    cancellation::{CancellationToken, Cancelled},
+    change::ChangeTag,
+    draw_diff::DrawDiff,
+    encoding::{read_result, write_result},
+    header::Header,
+    hunks::{apply_hunks, hunks, HunkId},
+    intern::diff_lines,
+    line_endings::split_ending,
+    lines::split_lines,
+    ops::{from_similar, with_byte_ranges, DiffOp},
+    themes::{prefix_column, Theme},
+    width::display_width,
+};
+
+/// A diff that has been worked out, ready to be rendered in different ways
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DiffResult};
+/// let theme = ArrowsTheme::default();
+/// let result = DiffResult::new("a\nb\nc\n", "a\nB\nc\n");
+///
+/// assert_eq!(result.old_annotated(&theme), " a\n<b\n c\n");
+/// assert_eq!(result.new_annotated(&theme), " a\n>B\n c\n");
+/// assert_eq!(
+///     format!("{}", result.draw(&theme)),
+///     "< left / > right\n a\n<b\n>B\n c\n"
+/// );
+/// ```
+///
+/// With the `serde` feature this can be serialized, with the texts and the
+/// ops. Deserializing checks the ops are a correct diff of the texts, as
+/// [`DiffResult::from_reader`] does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "RawDiffResult")
+)]
+pub struct DiffResult<'a> {
+    old: Cow<'a, str>,
+    new: Cow<'a, str>,
+    ops: Vec<DiffOp>,
+}
+
+/// A [`DiffResult`] as it is deserialized, before its ops are checked
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawDiffResult {
+    old: String,
+    new: String,
+    ops: Vec<DiffOp>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<RawDiffResult> for DiffResult<'_> {
+    type Error = VerifyError;
+
+    fn try_from(raw: RawDiffResult) -> Result<Self, Self::Error> {
+        verify(&raw.old, &raw.new, &raw.ops)?;
+
+        Ok(Self {
+            old: raw.old.into(),
+            new: raw.new.into(),
+            ops: raw.ops,
+        })
+    }
+}
+
+impl<'a> DiffResult<'a> {
+    /// Work out the diff between two pieces of text
+    #[must_use]
+    pub fn new(old: &'a str, new: &'a str) -> Self {
+        let old_lines: Vec<&str> = split_lines(old).collect();
+        let new_lines: Vec<&str> = split_lines(new).collect();
+
+        Self {
+            old: old.into(),
+            new: new.into(),
+            ops: from_similar(&diff_lines(&old_lines, &new_lines)),
+        }
+    }
+
+    /// Work out the diff between two pieces of text, giving up if the token
+    /// is cancelled before it is done, see [`CancellationToken`]
+    ///
+    /// # Errors
+    ///
+    /// Errors if the token was cancelled.
+    pub fn new_cancellable(
+        old: &'a str,
+        new: &'a str,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, Cancelled> {
+        let old_lines: Vec<&str> = split_lines(old).collect();
+        let new_lines: Vec<&str> = split_lines(new).collect();
+        let ops =
+            Algorithm::Myers.diff_observed(&old_lines, &new_lines, None, Some(cancellation))?;
+
+        Ok(Self {
+            old: old.into(),
+            new: new.into(),
+            ops,
+        })
+    }
+
+    /// Write the diff in a compact, versioned format, so it can be read back
+    /// with [`DiffResult::from_reader`] and drawn somewhere else
+    ///
+    /// The format starts with `termdiff 1`, and only changes along with the
+    /// number after it. Byte ranges on the ops aren't written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::DiffResult;
+    /// let result = DiffResult::new("a\nb\n", "a\nc\n");
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// result.to_writer(&mut buffer).unwrap();
+    ///
+    /// assert_eq!(DiffResult::from_reader(buffer.as_slice()).unwrap(), result);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Errors if writing fails.
+    pub fn to_writer(&self, writer: impl IoWrite) -> std::io::Result<()> {
+        write_result(writer, &self.old, &self.new, &self.ops)
+    }
+
+    /// Read a diff written by [`DiffResult::to_writer`]
+    ///
+    /// # Errors
+    ///
+    /// Errors if reading fails, or with [`std::io::ErrorKind::InvalidData`]
+    /// if what is read isn't a diff in a version of the format this can read,
+    /// or its ops aren't a correct diff of its texts.
+    pub fn from_reader(reader: impl Read) -> std::io::Result<DiffResult<'static>> {
+        let (old, new, ops) = read_result(reader)?;
+
+        Ok(DiffResult::from_parts(old, new, ops))
+    }
+
+    /// A diff of texts that are owned, with ops that have already been
+    /// worked out
+    pub(crate) fn from_parts(old: String, new: String, ops: Vec<DiffOp>) -> DiffResult<'static> {
+        DiffResult {
+            old: old.into(),
+            new: new.into(),
+            ops,
+        }
+    }
+
+    /// Give each op where its lines are in the texts in bytes, see
+    /// [`DiffOp::with_byte_ranges`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::DiffResult;
+    /// let old = "a\nb\nc\n";
+    /// let result = DiffResult::new(old, "a\nB\nc\n").with_byte_ranges();
+    /// let deleted = &result.ops()[1];
+    ///
+    /// assert_eq!(deleted.old_bytes().map(|bytes| &old[bytes]), Some("b\n"));
+    /// ```
+    #[must_use]
+    pub fn with_byte_ranges(mut self) -> Self {
+        let old_lines: Vec<&str> = split_lines(&self.old).collect();
+        let new_lines: Vec<&str> = split_lines(&self.new).collect();
+        self.ops = with_byte_ranges(self.ops, &old_lines, &new_lines);
+        self
+    }
+
+    /// The ops of the diff
+    #[must_use]
+    pub fn ops(&self) -> &[DiffOp] {
+        &self.ops
+    }
+
+    /// The old and new text
+    pub(crate) fn texts(&self) -> (&str, &str) {
+        (&self.old, &self.new)
+    }
+
+    /// How many lines were added
+    #[must_use]
+    pub fn insertions(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op.tag(), ChangeTag::Insert | ChangeTag::MovedTo))
+            .map(|op| op.new_range().len())
+            .sum()
+    }
+
+    /// How many lines were removed
+    #[must_use]
+    pub fn deletions(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op.tag(), ChangeTag::Delete | ChangeTag::MovedFrom))
+            .map(|op| op.old_range().len())
+            .sum()
+    }
+
+    /// How many columns the widest line of either text takes up in a
+    /// terminal, not counting line endings
+    ///
+    /// Wide characters, like most CJK ones, count as two columns. This is
+    /// the width of the content only, without the theme's prefixes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::DiffResult;
+    /// let result = DiffResult::new("short\n", "日本語の行\n");
+    ///
+    /// assert_eq!(result.max_line_width(), 10);
+    /// ```
+    #[must_use]
+    pub fn max_line_width(&self) -> usize {
+        split_lines(&self.old)
+            .chain(split_lines(&self.new))
+            .map(|line| display_width(split_ending(line).0))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// How much the two texts have in common, from 0 for nothing to 1 for
+    /// everything
+    ///
+    /// This is the share of all the lines of both texts that are unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::DiffResult;
+    ///
+    /// assert_eq!(DiffResult::new("a\nb\n", "a\nc\n").similarity(), 0.5);
+    /// assert_eq!(DiffResult::new("", "").similarity(), 1.0);
+    /// ```
+    #[must_use]
+    pub fn similarity(&self) -> f64 {
+        let equal: usize = self
+            .ops
+            .iter()
+            .filter(|op| op.tag() == ChangeTag::Equal)
+            .map(|op| op.old_range().len() + op.new_range().len())
+            .sum();
+        let total = split_lines(&self.old).count() + split_lines(&self.new).count();
+
+        similarity(equal, total)
+    }
+
+    /// Draw the whole diff
+    #[must_use]
+    pub fn draw<'b>(&'b self, theme: &'b dyn Theme) -> DrawDiff<'b> {
+        DrawDiff::new(&self.old, &self.new, theme).with_ops(&self.ops)
+    }
+
+    /// Draw the whole diff with several themes, each named by a label, reusing
+    /// the ops rather than working out the diff for each
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsColorTheme, ArrowsTheme, DiffResult};
+    /// let result = DiffResult::new("a\nb\n", "a\nc\n");
+    /// let rendered = result.render_all(&[
+    ///     ("terminal", &ArrowsColorTheme::default()),
+    ///     ("log", &ArrowsTheme::default()),
+    /// ]);
+    ///
+    /// assert_eq!(rendered[1], ("log", "< left / > right\n a\n<b\n>c\n".to_string()));
+    /// assert!(rendered[0].1.contains("\u{1b}["));
+    /// ```
+    #[must_use]
+    pub fn render_all<'label>(
+        &self,
+        themes: &[(&'label str, &dyn Theme)],
+    ) -> Vec<(&'label str, String)> {
+        themes
+            .iter()
+            .map(|(label, theme)| (*label, self.draw(*theme).to_string_with_capacity_estimate()))
+            .collect()
+    }
+
+    /// Draw each group of changed lines on its own, with no header, so they
+    /// can be shown separately
+    ///
+    /// Unchanged lines aren't drawn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffResult};
+    /// let theme = ArrowsTheme::default();
+    /// let result = DiffResult::new("a\nb\nc\nd\n", "A\nb\nc\n");
+    ///
+    /// assert_eq!(result.render_hunks(&theme), vec!["<a\n>A\n", "<d\n"]);
+    /// ```
+    #[must_use]
+    pub fn render_hunks(&self, theme: &dyn Theme) -> Vec<String> {
+        let old_lines: Vec<&str> = split_lines(&self.old).collect();
+        let new_lines: Vec<&str> = split_lines(&self.new).collect();
+
+        hunks(&self.ops)
+            .map(|hunk| {
+                let old = old_lines[hunk.old_range()].concat();
+                let new = new_lines[hunk.new_range()].concat();
+                let ops = hunk.rebased_ops();
+
+                DrawDiff::new(&old, &new, theme)
+                    .with_ops(&ops)
+                    .with_header(Header::Never)
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// The id of each group of changed lines, in the same order as
+    /// [`DiffResult::render_hunks`]
+    #[must_use]
+    pub fn hunk_ids(&self) -> Vec<HunkId> {
+        let old_lines: Vec<&str> = split_lines(&self.old).collect();
+        let new_lines: Vec<&str> = split_lines(&self.new).collect();
+
+        hunks(&self.ops)
+            .map(|hunk| hunk.id(&old_lines, &new_lines))
+            .collect()
+    }
+
+    /// The old text with only the changes in some hunks made
+    ///
+    /// Applying none of the hunks gives the old text back, and applying all
+    /// of them gives the new text. Ids of hunks not in the diff are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::DiffResult;
+    /// let result = DiffResult::new("a\nb\nc\nd\n", "A\nb\nc\n");
+    /// let ids = result.hunk_ids();
+    ///
+    /// assert_eq!(ids.len(), 2);
+    /// assert_eq!(result.apply_hunks(&ids[1..]), "a\nb\nc\n");
+    /// assert_eq!(result.apply_hunks(&result.hunk_ids()), "A\nb\nc\n");
+    /// ```
+    #[must_use]
+    pub fn apply_hunks(&self, hunks: &[HunkId]) -> String {
+        let old_lines: Vec<&str> = split_lines(&self.old).collect();
+        let new_lines: Vec<&str> = split_lines(&self.new).collect();
+
+        apply_hunks(&old_lines, &new_lines, &self.ops, hunks)
+    }
+
+    /// Render just the old text, marking the lines that were removed
+    #[must_use]
+    pub fn old_annotated(&self, theme: &dyn Theme) -> String {
+        let lines: Vec<&str> = split_lines(&self.old).collect();
+        let prefix_width = theme.prefix_width();
+        let mut buffer = String::new();
+
+        for op in &self.ops {
+            for line in lines.get(op.old_range()).unwrap_or_default() {
+                let (prefix, content) = match op.tag() {
+                    ChangeTag::Equal => (theme.equal_prefix(), theme.equal_content(line)),
+                    _ => (theme.delete_prefix(), theme.delete_content(line)),
+                };
+                write_annotated(&mut buffer, theme, line, &prefix, prefix_width, &content);
+            }
+        }
+
+        buffer
+    }
+
+    /// Render just the new text, marking the lines that were added
+    #[must_use]
+    pub fn new_annotated(&self, theme: &dyn Theme) -> String {
+        let lines: Vec<&str> = split_lines(&self.new).collect();
+        let prefix_width = theme.prefix_width();
+        let mut buffer = String::new();
+
+        for op in &self.ops {
+            for line in lines.get(op.new_range()).unwrap_or_default() {
+                let (prefix, content) = match op.tag() {
+                    ChangeTag::Equal => (theme.equal_prefix(), theme.equal_content(line)),
+                    _ => (theme.insert_prefix(), theme.insert_line(line)),
+                };
+                write_annotated(&mut buffer, theme, line, &prefix, prefix_width, &content);
+            }
+        }
+
+        buffer
+    }
+}
+
+/// The share of lines that are unchanged, counting the lines of both sides
+// Line counts are far too small to lose precision as a float
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn similarity(equal: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        equal as f64 / total as f64
+    }
+}
+
+fn write_annotated(
+    buffer: &mut String,
+    theme: &dyn Theme,
+    line: &str,
+    prefix: &str,
+    prefix_width: usize,
+    content: &str,
+) {
+    let prefix = prefix_column(theme, prefix.into(), prefix_width);
+    let _ = write!(buffer, "{prefix}{content}");
+
+    if !line.ends_with(['\r', '\n']) {
+        buffer.push_str(&theme.line_end());
+    }
+}
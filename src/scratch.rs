@@ -0,0 +1,68 @@
+use super::{change::Change, ops::DiffOp};
+
+/// Buffers kept between renders, so drawing many diffs one after another
+/// doesn't allocate them afresh each time
+///
+/// Holds the output text along with the lines, operations and changes worked
+/// out along the way. They are cleared before each use and keep their
+/// capacity afterwards.
+///
+/// See [`DrawDiff::render_with`](crate::DrawDiff::render_with).
+#[derive(Debug, Default)]
+pub struct DiffScratch {
+    pub(crate) output: String,
+    pub(crate) old: Vec<&'static str>,
+    pub(crate) new: Vec<&'static str>,
+    pub(crate) ops: Vec<DiffOp>,
+    pub(crate) changes: Vec<Change<'static>>,
+    pub(crate) visible: Vec<bool>,
+}
+
+impl DiffScratch {
+    /// Empty buffers, which grow to fit the diffs drawn with them
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffScratch, DrawDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let mut scratch = DiffScratch::new();
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("a\n", "b\n", &theme).render_with(&mut scratch),
+    ///     "< left / > right\n<a\n>b\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Empty a buffer of borrowed values so it can be kept for values borrowed
+/// for another lifetime, keeping its allocation
+///
+/// Collecting from a vector's own iterator into a vector of a type laid out
+/// the same reuses the allocation rather than making a new one.
+pub(crate) fn recycle<T, U>(buffer: Vec<T>) -> Vec<U> {
+    buffer.into_iter().filter_map(|_| None).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::recycle;
+
+    #[test]
+    fn recycling_keeps_the_allocation() {
+        let text = String::from("a b c");
+        let mut borrowed: Vec<&str> = Vec::with_capacity(16);
+        borrowed.extend(text.split(' '));
+        let pointer = borrowed.as_ptr().cast::<u8>();
+
+        let recycled: Vec<&'static str> = recycle(borrowed);
+
+        assert!(recycled.is_empty());
+        assert_eq!(recycled.capacity(), 16);
+        assert_eq!(recycled.as_ptr().cast::<u8>(), pointer);
+    }
+}
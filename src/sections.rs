@@ -0,0 +1,231 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use super::{
+    draw_diff::{DrawDiff, DrawOptions},
+    header::Header,
+    line_endings::split_ending,
+    lines::split_lines,
+    themes::Theme,
+};
+
+/// Gives the key of the section a line starts, or `None` for other lines
+type Marker<'a> = Box<dyn Fn(&str) -> Option<&str> + 'a>;
+
+/// Draws the diff of two documents a section at a time, pairing sections by
+/// their key
+///
+/// A section starts at each line the marker gives a key for, like the name
+/// of an INI section or a Markdown heading, and runs to the next. Sections
+/// with the same key are diffed with each other, wherever they are in the
+/// documents, so a change in one section never lines up with lines from
+/// another. Each pair is drawn under the theme's
+/// [`entry_header`](Theme::entry_header) with its key, in the order of the
+/// old document, followed by any sections that are only in the new one.
+///
+/// Lines before the first marker are a section with no key, and are drawn
+/// first with no header. The theme's own [`header`](Theme::header) isn't
+/// drawn for each section.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, SectionDiff};
+/// let theme = ArrowsTheme::default();
+/// let old = "[server]\nport = 80\n[client]\nretries = 3\n";
+/// let new = "[client]\nretries = 5\n[server]\nport = 80\n";
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         SectionDiff::new(old, new, &theme)
+///             .with_marker(|line| line.strip_prefix('[')?.split(']').next())
+///     ),
+///     "=== server
+///  [server]
+///  port = 80
+/// === client
+///  [client]
+/// <retries = 3
+/// >retries = 5
+/// "
+/// );
+/// ```
+pub struct SectionDiff<'a> {
+    old: &'a str,
+    new: &'a str,
+    theme: &'a dyn Theme,
+    marker: Marker<'a>,
+    options: DrawOptions<'a>,
+}
+
+impl<'a> SectionDiff<'a> {
+    /// Diff two documents, with sections starting at lines beginning with
+    /// `#`, like Markdown headings, keyed by the heading
+    #[must_use]
+    pub fn new(old: &'a str, new: &'a str, theme: &'a dyn Theme) -> Self {
+        Self {
+            old,
+            new,
+            theme,
+            marker: Box::new(|line| Some(line.strip_prefix('#')?.trim_start_matches('#').trim())),
+            options: Box::new(|diff| diff),
+        }
+    }
+
+    /// Say which lines start a section, by giving the key of the section
+    /// they start, or `None` for other lines
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, SectionDiff};
+    /// let theme = ArrowsTheme::default();
+    /// let marker = String::from("== ");
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         SectionDiff::new("== a\nx\n", "== a\ny\n", &theme)
+    ///             .with_marker(move |line| line.strip_prefix(marker.as_str()))
+    ///     ),
+    ///     "=== a\n == a\n<x\n>y\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_marker(mut self, marker: impl Fn(&str) -> Option<&str> + 'a) -> Self {
+        self.marker = Box::new(marker);
+        self
+    }
+
+    /// Set the options for every section's diff, by calling `with_` methods
+    /// on each [`DrawDiff`] before it is drawn
+    ///
+    /// Each diff starts with [`Header::Never`], which these can change.
+    #[must_use]
+    pub fn with_options(mut self, options: impl Fn(DrawDiff<'_>) -> DrawDiff<'_> + 'a) -> Self {
+        self.options = Box::new(options);
+        self
+    }
+
+    /// The sections of the old and new document with the same key, with an
+    /// empty section where one is missing
+    fn pairs(&self) -> Vec<(Option<&'a str>, &'a str, &'a str)> {
+        let old = sections(self.old, &self.marker);
+        let mut new: Vec<Option<Section<'a>>> = sections(self.new, &self.marker)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut pairs = vec![];
+
+        for section in old {
+            let paired = new
+                .iter_mut()
+                .find(|other| other.is_some_and(|other| other.key == section.key))
+                .and_then(Option::take);
+            pairs.push((
+                section.key,
+                section.text,
+                paired.map_or("", |other| other.text),
+            ));
+        }
+        for section in new.into_iter().flatten() {
+            pairs.push((section.key, "", section.text));
+        }
+
+        pairs.sort_by_key(|(key, _, _)| key.is_some());
+        pairs
+    }
+}
+
+impl Display for SectionDiff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (key, old, new) in self.pairs() {
+            if let Some(key) = key {
+                write!(f, "{}", self.theme.entry_header(key))?;
+            }
+            let diff = DrawDiff::new(old, new, self.theme).with_header(Header::Never);
+            write!(f, "{}", (self.options)(diff))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for SectionDiff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SectionDiff")
+            .field("old", &self.old)
+            .field("new", &self.new)
+            .field("theme", &self.theme)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Part of a document, from a line with a marker up to the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Section<'a> {
+    key: Option<&'a str>,
+    text: &'a str,
+}
+
+/// Split a document into sections, leaving out a first section with no key
+/// if it is empty
+fn sections<'a>(text: &'a str, marker: &dyn Fn(&str) -> Option<&str>) -> Vec<Section<'a>> {
+    let mut sections = vec![Section {
+        key: None,
+        text: "",
+    }];
+    let mut start = 0;
+    let mut offset = 0;
+
+    for line in split_lines(text) {
+        if let Some(key) = marker(split_ending(line).0) {
+            if let Some(last) = sections.last_mut() {
+                last.text = &text[start..offset];
+            }
+            sections.push(Section {
+                key: Some(key),
+                text: "",
+            });
+            start = offset;
+        }
+        offset += line.len();
+    }
+    if let Some(last) = sections.last_mut() {
+        last.text = &text[start..];
+    }
+
+    sections.retain(|section| section.key.is_some() || !section.text.is_empty());
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sections, Section};
+
+    #[test]
+    fn lines_before_the_first_marker_have_no_key() {
+        fn marker(line: &str) -> Option<&str> {
+            line.strip_prefix("# ")
+        }
+
+        assert_eq!(
+            sections("intro\n# a\nx\n# b\n", &marker),
+            vec![
+                Section {
+                    key: None,
+                    text: "intro\n"
+                },
+                Section {
+                    key: Some("a"),
+                    text: "# a\nx\n"
+                },
+                Section {
+                    key: Some("b"),
+                    text: "# b\n"
+                },
+            ]
+        );
+        assert_eq!(sections("", &marker), vec![]);
+    }
+}
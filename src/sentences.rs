@@ -0,0 +1,123 @@
+use super::{draw_diff::DrawDiff, themes::Theme};
+
+/// A diff of paragraphs of text that compares whole sentences, rather than
+/// lines
+///
+/// Both texts are reflowed so each sentence is on a line of its own, with a
+/// blank line between paragraphs. Rewrapping a paragraph then changes
+/// nothing, and changing a word only changes its sentence, with the word
+/// emphasized.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, SentenceDiff};
+/// let theme = ArrowsTheme::default();
+/// let old = "The fox is quick. It jumps\nover the dog.\n";
+/// let new = "The fox is quick. It\njumps over the lazy dog.\n";
+///
+/// assert_eq!(
+///     format!("{}", SentenceDiff::new(old, new).draw(&theme)),
+///     "< left / > right
+///  The fox is quick.
+/// <It jumps over the dog.
+/// >It jumps over the lazy dog.
+/// "
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentenceDiff {
+    old: String,
+    new: String,
+}
+
+impl SentenceDiff {
+    /// Reflow two pieces of text into sentences, ready to be diffed
+    #[must_use]
+    pub fn new(old: &str, new: &str) -> Self {
+        Self {
+            old: sentences(old),
+            new: sentences(new),
+        }
+    }
+
+    /// The old text, one sentence per line
+    #[must_use]
+    pub fn old_text(&self) -> &str {
+        &self.old
+    }
+
+    /// The new text, one sentence per line
+    #[must_use]
+    pub fn new_text(&self) -> &str {
+        &self.new
+    }
+
+    /// Draw the diff of the sentences
+    #[must_use]
+    pub fn draw<'a>(&'a self, theme: &'a dyn Theme) -> DrawDiff<'a> {
+        DrawDiff::new(&self.old, &self.new, theme)
+    }
+}
+
+/// Put each sentence of some text on a line of its own, with a blank line
+/// between paragraphs
+fn sentences(text: &str) -> String {
+    let mut reflowed = String::with_capacity(text.len());
+
+    for paragraph in paragraphs(text) {
+        if !reflowed.is_empty() {
+            reflowed.push('\n');
+        }
+
+        let mut words = paragraph.split_whitespace().peekable();
+        while let Some(word) = words.next() {
+            reflowed.push_str(word);
+            if ends_sentence(word) || words.peek().is_none() {
+                reflowed.push('\n');
+            } else {
+                reflowed.push(' ');
+            }
+        }
+    }
+
+    reflowed
+}
+
+/// The paragraphs of some text, which are separated by blank lines
+fn paragraphs(text: &str) -> impl Iterator<Item = String> + '_ {
+    let mut lines = text.lines().peekable();
+
+    std::iter::from_fn(move || {
+        while lines.peek()?.trim().is_empty() {
+            lines.next();
+        }
+
+        let mut paragraph = String::new();
+        while let Some(line) = lines.next_if(|line| !line.trim().is_empty()) {
+            paragraph.push_str(line);
+            paragraph.push('\n');
+        }
+        Some(paragraph)
+    })
+}
+
+/// Whether a word is the last in its sentence, ignoring any closing quotes
+/// or brackets after the punctuation
+fn ends_sentence(word: &str) -> bool {
+    word.trim_end_matches(['"', '\'', ')', ']', '’', '”'])
+        .ends_with(['.', '!', '?'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sentences;
+
+    #[test]
+    fn sentences_and_paragraphs_are_kept_apart() {
+        assert_eq!(
+            sentences("One. Two (\"three!\")\nfour?\n\n\n  Five\nsix"),
+            "One.\nTwo (\"three!\")\nfour?\n\nFive six\n"
+        );
+    }
+}
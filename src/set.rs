@@ -0,0 +1,423 @@
+use std::{
+    borrow::Cow,
+    fmt::{Debug, Display, Formatter, Write},
+};
+
+use super::{
+    draw_diff::{DrawDiff, DrawOptions},
+    framed::Framed,
+    result::DiffResult,
+    stat::DiffStatRenderer,
+    themes::Theme,
+};
+
+/// Draws the diffs of several named pieces of text one after the other,
+/// followed by a summary of them all
+///
+/// Every diff uses the same theme and options.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DiffSet, DrawDiff, Header};
+/// let theme = ArrowsTheme::default();
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DiffSet::new(&theme)
+///             .with_entry("a.toml", "x = 1\n", "x = 2\n")
+///             .with_entry("b.toml", "y = 1\n", "y = 1\n")
+///             .with_options(|diff| diff.with_header(Header::Never))
+///     ),
+///     "=== a.toml
+/// <x = 1
+/// >x = 2
+/// === b.toml
+///  y = 1
+///  a.toml | 2 +-
+///  b.toml | 0
+///  2 files changed, 1 insertion(+), 1 deletion(-)
+/// "
+/// );
+/// ```
+pub struct DiffSet<'a> {
+    theme: &'a dyn Theme,
+    entries: Vec<(&'a str, &'a str, &'a str)>,
+    removed: Vec<(&'a str, &'a str)>,
+    added: Vec<(&'a str, &'a str)>,
+    rename_similarity: Option<f64>,
+    groups: Vec<(&'a str, DiffSet<'a>)>,
+    collapse_above: Option<usize>,
+    options: DrawOptions<'a>,
+}
+
+impl<'a> DiffSet<'a> {
+    /// Make a new set of diffs, with no entries
+    #[must_use]
+    pub fn new(theme: &'a dyn Theme) -> Self {
+        Self {
+            theme,
+            entries: vec![],
+            removed: vec![],
+            added: vec![],
+            rename_similarity: None,
+            groups: vec![],
+            collapse_above: None,
+            options: Box::new(|diff| diff),
+        }
+    }
+
+    /// Add a named diff to the set
+    #[must_use]
+    pub fn with_entry(mut self, label: &'a str, old: &'a str, new: &'a str) -> Self {
+        self.entries.push((label, old, new));
+        self
+    }
+
+    /// Add a named piece of text that was removed, like a deleted file
+    #[must_use]
+    pub fn with_removed(mut self, label: &'a str, old: &'a str) -> Self {
+        self.removed.push((label, old));
+        self
+    }
+
+    /// Add a named piece of text that was added, like a new file
+    #[must_use]
+    pub fn with_added(mut self, label: &'a str, new: &'a str) -> Self {
+        self.added.push((label, new));
+        self
+    }
+
+    /// Treat a removed and an added entry as one renamed entry when their
+    /// contents are at least this similar, from 0 to 1
+    ///
+    /// The most similar pairs are matched first. Renamed entries are drawn
+    /// under the theme's [`renamed_header`](Theme::renamed_header) after the
+    /// other entries, followed by the removed and added entries that weren't
+    /// matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffSet, Header};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DiffSet::new(&theme)
+    ///             .with_removed("old.txt", "a\nb\nc\nd\ne\n")
+    ///             .with_added("new.txt", "a\nb\nc\nd\nE\n")
+    ///             .with_added("other.txt", "x\n")
+    ///             .with_rename_detection(0.5)
+    ///             .with_options(|diff| diff.with_header(Header::Never).with_context(1))
+    ///     ),
+    ///     "renamed: old.txt → new.txt (80% similar)
+    ///  d
+    /// <e
+    /// >E
+    /// === other.txt
+    /// >x
+    ///  old.txt → new.txt | 2 +-
+    ///  other.txt         | 1 +
+    ///  2 files changed, 2 insertions(+), 1 deletion(-)
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_rename_detection(mut self, min_similarity: f64) -> Self {
+        self.rename_similarity = Some(min_similarity);
+        self
+    }
+
+    /// Add a named set of diffs inside this one, like a directory, drawn
+    /// indented after the other entries
+    ///
+    /// The group keeps its own theme, options and
+    /// [collapse threshold](DiffSet::with_collapse_above), and is one entry
+    /// in this set's summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffSet, Header};
+    /// let theme = ArrowsTheme::default();
+    /// let src = DiffSet::new(&theme)
+    ///     .with_entry("lib.rs", "a\n", "b\n")
+    ///     .with_options(|diff| diff.with_header(Header::Never));
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DiffSet::new(&theme)
+    ///             .with_entry("README.md", "x\n", "x\ny\n")
+    ///             .with_group("src", src)
+    ///             .with_options(|diff| diff.with_header(Header::Never))
+    ///     ),
+    ///     "=== README.md
+    ///  x
+    /// >y
+    /// === src
+    ///   === lib.rs
+    ///   <a
+    ///   >b
+    ///    lib.rs | 2 +-
+    ///    1 file changed, 1 insertion(+), 1 deletion(-)
+    ///  README.md | 1 +
+    ///  src       | 2 +-
+    ///  2 files changed, 2 insertions(+), 1 deletion(-)
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_group(mut self, label: &'a str, group: DiffSet<'a>) -> Self {
+        self.groups.push((label, group));
+        self
+    }
+
+    /// Only draw the summary of the set, and not each diff, when more than
+    /// this many lines were added and removed in it altogether
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffSet};
+    /// let theme = ArrowsTheme::default();
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DiffSet::new(&theme)
+    ///             .with_entry("big.txt", "a\nb\n", "c\nd\n")
+    ///             .with_collapse_above(3)
+    ///     ),
+    ///     " big.txt | 4 ++--\n 1 file changed, 2 insertions(+), 2 deletions(-)\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_collapse_above(mut self, lines: usize) -> Self {
+        self.collapse_above = Some(lines);
+        self
+    }
+
+    /// Set the options for every diff, by calling `with_` methods on each
+    /// [`DrawDiff`] before it is drawn
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DiffSet, Header};
+    /// let theme = ArrowsTheme::default();
+    /// let context = 0;
+    ///
+    /// assert_eq!(
+    ///     format!(
+    ///         "{}",
+    ///         DiffSet::new(&theme)
+    ///             .with_entry("a.txt", "a\nb\n", "a\nc\n")
+    ///             .with_options(move |diff| diff.with_header(Header::Never).with_context(context))
+    ///     ),
+    ///     "=== a.txt
+    /// @@ line 2 @@
+    /// <b
+    /// >c
+    ///  a.txt | 2 +-
+    ///  1 file changed, 1 insertion(+), 1 deletion(-)
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_options(mut self, options: impl Fn(DrawDiff<'_>) -> DrawDiff<'_> + 'a) -> Self {
+        self.options = Box::new(options);
+        self
+    }
+
+    /// The removed and added entries that are renames, by their indexes,
+    /// with how similar they are
+    fn renames(&self) -> Vec<(usize, usize, f64)> {
+        let Some(min_similarity) = self.rename_similarity else {
+            return vec![];
+        };
+        let mut candidates = vec![];
+        for (removed, (_, old)) in self.removed.iter().enumerate() {
+            for (added, (_, new)) in self.added.iter().enumerate() {
+                let similarity = DiffResult::new(old, new).similarity();
+                if similarity >= min_similarity {
+                    candidates.push((removed, added, similarity));
+                }
+            }
+        }
+        candidates.sort_by(|(_, _, left), (_, _, right)| right.total_cmp(left));
+
+        let mut renames: Vec<(usize, usize, f64)> = vec![];
+        for (removed, added, similarity) in candidates {
+            if renames.iter().all(|(other_removed, other_added, _)| {
+                *other_removed != removed && *other_added != added
+            }) {
+                renames.push((removed, added, similarity));
+            }
+        }
+        renames
+    }
+
+    /// The header, label and diff of each entry, with renames matched up
+    fn results(&self) -> Vec<(Cow<'a, str>, Cow<'a, str>, DiffResult<'a>)> {
+        let renames = self.renames();
+        let mut results: Vec<(Cow<'a, str>, Cow<'a, str>, DiffResult<'a>)> = self
+            .entries
+            .iter()
+            .map(|(label, old, new)| {
+                let header = self.theme.entry_header(label);
+                (header, Cow::Borrowed(*label), DiffResult::new(old, new))
+            })
+            .collect();
+
+        for (removed, added, similarity) in &renames {
+            let ((old_label, old), (new_label, new)) = (self.removed[*removed], self.added[*added]);
+            results.push((
+                self.theme.renamed_header(old_label, new_label, *similarity),
+                format!("{old_label} → {new_label}").into(),
+                DiffResult::new(old, new),
+            ));
+        }
+        for (index, (label, old)) in self.removed.iter().enumerate() {
+            if renames.iter().all(|(removed, _, _)| *removed != index) {
+                results.push((
+                    self.theme.entry_header(label),
+                    Cow::Borrowed(*label),
+                    DiffResult::new(old, ""),
+                ));
+            }
+        }
+        for (index, (label, new)) in self.added.iter().enumerate() {
+            if renames.iter().all(|(_, added, _)| *added != index) {
+                results.push((
+                    self.theme.entry_header(label),
+                    Cow::Borrowed(*label),
+                    DiffResult::new("", new),
+                ));
+            }
+        }
+
+        results
+    }
+
+    /// The diffs of the set and of each of its groups, worked out once so
+    /// they can be both drawn and added up
+    fn diffs(&self) -> Diffs<'_, 'a> {
+        let results = self.results();
+        let groups: Vec<(&'a str, Diffs<'_, 'a>)> = self
+            .groups
+            .iter()
+            .map(|(label, group)| (*label, group.diffs()))
+            .collect();
+        let totals = results
+            .iter()
+            .map(|(_, _, result)| (result.insertions(), result.deletions()))
+            .chain(groups.iter().map(|(_, group)| group.totals))
+            .fold((0, 0), |(insertions, deletions), (added, removed)| {
+                (insertions + added, deletions + removed)
+            });
+
+        Diffs {
+            set: self,
+            results,
+            groups,
+            totals,
+        }
+    }
+}
+
+impl Debug for DiffSet<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiffSet")
+            .field("theme", &self.theme)
+            .field("entries", &self.entries)
+            .field("removed", &self.removed)
+            .field("added", &self.added)
+            .field("rename_similarity", &self.rename_similarity)
+            .field("groups", &self.groups)
+            .field("collapse_above", &self.collapse_above)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Display for DiffSet<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.diffs())
+    }
+}
+
+/// The diffs of a set, its groups and theirs, with how many lines were added
+/// and removed in it altogether
+struct Diffs<'set, 'a> {
+    set: &'set DiffSet<'a>,
+    results: Vec<(Cow<'a, str>, Cow<'a, str>, DiffResult<'a>)>,
+    groups: Vec<(&'a str, Diffs<'set, 'a>)>,
+    totals: (usize, usize),
+}
+
+impl Display for Diffs<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Diffs { set, results, .. } = self;
+        let (insertions, deletions) = self.totals;
+        let collapsed = set
+            .collapse_above
+            .is_some_and(|lines| insertions + deletions > lines);
+
+        let mut stat = DiffStatRenderer::new(set.theme);
+        for (header, label, result) in results {
+            if !collapsed {
+                write!(f, "{header}")?;
+                write!(f, "{}", (set.options)(result.draw(set.theme)))?;
+            }
+            stat = stat.with_entry(label, result);
+        }
+        for (label, group) in &self.groups {
+            if !collapsed {
+                write!(f, "{}", set.theme.entry_header(label))?;
+                write!(Framed::new(f, "  ", None), "{group}")?;
+            }
+            let (insertions, deletions) = group.totals;
+            stat = stat.with_counts(label, insertions, deletions);
+        }
+
+        write!(f, "{stat}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiffSet;
+    use crate::ArrowsTheme;
+
+    #[test]
+    fn each_entry_is_only_renamed_once() {
+        let theme = ArrowsTheme::default();
+        let set = DiffSet::new(&theme)
+            .with_removed("a", "1\n2\n3\n")
+            .with_removed("b", "1\n2\n4\n")
+            .with_added("c", "1\n2\n3\n")
+            .with_rename_detection(0.5);
+
+        assert_eq!(set.renames(), vec![(0, 0, 1.0)]);
+    }
+
+    #[test]
+    fn groups_add_up_their_own_groups() {
+        let theme = ArrowsTheme::default();
+        let inner = DiffSet::new(&theme).with_entry("c", "1\n", "2\n3\n");
+        let middle = DiffSet::new(&theme)
+            .with_entry("b", "", "1\n")
+            .with_group("inner", inner);
+        let set = DiffSet::new(&theme)
+            .with_entry("a", "1\n", "")
+            .with_group("middle", middle);
+        let diffs = set.diffs();
+
+        assert_eq!(diffs.totals, (3, 2));
+        assert_eq!(diffs.groups[0].1.totals, (3, 1));
+        assert_eq!(diffs.groups[0].1.groups[0].1.totals, (2, 1));
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt::Write;
+
+use crossterm::style::Stylize;
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+use super::{granularity::Granularity, inline::tokens, line_endings::LineEnding, themes::Theme};
+
+/// Draw a diff of two short pieces of text on one line, for putting in a
+/// sentence or a prompt
+///
+/// Words that were removed are crossed out and words that were added are
+/// underlined, inside the theme's styling for deleted and inserted text.
+/// There is no header or prefixes, and newlines are shown with the theme's
+/// [`line_ending_marker`](Theme::line_ending_marker).
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::style::{ContentStyle, Stylize};
+/// use termdiff::{inline, AnsiAssert, ArrowsTheme};
+/// let theme = ArrowsTheme::default();
+/// let output = inline("rename foo", "rename bar", &theme);
+/// let ansi = AnsiAssert::new(&output);
+///
+/// assert_eq!(ansi.plain(), "rename foobar");
+/// ansi.assert_style("foo", ContentStyle::new().crossed_out());
+/// ansi.assert_style("bar", ContentStyle::new().underlined());
+/// ```
+#[must_use]
+pub fn inline(old: &str, new: &str, theme: &dyn Theme) -> String {
+    let old_words = tokens(&[old], Granularity::Word);
+    let new_words = tokens(&[new], Granularity::Word);
+    let mut buffer = String::new();
+
+    for op in capture_diff_slices(Algorithm::Patience, &old_words, &new_words) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        let deleted = one_line(&old_words[old_range], theme);
+        let inserted = one_line(&new_words[new_range], theme);
+
+        let _ = match tag {
+            DiffTag::Equal => write!(buffer, "{}", theme.equal_content(&deleted)),
+            DiffTag::Delete => write!(buffer, "{}", removed(&deleted, theme)),
+            DiffTag::Insert => write!(buffer, "{}", added(&inserted, theme)),
+            DiffTag::Replace => write!(
+                buffer,
+                "{}{}",
+                removed(&deleted, theme),
+                added(&inserted, theme)
+            ),
+        };
+    }
+
+    buffer
+}
+
+/// Words joined back together, with newlines swapped for markers
+fn one_line(words: &[&str], theme: &dyn Theme) -> String {
+    words
+        .iter()
+        .map(|word| match *word {
+            "\n" => theme.line_ending_marker(LineEnding::Lf),
+            word => word.into(),
+        })
+        .collect()
+}
+
+fn removed(text: &str, theme: &dyn Theme) -> String {
+    theme
+        .delete_content(&text.crossed_out().to_string())
+        .into_owned()
+}
+
+fn added(text: &str, theme: &dyn Theme) -> String {
+    theme
+        .insert_line(&text.underlined().to_string())
+        .into_owned()
+}
@@ -0,0 +1,220 @@
+use std::fmt::{Display, Formatter};
+
+use super::{
+    result::DiffResult,
+    themes::Theme,
+    width::{display_width, pad},
+};
+
+/// Draws a summary of how many lines changed in each of a set of diffs, like
+/// `git diff --stat`
+///
+/// Bars longer than the maximum width are scaled down, keeping the
+/// proportion of insertions to deletions.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DiffResult, DiffStatRenderer};
+/// let theme = ArrowsTheme::default();
+/// let config = DiffResult::new("a\nb\nc\n", "a\nB\nC\nd\n");
+/// let readme = DiffResult::new("hello\n", "hello\nworld\n");
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DiffStatRenderer::new(&theme)
+///             .with_entry("config.toml", &config)
+///             .with_entry("README.md", &readme)
+///     ),
+///     " config.toml | 5 +++--
+///  README.md   | 1 +
+///  2 files changed, 4 insertions(+), 2 deletions(-)
+/// "
+/// );
+/// ```
+#[derive(Debug)]
+pub struct DiffStatRenderer<'a> {
+    theme: &'a dyn Theme,
+    entries: Vec<(&'a str, usize, usize)>,
+    max_width: usize,
+}
+
+impl<'a> DiffStatRenderer<'a> {
+    /// Make a new diff stat renderer, with no entries
+    #[must_use]
+    pub fn new(theme: &'a dyn Theme) -> Self {
+        Self {
+            theme,
+            entries: vec![],
+            max_width: 40,
+        }
+    }
+
+    /// Add a named diff to the summary
+    #[must_use]
+    pub fn with_entry(mut self, name: &'a str, result: &DiffResult<'_>) -> Self {
+        self.entries
+            .push((name, result.insertions(), result.deletions()));
+        self
+    }
+
+    /// Add a named entry to the summary, by how many lines it added and
+    /// removed
+    pub(crate) fn with_counts(
+        mut self,
+        name: &'a str,
+        insertions: usize,
+        deletions: usize,
+    ) -> Self {
+        self.entries.push((name, insertions, deletions));
+        self
+    }
+
+    /// The longest a bar can be, 40 by default
+    #[must_use]
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width.max(1);
+        self
+    }
+
+    /// Scale a count to fit the bar into the maximum width
+    fn scale(&self, count: usize, largest: usize) -> usize {
+        if largest <= self.max_width || count == 0 {
+            count
+        } else {
+            (count * self.max_width / largest).max(1)
+        }
+    }
+
+    /// How long the insertion and deletion parts of a bar are, never longer
+    /// together than the maximum width
+    ///
+    /// Rounding each part up to at least one can take the bar past the
+    /// maximum, so the part for the smaller count gives way first.
+    fn bars(&self, insertions: usize, deletions: usize, largest: usize) -> (usize, usize) {
+        let mut plus = self.scale(insertions, largest);
+        let mut minus = self.scale(deletions, largest);
+        let excess = (plus + minus).saturating_sub(self.max_width);
+
+        let (smaller, larger) = if insertions < deletions {
+            (&mut plus, &mut minus)
+        } else {
+            (&mut minus, &mut plus)
+        };
+        let taken = excess.min(*smaller);
+        *smaller -= taken;
+        *larger -= excess - taken;
+
+        (plus, minus)
+    }
+}
+
+impl Display for DiffStatRenderer<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name_width = self
+            .entries
+            .iter()
+            .map(|(name, _, _)| display_width(name))
+            .max()
+            .unwrap_or_default();
+        let largest = self
+            .entries
+            .iter()
+            .map(|(_, insertions, deletions)| insertions + deletions)
+            .max()
+            .unwrap_or_default();
+        let count_width = largest.to_string().len();
+
+        for (name, insertions, deletions) in &self.entries {
+            let (plus, minus) = self.bars(*insertions, *deletions, largest);
+            let plus = "+".repeat(plus);
+            let minus = "-".repeat(minus);
+
+            write!(
+                f,
+                " {} | {:>count_width$}",
+                pad(name, name_width),
+                insertions + deletions
+            )?;
+            if !plus.is_empty() || !minus.is_empty() {
+                write!(f, " ")?;
+            }
+            if !plus.is_empty() {
+                write!(f, "{}", self.theme.insert_line(&plus))?;
+            }
+            if !minus.is_empty() {
+                write!(f, "{}", self.theme.delete_content(&minus))?;
+            }
+            writeln!(f)?;
+        }
+
+        let insertions: usize = self
+            .entries
+            .iter()
+            .map(|(_, insertions, _)| insertions)
+            .sum();
+        let deletions: usize = self.entries.iter().map(|(_, _, deletions)| deletions).sum();
+        writeln!(
+            f,
+            " {} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+            self.entries.len(),
+            plural(self.entries.len()),
+            insertions,
+            plural(insertions),
+            deletions,
+            plural(deletions)
+        )
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiffStatRenderer;
+    use crate::{ArrowsTheme, DiffResult};
+
+    #[test]
+    fn long_bars_are_scaled_down() {
+        let theme = ArrowsTheme::default();
+        let old = "a\n".repeat(30);
+        let new = "b\n".repeat(90);
+        let result = DiffResult::new(&old, &new);
+
+        assert_eq!(
+            format!(
+                "{}",
+                DiffStatRenderer::new(&theme)
+                    .with_max_width(12)
+                    .with_entry("big", &result)
+            ),
+            format!(
+                " big | 120 {}{}\n 1 file changed, 90 insertions(+), 30 deletions(-)\n",
+                "+".repeat(9),
+                "-".repeat(3)
+            )
+        );
+    }
+
+    #[test]
+    fn bars_never_go_past_the_maximum_width() {
+        let theme = ArrowsTheme::default();
+        let renderer = DiffStatRenderer::new(&theme).with_max_width(1);
+
+        assert_eq!(renderer.bars(1, 100, 101), (0, 1));
+        assert_eq!(renderer.bars(100, 1, 101), (1, 0));
+        assert_eq!(
+            DiffStatRenderer::new(&theme)
+                .with_max_width(3)
+                .bars(1, 1000, 1001),
+            (1, 2)
+        );
+    }
+}
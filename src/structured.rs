@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::themes::{prefix_column, Theme};
+
+/// Draw the differences between the values in two YAML documents
+///
+/// The documents are parsed and compared value by value, so changes to
+/// formatting, comments or the order of keys don't show up. Each change is
+/// drawn on a line of its own with the path to the value, like
+/// `server.port: 80 → 8080`. Items in sequences are given by their index,
+/// and strings are quoted so a change of type shows up.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_yaml, ArrowsTheme};
+/// let theme = ArrowsTheme::default();
+/// let old = "server:\n  host: example.com\n  port: 80\nusers: [a, b]\n";
+/// let new = "users: [a]\nserver: {port: 8080, host: example.com, tls: true}\n";
+///
+/// assert_eq!(
+///     diff_yaml(old, new, &theme).unwrap(),
+///     "< left / > right
+///  server.port: 80 → 8080
+/// >server.tls: true
+/// <users[1]: \"b\"
+/// "
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors if either document isn't valid YAML.
+#[cfg(feature = "yaml")]
+pub fn diff_yaml(old: &str, new: &str, theme: &dyn Theme) -> Result<String, serde_yaml::Error> {
+    let old: serde_yaml::Value = serde_yaml::from_str(old)?;
+    let new: serde_yaml::Value = serde_yaml::from_str(new)?;
+
+    Ok(draw_paths(
+        &yaml_values::flatten(&old),
+        &yaml_values::flatten(&new),
+        theme,
+    ))
+}
+
+/// Draw the differences between the values in two TOML documents
+///
+/// The documents are parsed and compared value by value, so changes to
+/// formatting, comments or the order of keys don't show up. Each change is
+/// drawn on a line of its own with the path to the value, like
+/// `server.port: 80 → 8080`. Items in arrays are given by their index, and
+/// strings are quoted so a change of type shows up.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{diff_toml, ArrowsTheme};
+/// let theme = ArrowsTheme::default();
+/// let old = "[server]\nhost = \"example.com\"\nport = 80\n";
+/// let new = "server = { port = 8080, host = \"example.com\" }\n";
+///
+/// assert_eq!(
+///     diff_toml(old, new, &theme).unwrap(),
+///     "< left / > right\n server.port: 80 → 8080\n"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors if either document isn't valid TOML.
+#[cfg(feature = "toml")]
+pub fn diff_toml(old: &str, new: &str, theme: &dyn Theme) -> Result<String, toml::de::Error> {
+    let old: toml::Value = toml::from_str(old)?;
+    let new: toml::Value = toml::from_str(new)?;
+
+    Ok(draw_paths(
+        &toml_values::flatten(&old),
+        &toml_values::flatten(&new),
+        theme,
+    ))
+}
+
+/// A step on the path to a value
+///
+/// Paths sort step by step, so items in a sequence stay in order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Every value in a document that isn't a mapping or sequence, by its path,
+/// written out for display
+type Values = BTreeMap<Vec<Segment>, String>;
+
+/// A path written out, joining keys with dots and giving indexes in
+/// brackets
+fn path_text(path: &[Segment]) -> String {
+    let mut text = String::new();
+    for segment in path {
+        let _ = match segment {
+            Segment::Key(key) if text.is_empty() => write!(text, "{key}"),
+            Segment::Key(key) => write!(text, ".{key}"),
+            Segment::Index(index) => write!(text, "[{index}]"),
+        };
+    }
+    text
+}
+
+/// The path to a value in a map or sequence
+fn child(path: &[Segment], segment: Segment) -> Vec<Segment> {
+    let mut child = path.to_vec();
+    child.push(segment);
+    child
+}
+
+/// Draw the values that were removed, added or changed, by their paths
+///
+/// Removed values are drawn as deleted lines, added values as inserted
+/// lines, and changed values as one line with both the old and new value.
+fn draw_paths(old: &Values, new: &Values, theme: &dyn Theme) -> String {
+    let mut paths: Vec<&Vec<Segment>> = old.keys().chain(new.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let prefix_width = theme.prefix_width();
+    let mut buffer = theme.header().into_owned();
+    for path in paths {
+        let (values, path) = ((old.get(path), new.get(path)), path_text(path));
+        let _ = match values {
+            (Some(old_value), Some(new_value)) if old_value == new_value => continue,
+            (Some(old_value), Some(new_value)) => write!(
+                buffer,
+                "{}{}{}{}{}",
+                prefix_column(theme, theme.equal_prefix(), prefix_width),
+                theme.equal_content(&format!("{path}: ")),
+                theme.delete_content(&theme.highlight_delete(old_value)),
+                theme.equal_content(" → "),
+                theme.insert_line(&theme.highlight_insert(new_value))
+            ),
+            (Some(old_value), None) => write!(
+                buffer,
+                "{}{}",
+                prefix_column(theme, theme.delete_prefix(), prefix_width),
+                theme.delete_content(&format!("{path}: {old_value}"))
+            ),
+            (None, Some(new_value)) => write!(
+                buffer,
+                "{}{}",
+                prefix_column(theme, theme.insert_prefix(), prefix_width),
+                theme.insert_line(&format!("{path}: {new_value}"))
+            ),
+            (None, None) => continue,
+        };
+        buffer.push_str(&theme.line_end());
+    }
+
+    buffer
+}
+
+#[cfg(feature = "yaml")]
+mod yaml_values {
+    use serde_yaml::Value;
+
+    use super::{child, Segment, Values};
+
+    /// Every value in a document that isn't a mapping or sequence, by its
+    /// path
+    pub(super) fn flatten(value: &Value) -> Values {
+        let mut values = Values::new();
+        visit(&mut values, &[], value);
+        values
+    }
+
+    fn visit(values: &mut Values, path: &[Segment], value: &Value) {
+        match value {
+            Value::Mapping(mapping) if !mapping.is_empty() => {
+                for (key, value) in mapping {
+                    let key = match key {
+                        Value::String(key) => key.clone(),
+                        key => scalar(key),
+                    };
+                    visit(values, &child(path, Segment::Key(key)), value);
+                }
+            }
+            Value::Sequence(sequence) if !sequence.is_empty() => {
+                for (index, value) in sequence.iter().enumerate() {
+                    visit(values, &child(path, Segment::Index(index)), value);
+                }
+            }
+            Value::Tagged(tagged) => visit(values, path, &tagged.value),
+            value => {
+                values.insert(path.to_vec(), scalar(value));
+            }
+        }
+    }
+
+    fn scalar(value: &Value) -> String {
+        match value {
+            Value::Null => "null".to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Number(value) => value.to_string(),
+            Value::String(value) => format!("{value:?}"),
+            Value::Sequence(_) => "[]".to_string(),
+            Value::Mapping(_) => "{}".to_string(),
+            Value::Tagged(tagged) => format!("{} {}", tagged.tag, scalar(&tagged.value)),
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+mod toml_values {
+    use toml::Value;
+
+    use super::{child, Segment, Values};
+
+    /// Every value in a document that isn't a table or array, by its path
+    pub(super) fn flatten(value: &Value) -> Values {
+        let mut values = Values::new();
+        visit(&mut values, &[], value);
+        values
+    }
+
+    fn visit(values: &mut Values, path: &[Segment], value: &Value) {
+        match value {
+            Value::Table(table) if !table.is_empty() => {
+                for (key, value) in table {
+                    visit(values, &child(path, Segment::Key(key.clone())), value);
+                }
+            }
+            Value::Array(array) if !array.is_empty() => {
+                for (index, value) in array.iter().enumerate() {
+                    visit(values, &child(path, Segment::Index(index)), value);
+                }
+            }
+            Value::Table(_) => {
+                values.insert(path.to_vec(), "{}".to_string());
+            }
+            value => {
+                values.insert(path.to_vec(), value.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_paths, Segment, Values};
+    use crate::SnapshotTheme;
+
+    fn values(values: &[(&[Segment], &str)]) -> Values {
+        values
+            .iter()
+            .map(|(path, value)| (path.to_vec(), value.to_string()))
+            .collect()
+    }
+
+    fn key(key: &str) -> Segment {
+        Segment::Key(key.to_string())
+    }
+
+    #[test]
+    fn only_values_that_differ_are_drawn() {
+        let old = values(&[(&[key("a")], "1"), (&[key("b")], "2"), (&[key("c")], "3")]);
+        let new = values(&[(&[key("b")], "2"), (&[key("c")], "4"), (&[key("d")], "5")]);
+
+        assert_eq!(
+            draw_paths(&old, &new, &SnapshotTheme::default()),
+            "--- old\n+++ new\n-a: 1\n c: [-3-] → {+4+}\n+d: 5\n"
+        );
+    }
+
+    #[test]
+    fn items_are_in_index_order() {
+        let items = |indexes: &[usize]| {
+            let paths: Vec<Vec<Segment>> = indexes
+                .iter()
+                .map(|index| vec![key("a"), Segment::Index(*index)])
+                .collect();
+            paths
+                .into_iter()
+                .map(|path| (path, "x".to_string()))
+                .collect::<Values>()
+        };
+
+        assert_eq!(
+            draw_paths(&items(&[]), &items(&[2, 10]), &SnapshotTheme::default()),
+            "--- old\n+++ new\n+a[2]: x\n+a[10]: x\n"
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn changes_of_type_are_drawn() {
+        assert_eq!(
+            crate::diff_toml("port = \"80\"\n", "port = 80\n", &SnapshotTheme::default()).unwrap(),
+            "--- old\n+++ new\n port: [-\"80\"-] → {+80+}\n"
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn changes_of_type_are_drawn_in_yaml() {
+        assert_eq!(
+            crate::diff_yaml("port: \"80\"\n", "port: 80\n", &SnapshotTheme::default()).unwrap(),
+            "--- old\n+++ new\n port: [-\"80\"-] → {+80+}\n"
+        );
+    }
+}
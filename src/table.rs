@@ -0,0 +1,154 @@
+use csv::ReaderBuilder;
+
+use super::{
+    draw_diff::DrawDiff,
+    themes::Theme,
+    width::{display_width, pad},
+};
+
+/// A diff of two tables of comma or tab separated values, compared a row at
+/// a time
+///
+/// Both tables are laid out with the same column widths, so a value that
+/// makes a column wider doesn't change every other row. Rows that changed
+/// have the cells that differ emphasized.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, TableDiff};
+/// let theme = ArrowsTheme::default();
+/// let old = "name,port\nweb,80\ndb,5432\n";
+/// let new = "name,port\nweb,8080\ndb,5432\n";
+/// let table = TableDiff::csv(old, new).unwrap();
+///
+/// assert_eq!(
+///     format!("{}", table.draw(&theme)),
+///     "< left / > right
+///  name | port
+/// <web  | 80
+/// >web  | 8080
+///  db   | 5432
+/// "
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableDiff {
+    old: String,
+    new: String,
+}
+
+impl TableDiff {
+    /// Parse two tables with the given delimiter between values
+    ///
+    /// Rows may have different numbers of values.
+    ///
+    /// # Errors
+    ///
+    /// Errors if either table isn't valid, such as having a quote that is
+    /// never closed.
+    pub fn new(old: &str, new: &str, delimiter: u8) -> Result<Self, csv::Error> {
+        let old = parse(old, delimiter)?;
+        let new = parse(new, delimiter)?;
+        let widths = widths(old.iter().chain(&new));
+
+        Ok(Self {
+            old: layout(&old, &widths),
+            new: layout(&new, &widths),
+        })
+    }
+
+    /// Parse two tables of comma separated values
+    ///
+    /// # Errors
+    ///
+    /// Errors if either table isn't valid CSV.
+    pub fn csv(old: &str, new: &str) -> Result<Self, csv::Error> {
+        Self::new(old, new, b',')
+    }
+
+    /// Parse two tables of tab separated values
+    ///
+    /// # Errors
+    ///
+    /// Errors if either table isn't valid TSV.
+    pub fn tsv(old: &str, new: &str) -> Result<Self, csv::Error> {
+        Self::new(old, new, b'\t')
+    }
+
+    /// The old table, laid out in columns
+    #[must_use]
+    pub fn old_text(&self) -> &str {
+        &self.old
+    }
+
+    /// The new table, laid out in columns
+    #[must_use]
+    pub fn new_text(&self) -> &str {
+        &self.new
+    }
+
+    /// Draw the diff of the tables
+    #[must_use]
+    pub fn draw<'a>(&'a self, theme: &'a dyn Theme) -> DrawDiff<'a> {
+        DrawDiff::new(&self.old, &self.new, theme).with_minimal_inline(true)
+    }
+}
+
+fn parse(table: &str, delimiter: u8) -> Result<Vec<Vec<String>>, csv::Error> {
+    ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(table.as_bytes())
+        .records()
+        .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+        .collect()
+}
+
+/// The widest value in each column, in columns of the terminal
+fn widths<'a>(rows: impl Iterator<Item = &'a Vec<String>>) -> Vec<usize> {
+    let mut widths: Vec<usize> = vec![];
+
+    for row in rows {
+        for (column, value) in row.iter().enumerate() {
+            let width = display_width(value);
+            match widths.get_mut(column) {
+                Some(widest) => *widest = (*widest).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+
+    widths
+}
+
+/// Lay rows out with each column padded to its width, one row per line
+fn layout(rows: &[Vec<String>], widths: &[usize]) -> String {
+    let mut text = String::new();
+
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(value, width)| pad(value, *width))
+            .collect();
+        text.push_str(cells.join(" | ").trim_end());
+        text.push('\n');
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TableDiff;
+
+    #[test]
+    fn columns_are_as_wide_as_either_table_needs() {
+        let table = TableDiff::tsv("a\tb\nccc\td\n", "a\t\"b\tb\"\n").unwrap();
+
+        assert_eq!(table.old_text(), "a   | b\nccc | d\n");
+        assert_eq!(table.new_text(), "a   | b\tb\n");
+    }
+}
@@ -0,0 +1,232 @@
+use super::{
+    change::ChangeTag,
+    line_endings::split_ending,
+    ops::{DiffOp, OpTransform},
+};
+
+/// Treat the old text as a template, where a placeholder matches any text
+///
+/// Changed lines in the new text that match the template line they replace
+/// are drawn as equal, so golden files can leave out values that change on
+/// every run, like timestamps or ids. The placeholder is `{{any}}` unless
+/// another is given, and matches any run of characters within a line,
+/// including none. The line endings still need to be the same.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff, Template};
+/// let theme = ArrowsTheme::default();
+/// let template = Template::default();
+/// let actual = format!(
+///     "{}",
+///     DrawDiff::new(
+///         "id: {{any}}\nname: a\n",
+///         "id: 4f3a\nname: b\n",
+///         &theme
+///     )
+///     .with_transform(&template)
+/// );
+///
+/// assert_eq!(
+///     actual,
+///     "< left / > right\n id: {{any}}\n<name: a\n>name: b\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Template<'a> {
+    placeholder: &'a str,
+}
+
+impl Default for Template<'_> {
+    fn default() -> Self {
+        Self {
+            placeholder: "{{any}}",
+        }
+    }
+}
+
+impl<'a> Template<'a> {
+    /// Use a different placeholder
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{ArrowsTheme, DrawDiff, Template};
+    /// let theme = ArrowsTheme::default();
+    /// let template = Template::default().with_placeholder("***");
+    /// let actual = format!(
+    ///     "{}",
+    ///     DrawDiff::new("took ***ms\n", "took 12ms\n", &theme).with_transform(&template)
+    /// );
+    ///
+    /// assert_eq!(actual, "< left / > right\n took ***ms\n");
+    /// ```
+    #[must_use]
+    pub fn with_placeholder(mut self, placeholder: &'a str) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// The line matches the template line
+    fn matches(&self, template: &str, line: &str) -> bool {
+        let (template, template_ending) = split_ending(template);
+        let (line, line_ending) = split_ending(line);
+
+        if template_ending != line_ending {
+            return false;
+        }
+
+        if self.placeholder.is_empty() {
+            return template == line;
+        }
+
+        let mut pieces = template.split(self.placeholder);
+        let first = pieces.next().unwrap_or_default();
+        let Some(mut rest) = line.strip_prefix(first) else {
+            return false;
+        };
+        let mut pieces: Vec<&str> = pieces.collect();
+        let Some(last) = pieces.pop() else {
+            return rest.is_empty();
+        };
+
+        for piece in pieces {
+            match rest.find(piece) {
+                Some(index) => rest = &rest[index + piece.len()..],
+                None => return false,
+            }
+        }
+
+        rest.ends_with(last)
+    }
+
+    /// Line up a block of deleted template lines with the inserted lines
+    /// that replace them, making each pair that matches equal
+    fn align(
+        &self,
+        old: &[&str],
+        new: &[&str],
+        deleted: &DiffOp,
+        inserted: &DiffOp,
+    ) -> Vec<DiffOp> {
+        let old_range = deleted.old_range();
+        let new_range = inserted.new_range();
+        let (rows, columns) = (old_range.len(), new_range.len());
+
+        let mut longest = vec![vec![0_usize; columns + 1]; rows + 1];
+        for row in (0..rows).rev() {
+            for column in (0..columns).rev() {
+                longest[row][column] =
+                    if self.matches(old[old_range.start + row], new[new_range.start + column]) {
+                        longest[row + 1][column + 1] + 1
+                    } else {
+                        longest[row + 1][column].max(longest[row][column + 1])
+                    };
+            }
+        }
+
+        let mut ops = vec![];
+        let (mut row, mut column) = (0, 0);
+        while row < rows || column < columns {
+            let (old_at, new_at) = (old_range.start + row, new_range.start + column);
+
+            if row < rows
+                && column < columns
+                && self.matches(old[old_at], new[new_at])
+                && longest[row][column] == longest[row + 1][column + 1] + 1
+            {
+                ops.push(DiffOp::equal(old_at..old_at + 1, new_at..new_at + 1));
+                row += 1;
+                column += 1;
+            } else if column == columns
+                || (row < rows && longest[row + 1][column] >= longest[row][column + 1])
+            {
+                ops.push(DiffOp::delete(old_at..old_at + 1, new_at));
+                row += 1;
+            } else {
+                ops.push(DiffOp::insert(old_at, new_at..new_at + 1));
+                column += 1;
+            }
+        }
+
+        reorder(ops)
+    }
+}
+
+impl OpTransform for Template<'_> {
+    fn transform(&self, old: &[&str], new: &[&str], ops: Vec<DiffOp>) -> Vec<DiffOp> {
+        let mut transformed = Vec::with_capacity(ops.len());
+        let mut ops = ops.into_iter().peekable();
+
+        while let Some(op) = ops.next() {
+            match ops.peek() {
+                Some(next) if op.tag() == ChangeTag::Delete && next.tag() == ChangeTag::Insert => {
+                    transformed.extend(self.align(old, new, &op, next));
+                    ops.next();
+                }
+                _ => transformed.push(op),
+            }
+        }
+
+        transformed
+    }
+}
+
+/// Put the inserts between two equal lines after the deletes, so they are
+/// drawn as a changed block
+fn reorder(ops: Vec<DiffOp>) -> Vec<DiffOp> {
+    let mut reordered = Vec::with_capacity(ops.len());
+    let mut inserts = vec![];
+
+    for op in ops {
+        match op.tag() {
+            ChangeTag::Insert => inserts.push(op),
+            ChangeTag::Delete => reordered.push(op),
+            _ => {
+                reordered.append(&mut inserts);
+                reordered.push(op);
+            }
+        }
+    }
+
+    reordered.append(&mut inserts);
+    reordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Template;
+    use crate::{DiffOp, OpTransform};
+
+    #[test]
+    fn placeholders_match_any_text_within_a_line() {
+        let template = Template::default();
+
+        assert!(template.matches("{{any}}\n", "anything\n"));
+        assert!(template.matches("a{{any}}b{{any}}c", "abc"));
+        assert!(template.matches("a{{any}}b{{any}}c", "a1b2b3c"));
+        assert!(!template.matches("a{{any}}b", "ab\n"));
+        assert!(!template.matches("a{{any}}b", "a1c"));
+        assert!(!template.matches("a{{any}}a", "a"));
+    }
+
+    #[test]
+    fn lines_that_do_not_match_stay_changed() {
+        let old = ["a\n", "id {{any}}\n", "b\n"];
+        let new = ["x\n", "id 7\n", "y\n", "z\n"];
+        let ops = vec![DiffOp::delete(0..3, 0), DiffOp::insert(3, 0..4)];
+
+        assert_eq!(
+            Template::default().transform(&old, &new, ops),
+            vec![
+                DiffOp::delete(0..1, 0),
+                DiffOp::insert(1, 0..1),
+                DiffOp::equal(1..2, 1..2),
+                DiffOp::delete(2..3, 2),
+                DiffOp::insert(3, 2..3),
+                DiffOp::insert(3, 3..4),
+            ]
+        );
+    }
+}
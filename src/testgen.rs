@@ -0,0 +1,143 @@
+//! Generated pairs of texts for testing and benchmarking diffs
+//!
+//! The same seed and settings always give the same texts, so failures and
+//! benchmark numbers can be reproduced. This is useful for checking your own
+//! [`DiffAlgorithm`](crate::DiffAlgorithm) against the built in ones. Needs
+//! the `testgen` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use termdiff::{testgen::Generator, verify, DiffResult};
+//! let (old, new) = Generator::new(7)
+//!     .with_lines(50)
+//!     .with_change_density(0.2)
+//!     .pair();
+//! let result = DiffResult::new(&old, &new);
+//!
+//! assert_eq!(verify(&old, &new, result.ops()), Ok(()));
+//! assert_eq!(Generator::new(7).with_lines(50).with_change_density(0.2).pair(), (old, new));
+//! ```
+
+use std::{convert::TryFrom, fmt::Write};
+
+const WORDS: [&str; 16] = [
+    "let", "value", "return", "self", "match", "some", "none", "if", "else", "for", "in", "loop",
+    "break", "0", "1", "=",
+];
+
+/// Makes an old text, and a new text with some of its lines changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Generator {
+    seed: u64,
+    lines: usize,
+    density: f64,
+}
+
+impl Generator {
+    /// A generator for 100 lines with one in ten changed
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            lines: 100,
+            density: 0.1,
+        }
+    }
+
+    /// How many lines the old text has
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::testgen::Generator;
+    /// let (old, _) = Generator::new(1).with_lines(3).pair();
+    ///
+    /// assert_eq!(old.lines().count(), 3);
+    /// ```
+    #[must_use]
+    pub fn with_lines(mut self, lines: usize) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// The chance of each line being deleted, replaced, or having a line
+    /// inserted before it, from 0 for no changes to 1 for changes everywhere
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::testgen::Generator;
+    /// let (old, new) = Generator::new(1).with_change_density(0.0).pair();
+    ///
+    /// assert_eq!(old, new);
+    /// ```
+    #[must_use]
+    pub fn with_change_density(mut self, density: f64) -> Self {
+        self.density = density.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The old and the new text
+    #[must_use]
+    pub fn pair(&self) -> (String, String) {
+        let mut rng = Rng(self.seed);
+        let mut old = String::new();
+        let mut new = String::new();
+
+        for index in 0..self.lines {
+            let line = rng.line(index);
+            old.push_str(&line);
+
+            if rng.chance() >= self.density {
+                new.push_str(&line);
+                continue;
+            }
+
+            match rng.below(3) {
+                0 => {}
+                1 => {
+                    new.push_str(&rng.line(index));
+                    new.push_str(&line);
+                }
+                _ => new.push_str(&rng.line(index)),
+            }
+        }
+
+        (old, new)
+    }
+}
+
+/// A small, fast and deterministic random number generator (splitmix64)
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    /// A number from 0 up to but not including 1
+    fn chance(&mut self) -> f64 {
+        let high = u32::try_from(self.next() >> 32).unwrap_or(u32::MAX);
+        f64::from(high) / (f64::from(u32::MAX) + 1.0)
+    }
+
+    /// A line of a few words, with a number so most lines are different
+    fn line(&mut self, number: usize) -> String {
+        let mut line = format!("{number}:");
+        for _ in 0..=self.below(5) {
+            let word = WORDS[usize::try_from(self.below(16)).unwrap_or(0)];
+            let _ = write!(line, " {word}");
+        }
+        line.push('\n');
+        line
+    }
+}
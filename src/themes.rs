@@ -1,7 +1,15 @@
-use std::{borrow::Cow, fmt::Debug};
+//! The themes diffs can be drawn with, and ways to build new ones from them
+
+use std::{borrow::Cow, fmt::Debug, ops::Range};
 
 use crossterm::style::Stylize;
 
+use super::{
+    diff_context::DiffContext, line_endings::LineEnding, line_info::LineInfo, width::display_width,
+};
+
+pub mod ext;
+
 /// A [`Theme`] for the diff
 ///
 /// This is to allows some control over what the diff looks like without having
@@ -46,6 +54,275 @@ pub trait Theme: Debug {
 
     /// A header to put above the diff
     fn header<'this>(&self) -> Cow<'this, str>;
+
+    /// A header to put above the diff, knowing what is being diffed
+    ///
+    /// This is [`header`](Theme::header) unless a theme overrides it.
+    fn header_with_context<'this>(&self, _context: &DiffContext<'_>) -> Cow<'this, str> {
+        self.header()
+    }
+
+    /// The line written after the last line of a text without a newline, with
+    /// [`NewlinePolicy::Explicit`](crate::NewlinePolicy::Explicit)
+    fn no_newline<'this>(&self) -> Cow<'this, str> {
+        format!("\\ No newline at end of file{}", self.line_end()).into()
+    }
+
+    /// A header to put above each diff in a set of diffs, naming it
+    fn entry_header<'this>(&self, label: &str) -> Cow<'this, str> {
+        format!("=== {label}\n").into()
+    }
+
+    /// A header to put above the diff of a renamed entry in a set of diffs,
+    /// naming it before and after, with how similar its contents are from 0
+    /// to 1
+    fn renamed_header<'this>(&self, old: &str, new: &str, similarity: f64) -> Cow<'this, str> {
+        format!(
+            "renamed: {old} → {new} ({:.0}% similar){}",
+            similarity * 100.0,
+            self.line_end()
+        )
+        .into()
+    }
+
+    /// The line drawn between groups of lines when unchanged lines between
+    /// them have been left out
+    fn hunk_separator<'this>(&self) -> Cow<'this, str> {
+        format!("⋯{}", self.line_end()).into()
+    }
+
+    /// The line drawn above each group of changed lines when no unchanged
+    /// lines are shown around them, giving the line the group starts at
+    ///
+    /// This is the line number in the old text, or in the new text if the
+    /// group only adds lines.
+    fn hunk_position<'this>(&self, line: usize) -> Cow<'this, str> {
+        format!("@@ line {line} @@{}", self.line_end()).into()
+    }
+
+    /// The line drawn above each group of lines with
+    /// [`DrawDiff::with_hunk_headers`](crate::DrawDiff::with_hunk_headers),
+    /// giving the lines of the old and new text the group covers
+    ///
+    /// The ranges are line numbers starting from 1, not including the end,
+    /// so a group of lines 3 and 4 is `3..5`. A group with no lines on one
+    /// side has an empty range starting where they would be. The default is
+    /// dimmed, in the form of a unified diff.
+    fn hunk_header<'this>(&self, old: Range<usize>, new: Range<usize>) -> Cow<'this, str> {
+        format!(
+            "{}{}",
+            unified_range_header(old, new).dim(),
+            self.line_end()
+        )
+        .into()
+    }
+
+    /// The line drawn above a diff that is shown as a full replace, because
+    /// the two sides have too little in common
+    fn substantially_different<'this>(&self) -> Cow<'this, str> {
+        format!(
+            "substantially different, shown as a full replace{}",
+            self.line_end()
+        )
+        .into()
+    }
+
+    /// The line drawn above a diff that is shown as a full replace, because
+    /// it was cancelled before it was worked out
+    fn cancelled<'this>(&self) -> Cow<'this, str> {
+        format!("diff cancelled, shown as a full replace{}", self.line_end()).into()
+    }
+
+    /// The line drawn in place of the lines between ignore markers, with
+    /// [`DrawDiff::with_ignore_markers`](crate::DrawDiff::with_ignore_markers),
+    /// given how many lines the region has in the old and new text
+    ///
+    /// This is formatted as unchanged content, and is followed by a newline.
+    fn ignored_region<'this>(&self, old_lines: usize, new_lines: usize) -> Cow<'this, str> {
+        if old_lines == new_lines {
+            format!("⋯ {new_lines} ignored lines").into()
+        } else {
+            format!("⋯ {old_lines} → {new_lines} ignored lines").into()
+        }
+    }
+
+    /// The prefix to give the parts of a long line after it has been soft
+    /// wrapped, in place of the line's own prefix
+    fn continuation_prefix<'this>(&self) -> Cow<'this, str> {
+        "↪".into()
+    }
+
+    /// How to format lines that were removed from here and inserted
+    /// elsewhere, when move detection is on
+    fn moved_from_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.delete_content(input)
+    }
+
+    /// The prefix to give lines that were removed from here and inserted
+    /// elsewhere, when move detection is on
+    fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+        self.delete_prefix()
+    }
+
+    /// How to format lines that were inserted here and removed from
+    /// elsewhere, when move detection is on
+    fn moved_to_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.insert_line(input)
+    }
+
+    /// The prefix to give lines that were inserted here and removed from
+    /// elsewhere, when move detection is on
+    fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+        self.insert_prefix()
+    }
+
+    /// How many characters wide prefixes are padded to with spaces, so the
+    /// content of every line starts in the same column
+    ///
+    /// The default is the width of the widest of the equal, delete and
+    /// insert prefixes, not counting escape codes. Prefixes wider than this
+    /// are left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use termdiff::{themes::ext::ThemeExt, DrawDiff, SignsTheme};
+    /// let signs = SignsTheme::default();
+    /// let theme = signs.with_prefixes("", "--", "+");
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("a\nb\n", "a\nc\n", &theme).to_string(),
+    ///     "--- remove | insert +++\n  a\n--b\n+ c\n"
+    /// );
+    /// ```
+    fn prefix_width(&self) -> usize {
+        widest_prefix(self)
+    }
+
+    /// What to put between the prefix and the content of each line, like
+    /// `│ `
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use termdiff::{DrawDiff, Theme};
+    ///
+    /// #[derive(Debug)]
+    /// struct GutterTheme {}
+    /// impl Theme for GutterTheme {
+    ///     fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         " ".into()
+    ///     }
+    ///
+    ///     fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "-".into()
+    ///     }
+    ///
+    ///     fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+    ///         "+".into()
+    ///     }
+    ///
+    ///     fn header<'this>(&self) -> Cow<'this, str> {
+    ///         "".into()
+    ///     }
+    ///
+    ///     fn gutter<'this>(&self) -> Cow<'this, str> {
+    ///         " │ ".into()
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(
+    ///     DrawDiff::new("a\nb\n", "a\nc\n", &GutterTheme {}).to_string(),
+    ///     "  │ a\n- │ b\n+ │ c\n"
+    /// );
+    /// ```
+    fn gutter<'this>(&self) -> Cow<'this, str> {
+        "".into()
+    }
+
+    /// How to format already formatted lines that are blank or only changed
+    /// their indentation, when highlighting whitespace changes
+    fn whitespace_only_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.into()
+    }
+
+    /// How to format trailing spaces and tabs on changed lines, when
+    /// highlighting trailing whitespace
+    fn whitespace_marker<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.replace(' ', "·").replace('\t', "→").into()
+    }
+
+    /// Something to write before each line of the diff, which can depend on
+    /// where the line is, see [`LineInfo`]
+    fn line_start<'this>(&self, _line: &LineInfo) -> Cow<'this, str> {
+        "".into()
+    }
+
+    /// Something to write after each line of the diff, after its newline,
+    /// which can depend on where the line is, see [`LineInfo`]
+    fn line_finish<'this>(&self, _line: &LineInfo) -> Cow<'this, str> {
+        "".into()
+    }
+
+    /// The symbol for a line ending, when marking line ending changes
+    fn line_ending_marker<'this>(&self, ending: LineEnding) -> Cow<'this, str> {
+        match ending {
+            LineEnding::Lf => "␊".into(),
+            LineEnding::CrLf => "␍␊".into(),
+            LineEnding::Cr => "␍".into(),
+            LineEnding::None => "∅".into(),
+        }
+    }
+
+    /// What to put at the end of a line that is unchanged apart from its line
+    /// ending, when marking line ending changes
+    fn line_ending_change<'this>(&self, old: LineEnding, new: LineEnding) -> Cow<'this, str> {
+        format!(
+            " {}→{}",
+            self.line_ending_marker(old),
+            self.line_ending_marker(new)
+        )
+        .into()
+    }
+
+    /// What to put at the end of a line that is unchanged apart from how its
+    /// text is encoded, when comparing under a Unicode normalization form
+    fn normalization_change<'this>(&self) -> Cow<'this, str> {
+        " (normalized differently)".into()
+    }
+}
+
+/// The width of the widest of a theme's equal, delete and insert prefixes
+pub(crate) fn widest_prefix<T: Theme + ?Sized>(theme: &T) -> usize {
+    [
+        theme.equal_prefix(),
+        theme.delete_prefix(),
+        theme.insert_prefix(),
+    ]
+    .iter()
+    .map(|prefix| display_width(prefix))
+    .max()
+    .unwrap_or_default()
+}
+
+/// A prefix padded with spaces to the theme's
+/// [`prefix_width`](Theme::prefix_width), given as `width` so it can be
+/// worked out once for many lines, followed by its [`gutter`](Theme::gutter)
+pub(crate) fn prefix_column<'a>(
+    theme: &dyn Theme,
+    prefix: Cow<'a, str>,
+    width: usize,
+) -> Cow<'a, str> {
+    let padding = width.saturating_sub(display_width(&prefix));
+    let gutter = theme.gutter();
+
+    if padding == 0 && gutter.is_empty() {
+        prefix
+    } else {
+        format!("{prefix}{}{gutter}", " ".repeat(padding)).into()
+    }
 }
 
 /// A simple colorless using arrows theme
@@ -147,6 +424,41 @@ impl Theme for ArrowsColorTheme {
     fn header<'this>(&self) -> Cow<'this, str> {
         format!("{} / {}\n", "< left".red(), "> right".green()).into()
     }
+
+    fn moved_from_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.magenta().to_string().into()
+    }
+
+    fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+        "<".magenta().to_string().into()
+    }
+
+    fn moved_to_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.cyan().to_string().into()
+    }
+
+    fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+        ">".cyan().to_string().into()
+    }
+
+    fn whitespace_only_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.dim().to_string().into()
+    }
+
+    fn whitespace_marker<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.red().reverse().to_string().into()
+    }
+
+    fn line_ending_change<'this>(&self, old: LineEnding, new: LineEnding) -> Cow<'this, str> {
+        format!(
+            " {}→{}",
+            self.line_ending_marker(old),
+            self.line_ending_marker(new)
+        )
+        .yellow()
+        .to_string()
+        .into()
+    }
 }
 
 /// A simple colorless using signs theme
@@ -257,4 +569,476 @@ impl Theme for SignsColorTheme {
     fn header<'this>(&self) -> Cow<'this, str> {
         format!("{} | {}\n", "--- remove".red(), "insert +++".green()).into()
     }
+
+    fn moved_from_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.magenta().to_string().into()
+    }
+
+    fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+        "-".magenta().to_string().into()
+    }
+
+    fn moved_to_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.cyan().to_string().into()
+    }
+
+    fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+        "+".cyan().to_string().into()
+    }
+
+    fn whitespace_only_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.dim().to_string().into()
+    }
+
+    fn whitespace_marker<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.red().reverse().to_string().into()
+    }
+
+    fn line_ending_change<'this>(&self, old: LineEnding, new: LineEnding) -> Cow<'this, str> {
+        format!(
+            " {}→{}",
+            self.line_ending_marker(old),
+            self.line_ending_marker(new)
+        )
+        .yellow()
+        .to_string()
+        .into()
+    }
+}
+
+/// A theme for screen readers, marking changes with words rather than
+/// colors or symbols
+///
+/// Changed words are wrapped in `[DEL]` `[/DEL]` and `[INS]` `[/INS]`, and
+/// markers that would otherwise be symbols, like skipped lines and trailing
+/// whitespace, are spelled out.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{AccessibleTheme, DrawDiff};
+/// let theme = AccessibleTheme::default();
+///
+/// assert_eq!(
+///     DrawDiff::new("a fox \n", "a cat\n", &theme)
+///         .with_trailing_whitespace(true)
+///         .to_string(),
+///     "- deleted, + inserted
+/// - a [DEL]fox[/DEL][SPACE]
+/// + a [INS]cat[/INS]
+/// "
+/// );
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+pub struct AccessibleTheme {}
+
+impl Theme for AccessibleTheme {
+    fn highlight_insert<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        format!("[INS]{input}[/INS]").into()
+    }
+
+    fn highlight_delete<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        format!("[DEL]{input}[/DEL]").into()
+    }
+
+    fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+        "  ".into()
+    }
+
+    fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+        "- ".into()
+    }
+
+    fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+        "+ ".into()
+    }
+
+    fn trailing_lf_marker<'this>(&self) -> Cow<'this, str> {
+        "[NEWLINE]".into()
+    }
+
+    fn header<'this>(&self) -> Cow<'this, str> {
+        "- deleted, + inserted\n".into()
+    }
+
+    fn hunk_separator<'this>(&self) -> Cow<'this, str> {
+        "[LINES SKIPPED]\n".into()
+    }
+
+    fn continuation_prefix<'this>(&self) -> Cow<'this, str> {
+        "[CONTINUED] ".into()
+    }
+
+    fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+        "- [MOVED] ".into()
+    }
+
+    fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+        "+ [MOVED] ".into()
+    }
+
+    fn whitespace_marker<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.replace(' ', "[SPACE]").replace('\t', "[TAB]").into()
+    }
+
+    fn line_ending_marker<'this>(&self, ending: LineEnding) -> Cow<'this, str> {
+        match ending {
+            LineEnding::Lf => "LF".into(),
+            LineEnding::CrLf => "CRLF".into(),
+            LineEnding::Cr => "CR".into(),
+            LineEnding::None => "NONE".into(),
+        }
+    }
+
+    fn line_ending_change<'this>(&self, old: LineEnding, new: LineEnding) -> Cow<'this, str> {
+        format!(
+            " [LINE ENDING {} TO {}]",
+            self.line_ending_marker(old),
+            self.line_ending_marker(new)
+        )
+        .into()
+    }
+}
+
+/// A theme using only bold, underline and reverse video, for terminals that
+/// can show text attributes but not colors
+///
+/// Inserted lines are bold, changed words are underlined, and trailing
+/// whitespace is reversed. [`DiffConfig::from_env`](crate::DiffConfig::from_env)
+/// picks this theme when `NO_COLOR` is set, as that only asks for no colors.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::style::{ContentStyle, Stylize};
+/// use termdiff::{AnsiAssert, DrawDiff, MonochromeTheme};
+/// let theme = MonochromeTheme::default();
+/// let output = DrawDiff::new("a fox\n", "a cat\n", &theme).to_string();
+/// let ansi = AnsiAssert::new(&output);
+///
+/// assert_eq!(ansi.plain(), "< left / > right\n<a fox\n>a cat\n");
+/// ansi.assert_style("fox", ContentStyle::new().underlined());
+/// ansi.assert_style("cat", ContentStyle::new().bold().underlined());
+/// assert!(ansi.segments().iter().all(|(style, _)| style.foreground_color.is_none()));
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+pub struct MonochromeTheme {}
+
+impl Theme for MonochromeTheme {
+    fn highlight_insert<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.underlined().to_string().into()
+    }
+
+    fn highlight_delete<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.underlined().to_string().into()
+    }
+
+    fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+        " ".into()
+    }
+
+    fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+        "<".into()
+    }
+
+    fn insert_line<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.bold().to_string().into()
+    }
+
+    fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+        ">".bold().to_string().into()
+    }
+
+    fn header<'this>(&self) -> Cow<'this, str> {
+        format!("< left / {}\n", "> right".bold()).into()
+    }
+
+    fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+        "<".underlined().to_string().into()
+    }
+
+    fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+        ">".bold().underlined().to_string().into()
+    }
+
+    fn whitespace_marker<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.reverse().to_string().into()
+    }
+}
+
+/// A plain theme for snapshot tests, made only of ASCII and without color
+///
+/// Every part of the theme is spelled out here rather than taken from the
+/// defaults of [`Theme`], and the output it draws will only change in a new
+/// major version, so snapshots of it can be relied on. Carriage returns in
+/// the text are shown as `[CR]`, so the output only ever has `\n` line
+/// endings, whatever platform the text came from.
+///
+/// Changed words are wrapped in `[-` `-]` and `{+` `+}`, and moved lines are
+/// prefixed with `<` and `>`.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{DrawDiff, SnapshotTheme};
+/// let old = "The quick brown fox\r\njumps";
+/// let new = "The quick red fox\r\njumps\n";
+/// let theme = SnapshotTheme::default();
+///
+/// assert_eq!(
+///     format!("{}", DrawDiff::new(old, new, &theme)),
+///     "--- old
+/// +++ new
+/// -The quick [-brown-] fox[CR]
+/// -[-jumps-]
+/// +The quick {+red+} fox[CR]
+/// +{+jumps[LF]+}
+/// "
+/// );
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+pub struct SnapshotTheme {}
+
+impl SnapshotTheme {
+    fn visible_returns(input: &str) -> Cow<'_, str> {
+        if input.contains('\r') {
+            input.replace('\r', "[CR]").into()
+        } else {
+            input.into()
+        }
+    }
+}
+
+impl Theme for SnapshotTheme {
+    fn highlight_insert<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        format!("{{+{input}+}}").into()
+    }
+
+    fn highlight_delete<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        format!("[-{input}-]").into()
+    }
+
+    fn equal_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        Self::visible_returns(input)
+    }
+
+    fn delete_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        Self::visible_returns(input)
+    }
+
+    fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+        " ".into()
+    }
+
+    fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+        "-".into()
+    }
+
+    fn insert_line<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        Self::visible_returns(input)
+    }
+
+    fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+        "+".into()
+    }
+
+    fn line_end<'this>(&self) -> Cow<'this, str> {
+        "\n".into()
+    }
+
+    fn trailing_lf_marker<'this>(&self) -> Cow<'this, str> {
+        "[LF]".into()
+    }
+
+    fn header<'this>(&self) -> Cow<'this, str> {
+        "--- old\n+++ new\n".into()
+    }
+
+    fn header_with_context<'this>(&self, _context: &DiffContext<'_>) -> Cow<'this, str> {
+        self.header()
+    }
+
+    fn no_newline<'this>(&self) -> Cow<'this, str> {
+        "\\ No newline at end of file\n".into()
+    }
+
+    fn entry_header<'this>(&self, label: &str) -> Cow<'this, str> {
+        format!("=== {label}\n").into()
+    }
+
+    fn renamed_header<'this>(&self, old: &str, new: &str, similarity: f64) -> Cow<'this, str> {
+        format!("=== {old} => {new} ({:.0}%)\n", similarity * 100.0).into()
+    }
+
+    fn hunk_separator<'this>(&self) -> Cow<'this, str> {
+        "...\n".into()
+    }
+
+    fn hunk_position<'this>(&self, line: usize) -> Cow<'this, str> {
+        format!("@@ line {line} @@\n").into()
+    }
+
+    fn hunk_header<'this>(&self, old: Range<usize>, new: Range<usize>) -> Cow<'this, str> {
+        format!("{}\n", unified_range_header(old, new)).into()
+    }
+
+    fn substantially_different<'this>(&self) -> Cow<'this, str> {
+        "[substantially different]\n".into()
+    }
+
+    fn cancelled<'this>(&self) -> Cow<'this, str> {
+        "[cancelled]\n".into()
+    }
+
+    fn ignored_region<'this>(&self, old_lines: usize, new_lines: usize) -> Cow<'this, str> {
+        format!("[ignored {old_lines}/{new_lines} lines]").into()
+    }
+
+    fn continuation_prefix<'this>(&self) -> Cow<'this, str> {
+        "~".into()
+    }
+
+    fn moved_from_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        Self::visible_returns(input)
+    }
+
+    fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+        "<".into()
+    }
+
+    fn moved_to_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        Self::visible_returns(input)
+    }
+
+    fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+        ">".into()
+    }
+
+    fn prefix_width(&self) -> usize {
+        1
+    }
+
+    fn gutter<'this>(&self) -> Cow<'this, str> {
+        "".into()
+    }
+
+    fn whitespace_only_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.into()
+    }
+
+    fn whitespace_marker<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        input.replace(' ', ".").replace('\t', ">").into()
+    }
+
+    fn line_start<'this>(&self, _line: &LineInfo) -> Cow<'this, str> {
+        "".into()
+    }
+
+    fn line_finish<'this>(&self, _line: &LineInfo) -> Cow<'this, str> {
+        "".into()
+    }
+
+    fn line_ending_marker<'this>(&self, ending: LineEnding) -> Cow<'this, str> {
+        match ending {
+            LineEnding::Lf => "[LF]".into(),
+            LineEnding::CrLf => "[CRLF]".into(),
+            LineEnding::Cr => "[CR]".into(),
+            LineEnding::None => "[EOF]".into(),
+        }
+    }
+
+    fn line_ending_change<'this>(&self, old: LineEnding, new: LineEnding) -> Cow<'this, str> {
+        format!(
+            " {}->{}",
+            self.line_ending_marker(old),
+            self.line_ending_marker(new)
+        )
+        .into()
+    }
+    fn normalization_change<'this>(&self) -> Cow<'this, str> {
+        " [NORMALIZED]".into()
+    }
+}
+
+/// A hunk header as in a unified diff, like `@@ -3,2 +3,3 @@`, where ranges
+/// with no lines start at the line before
+fn unified_range_header(old: Range<usize>, new: Range<usize>) -> String {
+    let side = |range: Range<usize>| {
+        let start = if range.is_empty() {
+            range.start.saturating_sub(1)
+        } else {
+            range.start
+        };
+        format!("{start},{}", range.len())
+    };
+
+    format!("@@ -{} +{} @@", side(old), side(new))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{SnapshotTheme, Theme};
+    use crate::DrawDiff;
+
+    #[derive(Debug)]
+    struct ColoredArrows;
+
+    impl Theme for ColoredArrows {
+        fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+            " ".into()
+        }
+
+        fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+            "\u{1b}[31m←←\u{1b}[0m".into()
+        }
+
+        fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+            "\u{1b}[32m→\u{1b}[0m".into()
+        }
+
+        fn header<'this>(&self) -> Cow<'this, str> {
+            "".into()
+        }
+    }
+
+    #[test]
+    fn colored_non_ascii_prefixes_are_padded_by_their_columns() {
+        let theme = ColoredArrows;
+
+        assert_eq!(theme.prefix_width(), 2);
+        assert_eq!(
+            DrawDiff::new("a\nb\n", "a\nc\n", &theme).to_string(),
+            "  a\n\u{1b}[31m←←\u{1b}[0mb\n\u{1b}[32m→\u{1b}[0m c\n"
+        );
+    }
+
+    #[test]
+    fn snapshot_output_is_stable() {
+        let old = "fn a() {}\nfn b() {}\nlet x = 1;  \nend\r\n";
+        let new = "let y = 1;\t\nfn a() {}\nfn b() {}\nend\n";
+        let theme = SnapshotTheme::default();
+        let diff = DrawDiff::new(old, new, &theme)
+            .with_move_detection(2)
+            .with_trailing_whitespace(true)
+            .with_line_ending_markers(true)
+            .with_wrap_width(6);
+
+        assert_eq!(
+            format!("{diff}"),
+            "--- old
++++ new
++let y 
+~= 1;>
+ fn a()
+~ {}
+ fn b()
+~ {}
+-let x 
+~= 1;..
+ end [CRLF]->[LF]
+"
+        );
+    }
 }
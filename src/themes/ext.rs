@@ -0,0 +1,279 @@
+//! Ways to change a few hooks of an existing theme, without writing a whole
+//! theme
+//!
+//! # Examples
+//!
+//! ```
+//! use termdiff::{themes::ext::ThemeExt, ArrowsTheme, DrawDiff};
+//! let arrows = ArrowsTheme::default();
+//! let theme = arrows
+//!     .with_header("--- old / +++ new\n")
+//!     .with_prefixes(" ", "-", "+");
+//!
+//! assert_eq!(
+//!     DrawDiff::new("a\nb\n", "a\nc\n", &theme).to_string(),
+//!     "--- old / +++ new\n a\n-b\n+c\n"
+//! );
+//! ```
+
+use std::{borrow::Cow, ops::Range};
+
+use crossterm::style::Stylize;
+
+use super::{widest_prefix, Theme};
+use crate::{diff_context::DiffContext, line_endings::LineEnding, line_info::LineInfo};
+
+/// Combinators for changing parts of a theme, available on every theme
+pub trait ThemeExt: Theme {
+    /// The theme, with a different header
+    fn with_header<'a>(&'a self, header: &'a str) -> Decorated<'a, Self> {
+        Decorated::new(self).with_header(header)
+    }
+
+    /// The theme, with different prefixes for equal, removed and added lines
+    ///
+    /// Moved lines keep the theme's own prefixes.
+    fn with_prefixes<'a>(
+        &'a self,
+        equal: &'a str,
+        delete: &'a str,
+        insert: &'a str,
+    ) -> Decorated<'a, Self> {
+        Decorated::new(self).with_prefixes(equal, delete, insert)
+    }
+
+    /// The theme, with the content of unchanged lines dimmed so the changes
+    /// stand out
+    fn dimmed_equal(&self) -> Decorated<'_, Self> {
+        Decorated::new(self).dimmed_equal()
+    }
+}
+
+impl<T: Theme + ?Sized> ThemeExt for T {}
+
+/// A theme with some of its hooks overridden, see [`ThemeExt`]
+#[derive(Debug)]
+pub struct Decorated<'a, T: Theme + ?Sized> {
+    inner: &'a T,
+    header: Option<&'a str>,
+    prefixes: Option<(&'a str, &'a str, &'a str)>,
+    dimmed_equal: bool,
+}
+
+impl<'a, T: Theme + ?Sized> Decorated<'a, T> {
+    fn new(inner: &'a T) -> Self {
+        Self {
+            inner,
+            header: None,
+            prefixes: None,
+            dimmed_equal: false,
+        }
+    }
+
+    /// See [`ThemeExt::with_header`]
+    #[must_use]
+    pub fn with_header(mut self, header: &'a str) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// See [`ThemeExt::with_prefixes`]
+    #[must_use]
+    pub fn with_prefixes(mut self, equal: &'a str, delete: &'a str, insert: &'a str) -> Self {
+        self.prefixes = Some((equal, delete, insert));
+        self
+    }
+
+    /// See [`ThemeExt::dimmed_equal`]
+    #[must_use]
+    pub fn dimmed_equal(mut self) -> Self {
+        self.dimmed_equal = true;
+        self
+    }
+}
+
+impl<T: Theme + ?Sized> Theme for Decorated<'_, T> {
+    fn highlight_insert<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.highlight_insert(input)
+    }
+
+    fn highlight_delete<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.highlight_delete(input)
+    }
+
+    fn equal_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        let content = self.inner.equal_content(input);
+
+        if self.dimmed_equal {
+            content.into_owned().dim().to_string().into()
+        } else {
+            content
+        }
+    }
+
+    fn delete_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.delete_content(input)
+    }
+
+    fn equal_prefix<'this>(&self) -> Cow<'this, str> {
+        match self.prefixes {
+            Some((equal, _, _)) => equal.to_string().into(),
+            None => self.inner.equal_prefix(),
+        }
+    }
+
+    fn delete_prefix<'this>(&self) -> Cow<'this, str> {
+        match self.prefixes {
+            Some((_, delete, _)) => delete.to_string().into(),
+            None => self.inner.delete_prefix(),
+        }
+    }
+
+    fn insert_line<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.insert_line(input)
+    }
+
+    fn insert_prefix<'this>(&self) -> Cow<'this, str> {
+        match self.prefixes {
+            Some((_, _, insert)) => insert.to_string().into(),
+            None => self.inner.insert_prefix(),
+        }
+    }
+
+    fn line_end<'this>(&self) -> Cow<'this, str> {
+        self.inner.line_end()
+    }
+
+    fn trailing_lf_marker<'this>(&self) -> Cow<'this, str> {
+        self.inner.trailing_lf_marker()
+    }
+
+    fn header<'this>(&self) -> Cow<'this, str> {
+        match self.header {
+            Some(header) => header.to_string().into(),
+            None => self.inner.header(),
+        }
+    }
+
+    fn header_with_context<'this>(&self, context: &DiffContext<'_>) -> Cow<'this, str> {
+        match self.header {
+            Some(header) => header.to_string().into(),
+            None => self.inner.header_with_context(context),
+        }
+    }
+
+    fn no_newline<'this>(&self) -> Cow<'this, str> {
+        self.inner.no_newline()
+    }
+
+    fn entry_header<'this>(&self, label: &str) -> Cow<'this, str> {
+        self.inner.entry_header(label)
+    }
+
+    fn renamed_header<'this>(&self, old: &str, new: &str, similarity: f64) -> Cow<'this, str> {
+        self.inner.renamed_header(old, new, similarity)
+    }
+
+    fn hunk_separator<'this>(&self) -> Cow<'this, str> {
+        self.inner.hunk_separator()
+    }
+
+    fn hunk_position<'this>(&self, line: usize) -> Cow<'this, str> {
+        self.inner.hunk_position(line)
+    }
+
+    fn hunk_header<'this>(&self, old: Range<usize>, new: Range<usize>) -> Cow<'this, str> {
+        self.inner.hunk_header(old, new)
+    }
+
+    fn substantially_different<'this>(&self) -> Cow<'this, str> {
+        self.inner.substantially_different()
+    }
+
+    fn cancelled<'this>(&self) -> Cow<'this, str> {
+        self.inner.cancelled()
+    }
+
+    fn ignored_region<'this>(&self, old_lines: usize, new_lines: usize) -> Cow<'this, str> {
+        self.inner.ignored_region(old_lines, new_lines)
+    }
+
+    fn continuation_prefix<'this>(&self) -> Cow<'this, str> {
+        self.inner.continuation_prefix()
+    }
+
+    fn moved_from_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.moved_from_content(input)
+    }
+
+    fn moved_from_prefix<'this>(&self) -> Cow<'this, str> {
+        self.inner.moved_from_prefix()
+    }
+
+    fn moved_to_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.moved_to_content(input)
+    }
+
+    fn moved_to_prefix<'this>(&self) -> Cow<'this, str> {
+        self.inner.moved_to_prefix()
+    }
+
+    fn prefix_width(&self) -> usize {
+        match self.prefixes {
+            Some(_) => widest_prefix(self),
+            None => self.inner.prefix_width(),
+        }
+    }
+
+    fn gutter<'this>(&self) -> Cow<'this, str> {
+        self.inner.gutter()
+    }
+
+    fn whitespace_only_content<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.whitespace_only_content(input)
+    }
+
+    fn whitespace_marker<'this>(&self, input: &'this str) -> Cow<'this, str> {
+        self.inner.whitespace_marker(input)
+    }
+
+    fn line_start<'this>(&self, line: &LineInfo) -> Cow<'this, str> {
+        self.inner.line_start(line)
+    }
+
+    fn line_finish<'this>(&self, line: &LineInfo) -> Cow<'this, str> {
+        self.inner.line_finish(line)
+    }
+
+    fn line_ending_marker<'this>(&self, ending: LineEnding) -> Cow<'this, str> {
+        self.inner.line_ending_marker(ending)
+    }
+
+    fn line_ending_change<'this>(&self, old: LineEnding, new: LineEnding) -> Cow<'this, str> {
+        self.inner.line_ending_change(old, new)
+    }
+
+    fn normalization_change<'this>(&self) -> Cow<'this, str> {
+        self.inner.normalization_change()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{Attribute, ContentStyle};
+
+    use super::ThemeExt;
+    use crate::{AnsiAssert, ArrowsTheme, DrawDiff, Theme};
+
+    #[test]
+    fn dimming_only_changes_equal_lines() {
+        let arrows = ArrowsTheme::default();
+        let theme: &dyn Theme = &arrows;
+        let output = DrawDiff::new("a\nb\n", "a\nc\n", &theme.dimmed_equal()).to_string();
+        let ansi = AnsiAssert::new(&output);
+
+        assert_eq!(ansi.plain(), "< left / > right\n a\n<b\n>c\n");
+        assert!(ansi.style_of("a").unwrap().attributes.has(Attribute::Dim));
+        assert_eq!(ansi.style_of("c"), Some(ContentStyle::new()));
+    }
+}
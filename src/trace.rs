@@ -0,0 +1,67 @@
+use super::{
+    change::ChangeTag, line_endings::split_ending, lines::split_lines, result::DiffResult,
+};
+
+/// Log the changes between two pieces of text as `tracing` events
+///
+/// There is an info event for each line that was removed or added, with the
+/// `tag` of the change, the `hunk` it is in counting from 0, its line
+/// number as `old_line` if it was removed or `new_line` if it was added, and
+/// the `line` itself without its line ending. Unchanged lines aren't logged.
+///
+/// This means a diff lands in structured logs as fields, rather than as one
+/// long string with escape codes in it.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::trace_diff;
+///
+/// // Logs `tag=Delete hunk=0 old_line=2 line="b"`, then
+/// // `tag=Insert hunk=0 new_line=2 line="c"`
+/// trace_diff("a\nb\n", "a\nc\n");
+/// ```
+pub fn trace_diff(old: &str, new: &str) {
+    let result = DiffResult::new(old, new);
+    let old_lines: Vec<&str> = split_lines(old).collect();
+    let new_lines: Vec<&str> = split_lines(new).collect();
+    let mut hunk = 0;
+    let mut in_hunk = false;
+
+    for op in result.ops() {
+        let tag = op.tag();
+        if tag == ChangeTag::Equal {
+            if in_hunk {
+                hunk += 1;
+            }
+            in_hunk = false;
+            continue;
+        }
+        in_hunk = true;
+
+        match tag {
+            ChangeTag::Insert | ChangeTag::MovedTo => {
+                for index in op.new_range() {
+                    tracing::info!(
+                        target: "termdiff",
+                        ?tag,
+                        hunk,
+                        new_line = index + 1,
+                        line = split_ending(new_lines[index]).0,
+                    );
+                }
+            }
+            _ => {
+                for index in op.old_range() {
+                    tracing::info!(
+                        target: "termdiff",
+                        ?tag,
+                        hunk,
+                        old_line = index + 1,
+                        line = split_ending(old_lines[index]).0,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use super::{
+    change::ChangeTag,
+    ops::{DiffOp, MergeOps, OpTransform},
+    result::DiffResult,
+    verify::{verify, VerifyError},
+};
+
+/// Why a unified diff couldn't be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// A line starting with `@@` isn't a hunk header like
+    /// `@@ -1,3 +1,4 @@`
+    InvalidHunkHeader {
+        /// The number of the line in the patch, starting from 1
+        line: usize,
+    },
+    /// A line in a hunk doesn't start with ` `, `-`, `+` or `\`
+    InvalidLine {
+        /// The number of the line in the patch, starting from 1
+        line: usize,
+    },
+    /// A hunk has a different number of lines than its header says
+    WrongLength {
+        /// The number of the line in the patch the hunk's header is on,
+        /// starting from 1
+        line: usize,
+    },
+    /// There are no hunks in the patch
+    NoHunks,
+    /// Lines follow a `\ No newline at end of file` line on the side it
+    /// ended, or in another hunk
+    MisplacedNoNewline {
+        /// The number of the line in the patch that follows it, starting
+        /// from 1
+        line: usize,
+    },
+    /// The hunks don't make a diff of the texts read from them, like when
+    /// an empty line has no newline
+    Inconsistent(VerifyError),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHunkHeader { line } => write!(f, "line {line} is not a valid hunk header"),
+            Self::InvalidLine { line } => write!(f, "line {line} is not a valid line of a hunk"),
+            Self::WrongLength { line } => {
+                write!(
+                    f,
+                    "the hunk on line {line} does not match its header's length"
+                )
+            }
+            Self::NoHunks => write!(f, "there are no hunks in the patch"),
+            Self::MisplacedNoNewline { line } => {
+                write!(f, "line {line} comes after the end of the file")
+            }
+            Self::Inconsistent(error) => {
+                write!(f, "the hunks are not a diff of their lines: {error}")
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Read a diff of one file in the unified format, as given by `diff -u` or
+/// `git diff`, so it can be drawn with a theme
+///
+/// Only the lines in the hunks are known, so the texts of the diff are the
+/// lines of each hunk one after the other. Anything before the first hunk,
+/// like the `---` and `+++` lines, is skipped, as is anything after the last
+/// one.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{parse_unified, ArrowsTheme};
+/// let theme = ArrowsTheme::default();
+/// let patch = "--- a/greeting.txt
+/// +++ b/greeting.txt
+/// @@ -1,2 +1,2 @@
+///  hello
+/// -world
+/// +there
+/// ";
+/// let result = parse_unified(patch).unwrap();
+///
+/// assert_eq!(
+///     result.draw(&theme).to_string(),
+///     "< left / > right\n hello\n<world\n>there\n"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors if a hunk can't be read, or there are no hunks at all.
+pub fn parse_unified(patch: &str) -> Result<DiffResult<'static>, ParseError> {
+    let (mut old, mut new) = (String::new(), String::new());
+    let (mut old_line, mut new_line) = (0, 0);
+    let mut ops = vec![];
+    let mut remaining: Option<(usize, usize, usize)> = None;
+    let mut last_tag = None;
+    let mut hunks = 0;
+    // Which sides a `\ No newline at end of file` line has ended
+    let (mut old_ended, mut new_ended) = (false, false);
+
+    for (index, line) in patch.split_inclusive('\n').enumerate() {
+        let number = index + 1;
+
+        match remaining {
+            Some((header, 0, 0)) if line.starts_with('\\') => {
+                strip_newline(last_tag, &mut old, &mut new);
+                (old_ended, new_ended) = ended(last_tag, old_ended, new_ended);
+                remaining = Some((header, 0, 0));
+                continue;
+            }
+            Some((_, 0, 0)) | None => {
+                if line.starts_with("@@") {
+                    if old_ended || new_ended {
+                        return Err(ParseError::MisplacedNoNewline { line: number });
+                    }
+                    let (old_count, new_count) =
+                        hunk_header(line).ok_or(ParseError::InvalidHunkHeader { line: number })?;
+                    remaining = Some((number, old_count, new_count));
+                    hunks += 1;
+                }
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let Some((header, old_left, new_left)) = remaining else {
+            continue;
+        };
+        let content = line.get(1..).unwrap_or_default();
+        let tag = match line.chars().next() {
+            Some(' ' | '\n' | '\r') => ChangeTag::Equal,
+            Some('-') => ChangeTag::Delete,
+            Some('+') => ChangeTag::Insert,
+            Some('\\') => {
+                strip_newline(last_tag, &mut old, &mut new);
+                (old_ended, new_ended) = ended(last_tag, old_ended, new_ended);
+                continue;
+            }
+            Some('@') => return Err(ParseError::WrongLength { line: header }),
+            _ => return Err(ParseError::InvalidLine { line: number }),
+        };
+        let content = if line.starts_with(['\n', '\r']) {
+            line
+        } else {
+            content
+        };
+
+        let (old_used, new_used) = match tag {
+            ChangeTag::Equal => (1, 1),
+            ChangeTag::Delete => (1, 0),
+            _ => (0, 1),
+        };
+        if old_used > old_left || new_used > new_left {
+            return Err(ParseError::WrongLength { line: header });
+        }
+        if (old_used == 1 && old_ended) || (new_used == 1 && new_ended) {
+            return Err(ParseError::MisplacedNoNewline { line: number });
+        }
+        remaining = Some((header, old_left - old_used, new_left - new_used));
+
+        if old_used == 1 {
+            old.push_str(content);
+        }
+        if new_used == 1 {
+            new.push_str(content);
+        }
+        ops.push(DiffOp::new(
+            tag,
+            old_line..old_line + old_used,
+            new_line..new_line + new_used,
+        ));
+        old_line += old_used;
+        new_line += new_used;
+        last_tag = Some(tag);
+    }
+
+    match remaining {
+        Some((header, old_left, new_left)) if old_left > 0 || new_left > 0 => {
+            Err(ParseError::WrongLength { line: header })
+        }
+        _ if hunks == 0 => Err(ParseError::NoHunks),
+        _ => {
+            let ops = MergeOps::default().transform(&[], &[], ops);
+            verify(&old, &new, &ops).map_err(ParseError::Inconsistent)?;
+            Ok(DiffResult::from_parts(old, new, ops))
+        }
+    }
+}
+
+/// The number of old and new lines a hunk header like `@@ -1,3 +1,4 @@`
+/// says its hunk has
+fn hunk_header(line: &str) -> Option<(usize, usize)> {
+    let mut fields = line.strip_prefix("@@ ")?.split(' ');
+    let old = fields.next()?.strip_prefix('-')?;
+    let new = fields.next()?.strip_prefix('+')?;
+    let count = |range: &str| match range.split_once(',') {
+        Some((start, count)) => {
+            start.parse::<usize>().ok()?;
+            count.parse().ok()
+        }
+        None => range.parse::<usize>().ok().map(|_| 1),
+    };
+
+    Some((count(old)?, count(new)?))
+}
+
+/// Which sides have ended after a `\ No newline at end of file` line, given
+/// which had before it
+fn ended(last_tag: Option<ChangeTag>, old: bool, new: bool) -> (bool, bool) {
+    match last_tag {
+        Some(ChangeTag::Equal) => (true, true),
+        Some(ChangeTag::Delete) => (true, new),
+        Some(_) => (old, true),
+        None => (old, new),
+    }
+}
+
+/// Take the newline off the end of the text the last line was added to, for
+/// a `\ No newline at end of file` line
+fn strip_newline(last_tag: Option<ChangeTag>, old: &mut String, new: &mut String) {
+    let texts = match last_tag {
+        Some(ChangeTag::Equal) => vec![old, new],
+        Some(ChangeTag::Delete) => vec![old],
+        Some(_) => vec![new],
+        None => vec![],
+    };
+
+    for text in texts {
+        if text.ends_with('\n') {
+            text.pop();
+            if text.ends_with('\r') {
+                text.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_unified, ParseError};
+    use crate::{verify, ArrowsTheme, VerifyError};
+
+    #[test]
+    fn hunks_are_joined_and_missing_newlines_kept() {
+        let patch = "diff --git a/x b/x\n\
+                     @@ -1,2 +1,2 @@\n a\n-b\n+c\n\
+                     @@ -9 +9 @@\n-y\n\\ No newline at end of file\n+z\n";
+        let result = parse_unified(patch).unwrap();
+
+        assert_eq!(verify("a\nb\ny", "a\nc\nz\n", result.ops()), Ok(()));
+        assert_eq!(
+            result.draw(&ArrowsTheme {}).to_string(),
+            "< left / > right\n a\n<b\n>c\n<y\n>z␊\n"
+        );
+    }
+
+    #[test]
+    fn broken_patches_are_rejected() {
+        assert_eq!(parse_unified("just text\n"), Err(ParseError::NoHunks));
+        assert_eq!(
+            parse_unified("@@ nonsense @@\n"),
+            Err(ParseError::InvalidHunkHeader { line: 1 })
+        );
+        assert_eq!(
+            parse_unified("@@ -1,2 +1,1 @@\n a\n"),
+            Err(ParseError::WrongLength { line: 1 })
+        );
+        assert_eq!(
+            parse_unified("@@ -1 +1 @@\n*a\n"),
+            Err(ParseError::InvalidLine { line: 2 })
+        );
+    }
+
+    #[test]
+    fn missing_newlines_only_end_the_last_hunk() {
+        assert_eq!(
+            parse_unified("@@ -1 +1 @@\n-a\n\\ No newline at end of file\n+b\n@@ -5 +5 @@\n c\n"),
+            Err(ParseError::MisplacedNoNewline { line: 5 })
+        );
+        assert_eq!(
+            parse_unified("@@ -1,2 +1 @@\n-a\n\\ No newline at end of file\n-b\n+c\n"),
+            Err(ParseError::MisplacedNoNewline { line: 4 })
+        );
+    }
+
+    #[test]
+    fn empty_lines_without_newlines_are_rejected() {
+        assert_eq!(
+            parse_unified("@@ -1 +1 @@\n "),
+            Err(ParseError::Inconsistent(VerifyError::OutOfBounds { op: 0 }))
+        );
+        assert_eq!(
+            parse_unified("@@ -1,2 +1,2 @@\n x\n\r\n\\ No newline at end of file\n"),
+            Err(ParseError::Inconsistent(VerifyError::OutOfBounds { op: 0 }))
+        );
+    }
+
+    #[test]
+    fn parsed_ops_are_correct() {
+        let result = parse_unified("@@ -1,3 +1,3 @@\n-a\n+b\n c\n-d\n+e\n").unwrap();
+        let old = "a\nc\nd\n";
+        let new = "b\nc\ne\n";
+
+        assert_eq!(verify(old, new, result.ops()), Ok(()));
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use super::{change::ChangeTag, lines::split_lines, ops::DiffOp};
+
+/// Why some ops aren't a correct diff of two pieces of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The op doesn't start where the op before it ended
+    NotContiguous {
+        /// The index of the op
+        op: usize,
+    },
+    /// The op covers lines past the end of the text
+    OutOfBounds {
+        /// The index of the op
+        op: usize,
+    },
+    /// The op's ranges don't make sense for its tag, like an insert that
+    /// covers old lines
+    WrongShape {
+        /// The index of the op
+        op: usize,
+    },
+    /// The op says lines are equal when they aren't
+    NotEqual {
+        /// The index of the op
+        op: usize,
+    },
+    /// The ops stop before the end of the text
+    Incomplete,
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotContiguous { op } => {
+                write!(f, "op {op} does not follow on from the op before it")
+            }
+            Self::OutOfBounds { op } => write!(f, "op {op} covers lines past the end of the text"),
+            Self::WrongShape { op } => write!(f, "op {op} has ranges that do not match its tag"),
+            Self::NotEqual { op } => write!(f, "op {op} covers lines that are not equal"),
+            Self::Incomplete => write!(f, "the ops do not cover all of the text"),
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+/// Check that ops are a correct diff of two pieces of text
+///
+/// The ops need to cover every line of both texts in order, and applying
+/// them to the old text needs to give the new text. This is useful for
+/// checking ops from your own diff algorithm before handing them to
+/// [`DrawDiff::with_ops`](crate::DrawDiff::with_ops).
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{verify, ChangeTag, DiffOp, VerifyError};
+/// let ops = [
+///     DiffOp::new(ChangeTag::Equal, 0..1, 0..1),
+///     DiffOp::new(ChangeTag::Delete, 1..2, 1..1),
+///     DiffOp::new(ChangeTag::Insert, 2..2, 1..2),
+/// ];
+///
+/// assert_eq!(verify("a\nb\n", "a\nc\n", &ops), Ok(()));
+/// assert_eq!(
+///     verify("a\nb\n", "b\nc\n", &ops),
+///     Err(VerifyError::NotEqual { op: 0 })
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors with the first problem found in the ops.
+pub fn verify(old: &str, new: &str, ops: &[DiffOp]) -> Result<(), VerifyError> {
+    let old: Vec<&str> = split_lines(old).collect();
+    let new: Vec<&str> = split_lines(new).collect();
+    let (mut old_at, mut new_at) = (0, 0);
+
+    for (index, op) in ops.iter().enumerate() {
+        let (old_range, new_range) = (op.old_range(), op.new_range());
+        let shaped = match op.tag() {
+            ChangeTag::Equal => old_range.len() == new_range.len(),
+            ChangeTag::Delete | ChangeTag::MovedFrom => new_range.is_empty(),
+            ChangeTag::Insert | ChangeTag::MovedTo => old_range.is_empty(),
+        };
+
+        if old_range.start != old_at || new_range.start != new_at {
+            return Err(VerifyError::NotContiguous { op: index });
+        }
+        if !shaped || old_range.start > old_range.end || new_range.start > new_range.end {
+            return Err(VerifyError::WrongShape { op: index });
+        }
+        if old_range.end > old.len() || new_range.end > new.len() {
+            return Err(VerifyError::OutOfBounds { op: index });
+        }
+        if op.tag() == ChangeTag::Equal && old[old_range.clone()] != new[new_range.clone()] {
+            return Err(VerifyError::NotEqual { op: index });
+        }
+
+        old_at = old_range.end;
+        new_at = new_range.end;
+    }
+
+    if old_at == old.len() && new_at == new.len() {
+        Ok(())
+    } else {
+        Err(VerifyError::Incomplete)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify, VerifyError};
+    use crate::{ChangeTag, DiffOp};
+
+    #[test]
+    fn problems_are_found() {
+        let delete = |old, new| DiffOp::new(ChangeTag::Delete, old, new);
+
+        assert_eq!(verify("a\n", "", &[delete(0..1, 0..0)]), Ok(()));
+        assert_eq!(verify("a\n", "", &[]), Err(VerifyError::Incomplete));
+        assert_eq!(
+            verify("a\n", "", &[delete(0..2, 0..0)]),
+            Err(VerifyError::OutOfBounds { op: 0 })
+        );
+        assert_eq!(
+            verify("a\nb\n", "", &[delete(1..2, 0..0)]),
+            Err(VerifyError::NotContiguous { op: 0 })
+        );
+        assert_eq!(
+            verify("a\n", "a\n", &[delete(0..1, 0..1)]),
+            Err(VerifyError::WrongShape { op: 0 })
+        );
+    }
+}
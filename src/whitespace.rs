@@ -0,0 +1,150 @@
+use super::change::{Change, ChangeTag};
+
+/// What to do with changes that only add or remove blank lines, or only
+/// change the indentation of a line
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DrawDiff, WhitespaceChanges};
+/// let old = "if x {\ny();\n}\n";
+/// let new = "if x {\n    y();\n}\n";
+/// let theme = ArrowsTheme::default();
+///
+/// assert_eq!(
+///     format!(
+///         "{}",
+///         DrawDiff::new(old, new, &theme).with_whitespace_changes(WhitespaceChanges::Ignore)
+///     ),
+///     "< left / > right
+///  if x {
+///      y();
+///  }
+/// "
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WhitespaceChanges {
+    /// Render them like any other change
+    #[default]
+    Show,
+    /// Render them with the theme's [`whitespace_only_content`] styling
+    ///
+    /// [`whitespace_only_content`]: crate::Theme::whitespace_only_content
+    Highlight,
+    /// Render them as if they were unchanged, showing the new text
+    Ignore,
+}
+
+/// Mark the deleted and inserted lines that are blank, or that only differ
+/// from a line on the other side of their block in leading whitespace
+pub(crate) fn classify_whitespace(changes: &mut [Change<'_>]) {
+    let mut start = 0;
+
+    while start < changes.len() {
+        let length = changes[start..]
+            .iter()
+            .take_while(|change| matches!(change.tag(), ChangeTag::Delete | ChangeTag::Insert))
+            .count();
+
+        if length == 0 {
+            start += 1;
+        } else {
+            classify_block(&mut changes[start..start + length]);
+            start += length;
+        }
+    }
+}
+
+/// Pair up deletes and inserts in a block that match after their indentation
+/// is removed, keeping them in order
+fn classify_block(block: &mut [Change<'_>]) {
+    let contents: Vec<String> = block
+        .iter()
+        .map(|change| change.content().trim_start().to_string())
+        .collect();
+    let inserts: Vec<usize> = (0..block.len())
+        .filter(|index| block[*index].tag() == ChangeTag::Insert)
+        .collect();
+    let mut next_insert = 0;
+    let mut whitespace_only = vec![false; block.len()];
+
+    for (index, content) in contents.iter().enumerate() {
+        if content.trim_end().is_empty() {
+            whitespace_only[index] = true;
+            continue;
+        }
+
+        if block[index].tag() != ChangeTag::Delete {
+            continue;
+        }
+
+        if let Some(position) = inserts[next_insert..]
+            .iter()
+            .position(|insert| contents[*insert] == *content)
+        {
+            let insert = inserts[next_insert + position];
+            whitespace_only[index] = true;
+            whitespace_only[insert] = true;
+            next_insert += position + 1;
+        }
+    }
+
+    for (change, whitespace_only) in block.iter_mut().zip(whitespace_only) {
+        if whitespace_only {
+            change.mark_whitespace_only();
+        }
+    }
+}
+
+/// Drop deleted lines that only changed whitespace, and render inserted ones
+/// as if they were unchanged
+pub(crate) fn ignore_whitespace(changes: Vec<Change<'_>>) -> Vec<Change<'_>> {
+    changes
+        .into_iter()
+        .filter_map(|mut change| {
+            if !change.whitespace_only() {
+                return Some(change);
+            }
+
+            if change.tag() == ChangeTag::Delete {
+                None
+            } else {
+                change.retag(ChangeTag::Equal);
+                Some(change)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::classify_whitespace;
+    use crate::change::{Change, ChangeTag};
+
+    fn change(tag: ChangeTag, line: &str) -> Change<'_> {
+        Change::new(tag, vec![(false, line.into())])
+    }
+
+    #[test]
+    fn indentation_and_blank_lines_are_whitespace_only() {
+        let mut changes = vec![
+            change(ChangeTag::Equal, "fn a() {\n"),
+            change(ChangeTag::Delete, "x();\n"),
+            change(ChangeTag::Delete, "y();\n"),
+            change(ChangeTag::Insert, "\n"),
+            change(ChangeTag::Insert, "\tx();\n"),
+            change(ChangeTag::Insert, "z();\n"),
+            change(ChangeTag::Equal, "}\n"),
+        ];
+        classify_whitespace(&mut changes);
+
+        assert_eq!(
+            changes
+                .iter()
+                .map(Change::whitespace_only)
+                .collect::<Vec<_>>(),
+            vec![false, true, false, true, true, false, false]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How many columns some text takes up in a terminal, not counting escape
+/// codes
+pub(crate) fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('\u{1b}') {
+        width += rest[..start].width();
+        rest = after_escape(&rest[start + 1..]);
+    }
+
+    width + rest.width()
+}
+
+/// Some text with spaces after it, so it takes up at least `columns` columns
+pub(crate) fn pad(text: &str, columns: usize) -> String {
+    let padding = columns.saturating_sub(display_width(text));
+    format!("{text}{}", " ".repeat(padding))
+}
+
+/// Where to cut some text without escape codes so the part before the cut
+/// takes up no more than `columns` columns, or `None` if all of it fits
+pub(crate) fn fit_width(text: &str, columns: usize) -> Option<usize> {
+    let mut width = 0;
+
+    text.char_indices().find_map(|(index, character)| {
+        width += character.width().unwrap_or_default();
+        (width > columns).then_some(index)
+    })
+}
+
+/// The text after an escape sequence, given the text after its escape
+/// character
+fn after_escape(rest: &str) -> &str {
+    if let Some(sequence) = rest.strip_prefix('[') {
+        sequence
+            .find(|character: char| ('@'..='~').contains(&character))
+            .map_or("", |end| &sequence[end + 1..])
+    } else {
+        let next = rest.chars().next().map_or(0, char::len_utf8);
+        &rest[next..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{display_width, fit_width, pad};
+
+    #[test]
+    fn escape_codes_take_no_columns() {
+        assert_eq!(display_width("\u{1b}[31m→\u{1b}[0m"), 1);
+        assert_eq!(display_width("\u{1b}[1;38;5;9mé\u{1b}[0m x"), 3);
+        assert_eq!(display_width("\u{1b}é"), 0);
+        assert_eq!(display_width("a\u{1b}[31"), 1);
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(pad("\u{1b}[31m日\u{1b}[0m", 3), "\u{1b}[31m日\u{1b}[0m ");
+    }
+
+    #[test]
+    fn text_is_cut_before_the_character_that_does_not_fit() {
+        assert_eq!(fit_width("日本語", 3), Some(3));
+        assert_eq!(fit_width("日本語", 4), Some(6));
+        assert_eq!(fit_width("ab", 2), None);
+        assert_eq!(fit_width("e\u{301}x", 1), Some(3));
+    }
+}
@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::{
+    change::ChangeTag,
+    granularity::Granularity,
+    hunks::hunks,
+    inline::tokens,
+    intern::diff_lines,
+    lines::split_lines,
+    ops::from_similar,
+    result::DiffResult,
+    themes::{prefix_column, Theme},
+    width::{display_width, pad},
+};
+
+/// The words a diff removes and adds most often, for spotting changes made
+/// all over a text, like renaming `foo` to `bar`
+///
+/// The removed and added lines of each hunk are compared word by word, so
+/// only the words that actually changed are counted, not every word on a
+/// changed line. Words are split on whitespace, as they are for inline
+/// highlighting.
+///
+/// # Examples
+///
+/// ```
+/// use termdiff::{ArrowsTheme, DiffResult, WordFrequency};
+/// let theme = ArrowsTheme::default();
+/// let result = DiffResult::new("let foo = 1\nfoo + x\nfoo + y\n", "let bar = 1\nbar + z\nbar + y\n");
+/// let frequency = WordFrequency::new(&result);
+///
+/// assert_eq!(frequency.removed(), [("foo".to_string(), 3), ("x".to_string(), 1)]);
+/// assert_eq!(frequency.render(&theme, 1), "<foo 3\n>bar 3\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordFrequency {
+    removed: Vec<(String, usize)>,
+    added: Vec<(String, usize)>,
+}
+
+impl WordFrequency {
+    /// Count the words removed and added by a diff
+    #[must_use]
+    pub fn new(result: &DiffResult<'_>) -> Self {
+        let (old, new) = result.texts();
+        let old_lines: Vec<&str> = split_lines(old).collect();
+        let new_lines: Vec<&str> = split_lines(new).collect();
+        let mut removed = BTreeMap::new();
+        let mut added = BTreeMap::new();
+
+        for hunk in hunks(result.ops()) {
+            let old_words = tokens(&old_lines[hunk.old_range()], Granularity::Word);
+            let new_words = tokens(&new_lines[hunk.new_range()], Granularity::Word);
+
+            for op in from_similar(&diff_lines(&old_words, &new_words)) {
+                let (counts, words) = match op.tag() {
+                    ChangeTag::Equal => continue,
+                    ChangeTag::Delete | ChangeTag::MovedFrom => {
+                        (&mut removed, &old_words[op.old_range()])
+                    }
+                    ChangeTag::Insert | ChangeTag::MovedTo => {
+                        (&mut added, &new_words[op.new_range()])
+                    }
+                };
+
+                for word in words.iter().filter(|word| !word.trim().is_empty()) {
+                    *counts.entry(*word).or_insert(0) += 1;
+                }
+            }
+        }
+
+        Self {
+            removed: most_frequent(removed),
+            added: most_frequent(added),
+        }
+    }
+
+    /// The words that were removed, with how many times, most often first
+    #[must_use]
+    pub fn removed(&self) -> &[(String, usize)] {
+        &self.removed
+    }
+
+    /// The words that were added, with how many times, most often first
+    #[must_use]
+    pub fn added(&self) -> &[(String, usize)] {
+        &self.added
+    }
+
+    /// Draw up to `limit` of the most often removed words, then up to
+    /// `limit` of the most often added, one per line with their counts
+    #[must_use]
+    pub fn render(&self, theme: &dyn Theme, limit: usize) -> String {
+        let removed = &self.removed[..limit.min(self.removed.len())];
+        let added = &self.added[..limit.min(self.added.len())];
+        let width = removed
+            .iter()
+            .chain(added)
+            .map(|(word, _)| display_width(word))
+            .max()
+            .unwrap_or_default();
+        let prefix_width = theme.prefix_width();
+        let mut buffer = String::new();
+
+        for (word, count) in removed {
+            let _ = write!(
+                buffer,
+                "{}{} {count}{}",
+                prefix_column(theme, theme.delete_prefix(), prefix_width),
+                theme.delete_content(&pad(word, width)),
+                theme.line_end()
+            );
+        }
+        for (word, count) in added {
+            let _ = write!(
+                buffer,
+                "{}{} {count}{}",
+                prefix_column(theme, theme.insert_prefix(), prefix_width),
+                theme.insert_line(&pad(word, width)),
+                theme.line_end()
+            );
+        }
+
+        buffer
+    }
+}
+
+/// Words with their counts, most often first and then alphabetically
+fn most_frequent(counts: BTreeMap<&str, usize>) -> Vec<(String, usize)> {
+    let mut words: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(word, count)| (word.to_string(), count))
+        .collect();
+    words.sort_by(|(_, left), (_, right)| right.cmp(left));
+    words
+}
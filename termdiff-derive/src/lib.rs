@@ -0,0 +1,203 @@
+//! Derive [termdiff](https://docs.rs/termdiff) themes from the fields of a
+//! struct
+//!
+//! Use this through termdiff's `derive` feature, as `termdiff::Theme`.
+
+#![warn(
+    rust_2018_idioms,
+    unused,
+    rust_2021_compatibility,
+    nonstandard_style,
+    future_incompatible,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs
+)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields};
+
+/// Hooks that return text, which can be set from a field of any type that
+/// implements `ToString`
+const TEXT_HOOKS: [&str; 10] = [
+    "equal_prefix",
+    "delete_prefix",
+    "insert_prefix",
+    "header",
+    "line_end",
+    "trailing_lf_marker",
+    "hunk_separator",
+    "continuation_prefix",
+    "moved_from_prefix",
+    "moved_to_prefix",
+];
+
+/// Hooks with no default, which need a field
+const REQUIRED_HOOKS: [&str; 4] = ["equal_prefix", "delete_prefix", "insert_prefix", "header"];
+
+/// Colors for the content of lines, which can be set from a field of any
+/// type that converts into a crossterm `Color`
+const COLOR_FIELDS: [&str; 3] = ["equal_color", "delete_color", "insert_color"];
+
+/// Implement `Theme` for a struct, from its fields
+///
+/// Fields are used for the hook they are named after, or the one given with
+/// `#[theme(hook)]`. Other fields are left alone.
+///
+/// * `equal_prefix`, `delete_prefix`, `insert_prefix` and `header` are needed,
+///   and `line_end`, `trailing_lf_marker`, `hunk_separator`,
+///   `continuation_prefix`, `moved_from_prefix` and `moved_to_prefix` can be
+///   given too. They can be anything that implements `ToString`.
+/// * `equal_color`, `delete_color` and `insert_color` color the content of
+///   lines, and the changed parts of removed and added lines are underlined
+///   too. They can be anything that converts into a crossterm `Color`.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::style::{Color, ContentStyle, Stylize};
+/// use termdiff::{AnsiAssert, DrawDiff, Theme};
+///
+/// #[derive(Debug, Theme)]
+/// struct PlusMinus {
+///     equal_prefix: &'static str,
+///     delete_prefix: &'static str,
+///     insert_prefix: &'static str,
+///     #[theme(header)]
+///     title: String,
+///     delete_color: Color,
+/// }
+///
+/// let theme = PlusMinus {
+///     equal_prefix: " ",
+///     delete_prefix: "-",
+///     insert_prefix: "+",
+///     title: "--- old / +++ new\n".to_string(),
+///     delete_color: Color::Red,
+/// };
+///
+/// let output = AnsiAssert::new(&DrawDiff::new("a\nb\n", "a\nc\n", &theme).to_string());
+///
+/// assert_eq!(output.plain(), "--- old / +++ new\n a\n-b\n+c\n");
+/// output.assert_style("b", ContentStyle::new().red().underlined());
+/// ```
+#[proc_macro_derive(Theme, attributes(theme))]
+pub fn derive_theme(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            input,
+            "Theme can only be derived for structs",
+        ));
+    };
+    let fields: Vec<(Ident, Ident)> = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .filter_map(|field| hook(field).transpose())
+            .collect::<syn::Result<_>>()?,
+        _ => vec![],
+    };
+    let field_for = |hook: &str| {
+        fields
+            .iter()
+            .find(|(name, _)| name == hook)
+            .map(|(_, field)| field)
+    };
+
+    for hook in REQUIRED_HOOKS {
+        if field_for(hook).is_none() {
+            return Err(Error::new(
+                Span::call_site(),
+                format!("a field named `{hook}`, or marked `#[theme({hook})]`, is needed"),
+            ));
+        }
+    }
+
+    let mut methods = vec![];
+    for hook in TEXT_HOOKS {
+        if let Some(field) = field_for(hook) {
+            let hook = Ident::new(hook, Span::call_site());
+            methods.push(quote! {
+                fn #hook<'this>(&self) -> ::std::borrow::Cow<'this, str> {
+                    ::std::string::ToString::to_string(&self.#field).into()
+                }
+            });
+        }
+    }
+    for (color, content, highlight) in [
+        ("equal_color", "equal_content", None),
+        ("delete_color", "delete_content", Some("highlight_delete")),
+        ("insert_color", "insert_line", Some("highlight_insert")),
+    ] {
+        let Some(field) = field_for(color) else {
+            continue;
+        };
+        let content = Ident::new(content, Span::call_site());
+        methods.push(quote! {
+            fn #content<'this>(&self, input: &'this str) -> ::std::borrow::Cow<'this, str> {
+                ::termdiff::__private::Stylize::with(input, self.#field)
+                    .to_string()
+                    .into()
+            }
+        });
+        if let Some(highlight) = highlight {
+            let highlight = Ident::new(highlight, Span::call_site());
+            methods.push(quote! {
+                fn #highlight<'this>(&self, input: &'this str) -> ::std::borrow::Cow<'this, str> {
+                    ::termdiff::__private::Stylize::underlined(
+                        ::termdiff::__private::Stylize::with(input, self.#field),
+                    )
+                    .to_string()
+                    .into()
+                }
+            });
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::termdiff::Theme for #name #type_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
+
+/// The hook a field is for, and the field, if it is for one
+fn hook(field: &syn::Field) -> syn::Result<Option<(Ident, Ident)>> {
+    let Some(name) = field.ident.clone() else {
+        return Ok(None);
+    };
+    let mut hook = None;
+
+    for attribute in field
+        .attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("theme"))
+    {
+        let ident: Ident = attribute.parse_args()?;
+        if !TEXT_HOOKS.contains(&ident.to_string().as_str())
+            && !COLOR_FIELDS.contains(&ident.to_string().as_str())
+        {
+            return Err(Error::new_spanned(ident, "not a hook that can be derived"));
+        }
+        hook = Some(ident);
+    }
+
+    let hook = hook.unwrap_or_else(|| name.clone());
+    let known = TEXT_HOOKS.contains(&hook.to_string().as_str())
+        || COLOR_FIELDS.contains(&hook.to_string().as_str());
+
+    Ok(known.then_some((hook, name)))
+}
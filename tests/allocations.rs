@@ -0,0 +1,72 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::sink,
+};
+
+use termdiff::{diff, diff_with_scratch, ArrowsTheme, DiffScratch, DrawDiff};
+
+struct CountingAllocator {}
+
+thread_local! {
+    // Counted per thread so tests running alongside each other don't add to
+    // each other's counts
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator {};
+
+fn allocations(run: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    run();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn rendering_identical_text_does_not_allocate() {
+    let text = "fn main() {\n    println!(\"Hello, world!\");\r\n}\r".repeat(1_000);
+    let theme = ArrowsTheme::default();
+    let mut output = sink();
+
+    let allocated = allocations(|| diff(&mut output, &text, &text, &theme).unwrap());
+
+    assert_eq!(allocated, 0);
+}
+
+#[test]
+fn rendering_with_scratch_reuses_its_buffers() {
+    let old = "a\nb\nc\n".repeat(100);
+    let new = "a\nB\nc\n".repeat(100);
+    let theme = ArrowsTheme::default();
+    let mut scratch = DiffScratch::new();
+    let mut output = sink();
+    diff_with_scratch(&mut output, &old, &new, &theme, &mut scratch).unwrap();
+
+    let fresh = allocations(|| {
+        DrawDiff::new(&old, &new, &theme).render_into(&mut String::new());
+    });
+    let reused = allocations(|| {
+        diff_with_scratch(&mut output, &old, &new, &theme, &mut scratch).unwrap();
+    });
+
+    // The output, both sides' lines, the operations, the changes and which of
+    // them are shown
+    assert!(
+        fresh - reused >= 6,
+        "{:?} allocations with scratch buffers, {:?} without",
+        reused,
+        fresh
+    );
+}
@@ -0,0 +1,85 @@
+use proptest::prelude::*;
+use termdiff::{verify, ChangeTag, DiffOp, DiffResult, IncrementalDiff};
+
+/// Text made of a few short lines, so that diffs have plenty of equal lines
+fn text() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop::sample::select(vec!["a\n", "b\n", "c\r\n", "\n", "a"]),
+        0..12,
+    )
+    .prop_map(|lines| lines.concat())
+}
+
+/// Build the new text from the old text and the ops
+fn apply(old: &str, new: &str, ops: &[DiffOp]) -> String {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+
+    ops.iter()
+        .flat_map(|op| match op.tag() {
+            ChangeTag::Equal => old[op.old_range()].to_vec(),
+            ChangeTag::Insert | ChangeTag::MovedTo => new[op.new_range()].to_vec(),
+            ChangeTag::Delete | ChangeTag::MovedFrom => vec![],
+        })
+        .collect()
+}
+
+/// The ops that turn the new text back into the old text
+fn invert(ops: &[DiffOp]) -> Vec<DiffOp> {
+    ops.iter()
+        .map(|op| {
+            let tag = match op.tag() {
+                ChangeTag::Equal => ChangeTag::Equal,
+                ChangeTag::Delete => ChangeTag::Insert,
+                ChangeTag::Insert => ChangeTag::Delete,
+                ChangeTag::MovedFrom => ChangeTag::MovedTo,
+                ChangeTag::MovedTo => ChangeTag::MovedFrom,
+            };
+            DiffOp::new(tag, op.new_range(), op.old_range())
+        })
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn applying_the_diff_gives_the_new_text(old in text(), new in text()) {
+        let result = DiffResult::new(&old, &new);
+
+        prop_assert_eq!(verify(&old, &new, result.ops()), Ok(()));
+        prop_assert_eq!(apply(&old, &new, result.ops()), new.as_str());
+    }
+
+    #[test]
+    fn inverting_twice_changes_nothing(old in text(), new in text()) {
+        let result = DiffResult::new(&old, &new);
+        let inverted = invert(result.ops());
+
+        prop_assert_eq!(verify(&new, &old, &inverted), Ok(()));
+        prop_assert_eq!(apply(&new, &old, &inverted), old.as_str());
+        prop_assert_eq!(invert(&inverted), result.ops());
+    }
+
+    #[test]
+    fn incremental_updates_are_correct(old in text(), middle in text(), new in text()) {
+        let mut incremental = IncrementalDiff::new(&old, &middle);
+        incremental.update(&old, &new);
+
+        prop_assert_eq!(verify(&old, &new, incremental.ops()), Ok(()));
+        prop_assert_eq!(apply(&old, &new, incremental.ops()), new.as_str());
+    }
+}
+
+#[cfg(feature = "testgen")]
+proptest! {
+    #[test]
+    fn generated_pairs_diff_correctly(seed in any::<u64>(), lines in 0..200_usize, density in 0.0..=1.0_f64) {
+        let (old, new) = termdiff::testgen::Generator::new(seed)
+            .with_lines(lines)
+            .with_change_density(density)
+            .pair();
+        let result = DiffResult::new(&old, &new);
+
+        prop_assert_eq!(verify(&old, &new, result.ops()), Ok(()));
+        prop_assert_eq!(apply(&old, &new, result.ops()), new.as_str());
+    }
+}